@@ -0,0 +1,4767 @@
+use std::{
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, TryRecvError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{
+    layout::{Position, Rect},
+    widgets::ListState,
+};
+
+use crate::{
+    choices::{
+        self, Bootloader, DesktopOption, DriverOption, Filesystem, Firmware, MountPoint,
+        PartitionScheme, SwapConfig, DESKTOPS, PACKAGES,
+    },
+    config,
+    filter::filter_items,
+    install::{self, InstallEvent, InstallPhase},
+    retry::Backoff,
+    secret::Secret,
+    theme::Theme,
+    system::{
+        clock::TimeSyncError,
+        disks::{DiskInfo, Partition},
+        hardware::{GpuVendor, Hardware},
+        keymaps::DEFAULT_KEYMAP,
+        memory::{self, FALLBACK_SWAP_GIB},
+        mirrors::{self, Mirror},
+        network::CheckUpdate,
+        timezones::{self, FALLBACK_TIMEZONE},
+        wifi::WifiNetwork,
+    },
+    validate::{
+        validate_hostname, validate_package_name, validate_passphrase, validate_swap_size,
+        validate_username,
+    },
+    widgets::{log_buffer::LogBuffer, spinner::Spinner, text_input::TextInput},
+};
+
+/// The screens of the installer wizard, in the order they are visited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Welcome,
+    /// What the installer found out about the machine; nothing to choose.
+    Hardware,
+    /// Skipped when a wired connection is up.
+    Wifi,
+    Network,
+    Mirrors,
+    Language,
+    Locale,
+    Keyboard,
+    Timezone,
+    Disk,
+    PartitionScheme,
+    /// Only shown when the disk is erased.
+    Filesystem,
+    Swap,
+    Bootloader,
+    Hostname,
+    UserAccount,
+    Desktop,
+    /// Graphics drivers for the cards found on the hardware screen.
+    Graphics,
+    /// Extra packages ticked from a catalog.
+    Packages,
+    /// Any other packages, typed in by name.
+    ExtraPackages,
+    Summary,
+    Installing,
+    Completion,
+}
+
+impl Step {
+    /// Every step, in the order the wizard goes through them.
+    pub const ALL: [Step; 23] = [
+        Step::Welcome,
+        Step::Hardware,
+        Step::Wifi,
+        Step::Network,
+        Step::Mirrors,
+        Step::Language,
+        Step::Locale,
+        Step::Keyboard,
+        Step::Timezone,
+        Step::Disk,
+        Step::PartitionScheme,
+        Step::Filesystem,
+        Step::Swap,
+        Step::Bootloader,
+        Step::Hostname,
+        Step::UserAccount,
+        Step::Desktop,
+        Step::Graphics,
+        Step::Packages,
+        Step::ExtraPackages,
+        Step::Summary,
+        Step::Installing,
+        Step::Completion,
+    ];
+
+    /// Short name shown in the header's breadcrumb.
+    pub fn label(self) -> &'static str {
+        match self {
+            Step::Welcome => "Welcome",
+            Step::Hardware => "Hardware",
+            Step::Wifi => "Wi-Fi",
+            Step::Network => "Network",
+            Step::Mirrors => "Mirrors",
+            Step::Language => "Language",
+            Step::Locale => "Formats",
+            Step::Keyboard => "Keyboard",
+            Step::Timezone => "Timezone",
+            Step::Disk => "Disk",
+            Step::PartitionScheme => "Partitions",
+            Step::Filesystem => "Filesystem",
+            Step::Swap => "Swap",
+            Step::Bootloader => "Bootloader",
+            Step::Hostname => "Hostname",
+            Step::UserAccount => "User",
+            Step::Desktop => "Desktop",
+            Step::Graphics => "Graphics",
+            Step::Packages => "Packages",
+            Step::ExtraPackages => "Extras",
+            Step::Summary => "Summary",
+            Step::Installing => "Install",
+            Step::Completion => "Done",
+        }
+    }
+
+    /// The keys that do something on this step and what they do, for the
+    /// help overlay, given where typed letters go.
+    pub fn keybindings(self, typing: Typing) -> Vec<(&'static str, &'static str)> {
+        // Letters typed into a field or filter are not the vi keys or `q`.
+        let arrows = if typing == Typing::Keys { "↑/↓ or k/j" } else { "↑/↓" };
+        let list = [(arrows, "move the highlight"), ("PgUp/PgDn", "move a page at a time")];
+        let mut keys = match self {
+            Step::Wifi => {
+                vec![("Tab", "switch between the list and the passphrase"), ("s", "skip")]
+            }
+            Step::Network => vec![("r", "check the connection again")],
+            Step::Mirrors => vec![
+                ("Space", "tick the country; the mirrors of every ticked one are used"),
+                ("r", "rank the mirrors by speed with reflector"),
+            ],
+            Step::Language => vec![("type", "filter the list")],
+            Step::Locale => vec![
+                ("type", "filter the list"),
+                ("Space", "also generate the locale, e.g. to switch language later"),
+            ],
+            Step::Keyboard => vec![
+                ("type", "filter the list, or try out the layout in the test field"),
+                ("Tab", "switch between the list and the test field"),
+            ],
+            Step::Timezone => vec![("type", "filter the list")],
+            Step::Packages => {
+                vec![("type", "filter the list"), ("Space", "tick the package, or untick it")]
+            }
+            Step::Disk => vec![
+                ("e", "turn encryption on or off"),
+                ("Tab/Shift+Tab", "move between the list and the passphrase fields"),
+            ],
+            Step::Filesystem => vec![
+                ("s", "btrfs subvolumes on or off"),
+                ("n", "btrfs snapshots on or off"),
+            ],
+            Step::Swap if typing == Typing::Field => {
+                vec![("type", "set the size of the swap partition or file, e.g. 4G")]
+            }
+            Step::Swap => vec![],
+            Step::PartitionScheme => {
+                vec![("Space", "when partitioning by hand, change the partition's mount point")]
+            }
+            Step::UserAccount => vec![
+                ("Tab/Shift+Tab", "move between the fields"),
+                ("Space", "on the root field, lock root or give it a password"),
+            ],
+            Step::Summary => vec![
+                ("r", "check the connection again"),
+                ("c", "show or hide the commands that will run"),
+                ("f", "show or hide the fstab the new system gets"),
+                ("PgUp/PgDn", "scroll through the commands or the fstab"),
+            ],
+            Step::Installing => vec![
+                ("PgUp/PgDn", "scroll through the log"),
+                ("Esc", "cancel the installation before its next phase"),
+            ],
+            Step::Welcome
+            | Step::Hardware
+            | Step::Bootloader
+            | Step::Hostname
+            | Step::Desktop
+            | Step::Graphics => vec![],
+            Step::Completion => vec![("q/Ctrl+Q", "exit to the live environment")],
+            Step::ExtraPackages => vec![("type", "package names, separated by spaces")],
+        };
+        if matches!(
+            self,
+            Step::Wifi
+                | Step::Mirrors
+                | Step::Language
+                | Step::Locale
+                | Step::Keyboard
+                | Step::Timezone
+                | Step::Disk
+                | Step::PartitionScheme
+                | Step::Filesystem
+                | Step::Swap
+                | Step::Bootloader
+                | Step::Desktop
+                | Step::Graphics
+                | Step::Packages
+                | Step::Completion
+        ) {
+            keys.splice(0..0, list);
+        }
+        match self {
+            Step::Installing => {}
+            Step::Completion => keys.push(("Enter", "do the highlighted action")),
+            Step::Summary => keys.push(("Enter", "type the disk's name, then begin installing")),
+            Step::Wifi => keys.push(("Enter", "connect, or continue once connected")),
+            Step::Disk | Step::UserAccount => {
+                keys.push(("Enter", "go to the next field, or confirm on the last one"))
+            }
+            _ => keys.push(("Enter", "confirm and go to the next step")),
+        }
+        if !matches!(self, Step::Welcome | Step::Installing | Step::Completion) {
+            keys.extend(match typing {
+                Typing::Keys => vec![("Esc/←/Backspace", "go back")],
+                Typing::Field => vec![("Esc", "go back")],
+                Typing::Filter => vec![("Esc", "clear the filter, or go back"), ("←", "go back")],
+            });
+        }
+        let help = if typing == Typing::Field { "F1" } else { "?/F1" };
+        keys.extend([
+            (help, "show this help; any key closes it"),
+            ("F2", "switch to the next color theme"),
+            ("F3", "emoji-free titles and brighter hints on or off"),
+        ]);
+        match (self, typing) {
+            (Step::Completion, _) => {}
+            (_, Typing::Keys) => keys.push(("q/Ctrl+Q", "quit")),
+            _ => keys.push(("Ctrl+Q", "quit")),
+        }
+        keys
+    }
+
+    /// The step after this one, or `None` on the last step.
+    pub fn next(self) -> Option<Step> {
+        let step = match self {
+            Step::Welcome => Step::Hardware,
+            Step::Hardware => Step::Wifi,
+            Step::Wifi => Step::Network,
+            Step::Network => Step::Mirrors,
+            Step::Mirrors => Step::Language,
+            Step::Language => Step::Locale,
+            Step::Locale => Step::Keyboard,
+            Step::Keyboard => Step::Timezone,
+            Step::Timezone => Step::Disk,
+            Step::Disk => Step::PartitionScheme,
+            Step::PartitionScheme => Step::Filesystem,
+            Step::Filesystem => Step::Swap,
+            Step::Swap => Step::Bootloader,
+            Step::Bootloader => Step::Hostname,
+            Step::Hostname => Step::UserAccount,
+            Step::UserAccount => Step::Desktop,
+            Step::Desktop => Step::Graphics,
+            Step::Graphics => Step::Packages,
+            Step::Packages => Step::ExtraPackages,
+            Step::ExtraPackages => Step::Summary,
+            Step::Summary => Step::Installing,
+            Step::Installing => Step::Completion,
+            Step::Completion => return None,
+        };
+        Some(step)
+    }
+
+    /// The step before this one, or `None` on the first step and once
+    /// installation has started.
+    pub fn prev(self) -> Option<Step> {
+        let step = match self {
+            Step::Welcome => return None,
+            Step::Hardware => Step::Welcome,
+            Step::Wifi => Step::Hardware,
+            Step::Network => Step::Wifi,
+            Step::Mirrors => Step::Network,
+            Step::Language => Step::Mirrors,
+            Step::Locale => Step::Language,
+            Step::Keyboard => Step::Locale,
+            Step::Timezone => Step::Keyboard,
+            Step::Disk => Step::Timezone,
+            Step::PartitionScheme => Step::Disk,
+            Step::Filesystem => Step::PartitionScheme,
+            Step::Swap => Step::Filesystem,
+            Step::Bootloader => Step::Swap,
+            Step::Hostname => Step::Bootloader,
+            Step::UserAccount => Step::Hostname,
+            Step::Desktop => Step::UserAccount,
+            Step::Graphics => Step::Desktop,
+            Step::Packages => Step::Graphics,
+            Step::ExtraPackages => Step::Packages,
+            Step::Summary => Step::ExtraPackages,
+            // Nothing to go back to once the disk is being written.
+            Step::Installing | Step::Completion => return None,
+        };
+        Some(step)
+    }
+}
+
+/// Where typed letters go on the current screen, which decides what the
+/// other keys are left to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Typing {
+    /// Nowhere: letters like `q` and `j` are keys of their own.
+    Keys,
+    /// Into a text field, along with ←, → and Backspace.
+    Field,
+    /// Into the list's filter, along with Backspace.
+    Filter,
+}
+
+/// A display language offered on the language screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,
+    pub locale: &'static str,
+    /// Console keymap highlighted by default on the keyboard screen.
+    pub keymap: &'static str,
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        name: "English",
+        locale: "en_US.UTF-8",
+        keymap: "us",
+    },
+    Language {
+        name: "Français",
+        locale: "fr_FR.UTF-8",
+        keymap: "fr",
+    },
+    Language {
+        name: "Español",
+        locale: "es_ES.UTF-8",
+        keymap: "es",
+    },
+    Language {
+        name: "Deutsch",
+        locale: "de_DE.UTF-8",
+        keymap: "de",
+    },
+    Language {
+        name: "Italiano",
+        locale: "it_IT.UTF-8",
+        keymap: "it",
+    },
+    Language {
+        name: "Português (Brasil)",
+        locale: "pt_BR.UTF-8",
+        keymap: "br-abnt2",
+    },
+    Language {
+        name: "Nederlands",
+        locale: "nl_NL.UTF-8",
+        keymap: "nl",
+    },
+    Language {
+        name: "Polski",
+        locale: "pl_PL.UTF-8",
+        keymap: "pl",
+    },
+    Language {
+        name: "Svenska",
+        locale: "sv_SE.UTF-8",
+        keymap: "sv-latin1",
+    },
+    Language {
+        name: "Türkçe",
+        locale: "tr_TR.UTF-8",
+        keymap: "trq",
+    },
+    Language {
+        name: "Русский",
+        locale: "ru_RU.UTF-8",
+        keymap: "ru",
+    },
+    Language {
+        name: "日本語",
+        locale: "ja_JP.UTF-8",
+        keymap: "jp106",
+    },
+    Language {
+        name: "简体中文",
+        locale: "zh_CN.UTF-8",
+        keymap: "us",
+    },
+    Language {
+        name: "한국어",
+        locale: "ko_KR.UTF-8",
+        keymap: "us",
+    },
+];
+
+/// The answers collected by the wizard, independent of how they are shown.
+#[derive(Debug, Default)]
+pub struct InstallerState {
+    /// Index into [`LANGUAGES`].
+    pub language: usize,
+    /// Locale for numbers, dates, currency and measurements (the `LC_*`
+    /// settings), e.g. `de_DE.UTF-8`; without one they follow the language.
+    pub format_locale: Option<String>,
+    /// More locales for `locale-gen` to build, ticked on the formats screen,
+    /// for users who switch between languages.
+    pub extra_locales: Vec<String>,
+    /// Console keymap name, e.g. `de-latin1`.
+    pub keymap: Option<String>,
+    /// `Region/City` zone name, e.g. `Europe/Paris`.
+    pub timezone: Option<String>,
+    /// Device path of the installation target, e.g. `/dev/sda`.
+    pub disk: Option<String>,
+    pub hostname: Option<String>,
+    pub username: Option<String>,
+    pub full_name: Option<String>,
+    pub password: Option<Secret>,
+    /// Password for root. Without one root is locked and the user
+    /// administers the system with sudo.
+    pub root_password: Option<Secret>,
+    /// Index into [`DESKTOPS`].
+    pub desktop: usize,
+    /// Graphics drivers to install. Which ones fit depends on the machine,
+    /// so they are not saved with the answers; without any, only what the
+    /// desktop pulls in is installed.
+    pub driver: Option<DriverOption>,
+    /// Names of the packages ticked on the packages screen, in the order
+    /// they were ticked; installed along with everything else.
+    pub extra_packages: Vec<String>,
+    /// Packages typed in by name on the screen after, in the order given.
+    /// Names that cannot be package names are left out.
+    pub typed_packages: Vec<String>,
+    pub partition_scheme: PartitionScheme,
+    /// Partitions already on the disk and where they are mounted, when
+    /// partitioning by hand. They only exist on this machine, so they are
+    /// not saved with the answers.
+    pub mounts: Vec<(Partition, MountPoint)>,
+    /// Root filesystem; only used when the disk is erased.
+    pub filesystem: Filesystem,
+    /// Create the `@` and `@home` subvolumes on btrfs.
+    pub btrfs_subvolumes: bool,
+    /// Set up snapper snapshots of `@` on btrfs.
+    pub btrfs_snapshots: bool,
+    pub swap: SwapConfig,
+    /// Found at startup rather than asked; decides how the bootloader goes in.
+    pub firmware: Firmware,
+    pub bootloader: Bootloader,
+    /// Mirrorlist written into the new system after pacstrap; without one
+    /// it keeps the live system's.
+    pub mirrorlist: Option<String>,
+    /// Encrypt the root partition with LUKS. With manual partitioning the
+    /// user sets this up themselves, so it is left alone.
+    pub encryption: Option<EncryptionConfig>,
+}
+
+/// How the root partition is encrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptionConfig {
+    /// Asked for at every boot. Like the password, it is never saved.
+    pub passphrase: Secret,
+}
+
+impl InstallerState {
+    /// The locale matching the chosen display language, e.g. `en_US.UTF-8`.
+    pub fn locale(&self) -> &'static str {
+        LANGUAGES[self.language].locale
+    }
+
+    /// The locale numbers, dates and the like are shown in.
+    pub fn format_locale(&self) -> &str {
+        self.format_locale.as_deref().unwrap_or(self.locale())
+    }
+
+    /// Every locale `locale-gen` builds: the language's, the formats' and
+    /// the ticked ones, each once.
+    pub fn generated_locales(&self) -> Vec<&str> {
+        let mut locales = vec![self.locale()];
+        let ticked = self.extra_locales.iter().map(String::as_str);
+        for locale in std::iter::once(self.format_locale()).chain(ticked) {
+            if !locales.contains(&locale) {
+                locales.push(locale);
+            }
+        }
+        locales
+    }
+
+    pub fn desktop(&self) -> &'static DesktopOption {
+        &DESKTOPS[self.desktop]
+    }
+}
+
+/// What is known about internet access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Not checked yet, or a new check was asked for.
+    Unchecked,
+    Checking,
+    Online,
+    Offline,
+}
+
+/// Where setting the clock over NTP has got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSync {
+    /// Not tried yet, or trying again was asked for.
+    Unsynced,
+    Syncing,
+    Synced,
+    /// It did not work; the user is warned, but may install anyway.
+    Failed(String),
+}
+
+/// Where joining a wireless network has got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WifiStatus {
+    Idle,
+    /// Enter was pressed; the main loop starts connecting.
+    Requested,
+    /// Joining the network with this SSID.
+    Connecting(String),
+    /// Joined the network with this SSID.
+    Connected(String),
+    Failed(String),
+}
+
+/// Where ranking the mirrors with reflector has got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ranking {
+    Idle,
+    /// `r` was pressed; the main loop starts reflector.
+    Requested,
+    Running,
+    Failed(String),
+}
+
+/// A message shown over the current screen until the user dismisses it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Popup {
+    pub title: String,
+    pub message: String,
+    /// The step to go back to once the popup is dismissed.
+    pub back_to: Step,
+    /// What `r` does as well as dismissing it, if anything.
+    pub retry: Option<Retry>,
+}
+
+/// What can be tried again from a popup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// Check the internet connection again.
+    NetworkCheck,
+    /// Try synchronizing the clock again.
+    TimeSync,
+    /// Carry on installing from the command that failed, at this index of
+    /// the plan. Only `r` or `a` close this popup.
+    Install { from: usize },
+}
+
+/// What can be done from the completion screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishAction {
+    Reboot,
+    PowerOff,
+    /// A shell inside the new system, for finishing touches by hand.
+    Chroot,
+    ViewLog,
+    Exit,
+}
+
+impl FinishAction {
+    pub const ALL: [FinishAction; 5] = [
+        FinishAction::Reboot,
+        FinishAction::PowerOff,
+        FinishAction::Chroot,
+        FinishAction::ViewLog,
+        FinishAction::Exit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FinishAction::Reboot => "Reboot now",
+            FinishAction::PowerOff => "Power off",
+            FinishAction::Chroot => "Open a shell in the new system (arch-chroot)",
+            FinishAction::ViewLog => "View the installation log",
+            FinishAction::Exit => "Exit to the live environment",
+        }
+    }
+
+    /// The program and argument this runs once the terminal has been handed
+    /// back, if it runs one.
+    pub fn command(self) -> Option<[&'static str; 2]> {
+        match self {
+            FinishAction::Reboot => Some(["systemctl", "reboot"]),
+            FinishAction::PowerOff => Some(["systemctl", "poweroff"]),
+            FinishAction::Chroot => Some(["arch-chroot", install::TARGET]),
+            FinishAction::ViewLog | FinishAction::Exit => None,
+        }
+    }
+}
+
+/// Where a list was last drawn, for mapping mouse clicks to its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListHitbox {
+    /// The rows inside the list's border.
+    pub area: Rect,
+    /// Index of the item in the first visible row.
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl ListHitbox {
+    /// The item drawn at `column`, `row`, if any.
+    pub fn index_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.area.contains(Position::new(column, row)) {
+            return None;
+        }
+        let index = self.offset + usize::from(row - self.area.y);
+        (index < self.len).then_some(index)
+    }
+}
+
+/// Everything the installer knows: where the user is in the wizard and
+/// what they have chosen so far.
+pub struct AppState {
+    pub step: Step,
+    pub spinner: Spinner,
+    pub theme: Theme,
+    /// Draw every theme plain with bright hints, for `--accessible` and
+    /// `--no-emoji`; F3 switches it.
+    pub accessible: bool,
+    /// Whether the spinner turns; `--accessible` shows a static label
+    /// instead, which screen readers don't keep announcing.
+    pub animate: bool,
+    pub installer: InstallerState,
+    /// UTF-8 locales offered on the formats screen.
+    pub locales: Vec<String>,
+    pub selected_locale: usize,
+    pub keymaps: Vec<String>,
+    pub selected_keymap: usize,
+    /// Scratch text typed on the keyboard screen to try out the layout.
+    pub keymap_test: TextInput,
+    /// Whether typing on the keyboard screen goes into [`Self::keymap_test`]
+    /// rather than the list's filter.
+    pub testing_keymap: bool,
+    /// The keymap last loaded into the console for trying out, if any.
+    pub loaded_keymap: Option<String>,
+    pub timezones: Vec<String>,
+    pub selected_region: usize,
+    /// The region whose cities are listed; `None` while picking a region.
+    pub timezone_region: Option<String>,
+    pub selected_timezone: usize,
+    /// Text typed on the language, formats or timezone screen to narrow its list.
+    pub filter: String,
+    pub disks: Vec<DiskInfo>,
+    /// Why disk detection failed, shown on the disk screen.
+    pub disk_error: Option<String>,
+    pub selected_disk: usize,
+    /// Whether encryption is switched on on the disk screen.
+    pub encrypt: bool,
+    pub passphrase: TextInput,
+    pub passphrase_confirm: TextInput,
+    /// What has focus on the disk screen: 0 for the list, 1 and 2 for the
+    /// passphrase fields.
+    pub disk_focus: usize,
+    /// Index into [`PartitionScheme::ALL`].
+    pub selected_scheme: usize,
+    /// Whether the partition screen has gone on from the schemes to giving
+    /// the disk's partitions mount points.
+    pub assigning_mounts: bool,
+    /// Partitions on the disk they were listed from, which is given with
+    /// them so a different disk gets listed again.
+    pub partitions: Vec<Partition>,
+    pub partitions_disk: Option<String>,
+    /// Why listing the partitions failed.
+    pub partitions_error: Option<String>,
+    /// Mount point given to each of `partitions`, if any.
+    pub partition_mounts: Vec<Option<MountPoint>>,
+    pub selected_partition: usize,
+    /// Index into [`Filesystem::ALL`].
+    pub selected_filesystem: usize,
+    /// Index into [`AppState::swap_options`].
+    pub selected_swap: usize,
+    /// Size in GiB typed for a swap partition or file.
+    pub swap_size: TextInput,
+    /// What the swap size starts on, from the amount of memory.
+    pub suggested_swap_gib: u32,
+    /// Shown on the hardware screen.
+    pub hardware: Hardware,
+    /// Index into [`Bootloader::ALL`]. Those this machine's firmware cannot
+    /// start are shown greyed out and never highlighted.
+    pub selected_bootloader: usize,
+    /// Index into the drivers offered for this machine's graphics cards.
+    pub selected_driver: usize,
+    /// Index into [`PACKAGES`].
+    pub selected_package: usize,
+    /// Index into [`FinishAction::ALL`].
+    pub selected_action: usize,
+    /// Space-separated names of more packages to install.
+    pub package_names: TextInput,
+    pub hostname: TextInput,
+    pub username: TextInput,
+    pub full_name: TextInput,
+    pub password: TextInput,
+    pub password_confirm: TextInput,
+    /// Whether root gets a password of its own rather than being locked.
+    pub separate_root: bool,
+    pub root_password: TextInput,
+    pub root_password_confirm: TextInput,
+    /// Which field of the user account screen has focus: an index into
+    /// [`ACCOUNT_FIELDS`], then the root toggle, then [`ROOT_FIELDS`].
+    pub account_focus: usize,
+    /// Accounts already on the live system, whose names can't be taken.
+    pub existing_users: Vec<String>,
+    /// Where to save the answers when the summary is confirmed, if anywhere.
+    pub answers_path: Option<PathBuf>,
+    /// Where to export the selections as JSON at the same time, if anywhere.
+    pub export_path: Option<PathBuf>,
+    /// Why saving the answers failed, shown on the summary screen.
+    pub answers_error: Option<String>,
+    /// Overall installation progress, from 0.0 to 1.0.
+    pub progress: f64,
+    pub phase: InstallPhase,
+    /// Latest status message from the installation thread.
+    pub install_message: String,
+    /// Every task the installation has reported so far, oldest first.
+    pub install_log: LogBuffer,
+    /// The commands the installation will run, while they are shown on the
+    /// summary screen instead of the answers.
+    pub command_review: Option<LogBuffer>,
+    /// The fstab the installation leads to, while it is shown likewise.
+    pub fstab_review: Option<LogBuffer>,
+    /// Events from the running installation, if one is attached.
+    pub install_rx: Option<Receiver<InstallEvent>>,
+    /// When the running installation was attached and how far along it
+    /// already was, for the elapsed time and the estimate of what is left.
+    pub install_started: Option<(Instant, f64)>,
+    /// Index of the command the next installation run starts at; past 0
+    /// after a failed command is retried.
+    pub install_from: usize,
+    /// How many times in a row the command at `install_from` has failed.
+    pub install_attempts: u32,
+    /// Whether a command of the installation has been started, so the disk
+    /// may have been changed, even if the installation stopped since.
+    pub install_ran: bool,
+    /// Failures of a retryable command after which no retry is offered.
+    pub max_install_attempts: u32,
+    /// How network checks and downloads are tried again before they count
+    /// as failed.
+    pub backoff: Backoff,
+    /// Why the installation failed; once set, nothing more is installed.
+    pub install_error: Option<String>,
+    /// Shared with the installation thread, which stops before its next
+    /// phase once this is set.
+    pub install_cancel: Arc<AtomicBool>,
+    /// Whether a wired connection was up at startup; Wi-Fi is not asked for then.
+    pub wired: bool,
+    /// Wireless networks in range, strongest first.
+    pub wifi_networks: Vec<WifiNetwork>,
+    /// Why scanning for wireless networks failed, shown on the Wi-Fi screen.
+    pub wifi_error: Option<String>,
+    pub selected_wifi: usize,
+    pub wifi_passphrase: TextInput,
+    /// What has focus on the Wi-Fi screen: 0 for the list, 1 for the passphrase.
+    pub wifi_focus: usize,
+    pub wifi_status: WifiStatus,
+    /// Outcome of the running connection attempt, if one is attached.
+    pub wifi_rx: Option<Receiver<Result<(), String>>>,
+    pub connectivity: Connectivity,
+    /// Mirrors in the live system's mirrorlist.
+    pub mirrors: Vec<Mirror>,
+    /// Why the mirrorlist could not be read, shown on the mirror screen.
+    pub mirror_error: Option<String>,
+    /// Index into the country filter: 0 for every country, then
+    /// [`mirrors::countries`] in order.
+    pub selected_country: usize,
+    /// Countries ticked on the mirror screen, whose mirrors are used
+    /// together. Without any, the highlighted entry decides.
+    pub picked_countries: Vec<String>,
+    /// Reflector's ranking for those countries, fastest first.
+    pub ranked_mirrors: Option<Vec<Mirror>>,
+    pub ranking: Ranking,
+    /// Reflector's answer, if it is running.
+    pub ranking_rx: Option<Receiver<Result<Vec<Mirror>, String>>>,
+    /// Answer from the running network check, if one is attached.
+    pub network_rx: Option<Receiver<CheckUpdate>>,
+    /// Which try of how many the running network check is on, once the
+    /// first has failed.
+    pub network_retry: Option<(u32, u32)>,
+    /// Whether the clock is set from the network yet, which is done on the
+    /// summary screen once online.
+    pub time_sync: TimeSync,
+    /// Answer of the running clock synchronization, if one is attached.
+    pub time_sync_rx: Option<Receiver<Result<(), TimeSyncError>>>,
+    /// Why the last network check failed.
+    pub network_error: Option<String>,
+    /// Scroll positions of the list screens; the selection itself lives above.
+    pub language_list: ListState,
+    pub locale_list: ListState,
+    pub desktop_list: ListState,
+    pub keymap_list: ListState,
+    pub timezone_list: ListState,
+    pub disk_list: ListState,
+    pub wifi_list: ListState,
+    pub country_list: ListState,
+    pub scheme_list: ListState,
+    pub partition_list: ListState,
+    pub filesystem_list: ListState,
+    pub swap_list: ListState,
+    pub bootloader_list: ListState,
+    pub driver_list: ListState,
+    pub package_list: ListState,
+    pub action_list: ListState,
+    /// The list on the last drawn screen, if it had one.
+    pub list_hitbox: Option<ListHitbox>,
+    /// Where the footer's Next button was last drawn, if it was shown.
+    pub next_button: Option<Rect>,
+    /// The list entry last clicked and when, for spotting double clicks.
+    pub last_click: Option<(Step, usize, Instant)>,
+    /// Error popup drawn over the current screen, if any.
+    pub popup: Option<Popup>,
+    /// Whether the key help overlay is shown.
+    pub show_help: bool,
+    /// Whether `q` was pressed and the user is being asked to confirm.
+    pub confirm_quit: bool,
+    /// Whether Esc was pressed during the installation and the user is
+    /// being asked to confirm cancelling it.
+    pub confirm_cancel: bool,
+    /// Asked for once the installation is confirmed on the summary screen:
+    /// the disk's name, typed out before anything is written to it.
+    pub erase_confirm: Option<TextInput>,
+    /// Rebooting or powering off, once picked on the completion screen
+    /// while the user is asked to confirm it.
+    pub confirm_finish: Option<FinishAction>,
+    /// Whether the installation log is shown over the completion screen.
+    pub viewing_log: bool,
+    /// What to run after quitting, once the terminal is back to normal.
+    pub finish: Option<FinishAction>,
+    pub should_quit: bool,
+    /// Whether anything shown has changed since the screen was last drawn.
+    pub dirty: bool,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        let mut hostname = TextInput::new();
+        hostname.set_value(DEFAULT_HOSTNAME);
+        let mut swap_size = TextInput::new();
+        swap_size.set_value(&format!("{FALLBACK_SWAP_GIB}G"));
+        Self {
+            step: Step::Welcome,
+            spinner: Spinner::braille(),
+            theme: Theme::default(),
+            accessible: false,
+            animate: true,
+            installer: InstallerState::default(),
+            locales: Vec::new(),
+            selected_locale: 0,
+            keymaps: Vec::new(),
+            selected_keymap: 0,
+            keymap_test: TextInput::new(),
+            testing_keymap: false,
+            loaded_keymap: None,
+            timezones: Vec::new(),
+            selected_region: 0,
+            timezone_region: None,
+            selected_timezone: 0,
+            filter: String::new(),
+            disks: Vec::new(),
+            disk_error: None,
+            selected_disk: 0,
+            encrypt: false,
+            passphrase: TextInput::masked(),
+            passphrase_confirm: TextInput::masked(),
+            disk_focus: 0,
+            selected_scheme: 0,
+            assigning_mounts: false,
+            partitions: Vec::new(),
+            partitions_disk: None,
+            partitions_error: None,
+            partition_mounts: Vec::new(),
+            selected_partition: 0,
+            selected_filesystem: 0,
+            selected_swap: 0,
+            swap_size,
+            suggested_swap_gib: FALLBACK_SWAP_GIB,
+            hardware: Hardware::default(),
+            selected_bootloader: 0,
+            selected_driver: 0,
+            selected_package: 0,
+            selected_action: 0,
+            package_names: TextInput::new(),
+            hostname,
+            username: TextInput::new(),
+            full_name: TextInput::new(),
+            password: TextInput::masked(),
+            password_confirm: TextInput::masked(),
+            separate_root: false,
+            root_password: TextInput::masked(),
+            root_password_confirm: TextInput::masked(),
+            account_focus: 0,
+            existing_users: Vec::new(),
+            answers_path: None,
+            export_path: None,
+            answers_error: None,
+            progress: 0.0,
+            phase: InstallPhase::Partition,
+            install_message: String::new(),
+            install_log: LogBuffer::with_capacity(INSTALL_LOG_LINES),
+            command_review: None,
+            fstab_review: None,
+            install_rx: None,
+            install_started: None,
+            install_from: 0,
+            install_attempts: 0,
+            install_ran: false,
+            max_install_attempts: install::MAX_ATTEMPTS,
+            backoff: Backoff::default(),
+            install_error: None,
+            install_cancel: Arc::default(),
+            wired: false,
+            wifi_networks: Vec::new(),
+            wifi_error: None,
+            selected_wifi: 0,
+            wifi_passphrase: TextInput::masked(),
+            wifi_focus: 0,
+            wifi_status: WifiStatus::Idle,
+            wifi_rx: None,
+            connectivity: Connectivity::Unchecked,
+            mirrors: Vec::new(),
+            mirror_error: None,
+            selected_country: 0,
+            picked_countries: Vec::new(),
+            ranked_mirrors: None,
+            ranking: Ranking::Idle,
+            ranking_rx: None,
+            network_rx: None,
+            network_retry: None,
+            time_sync: TimeSync::Unsynced,
+            time_sync_rx: None,
+            network_error: None,
+            language_list: ListState::default(),
+            locale_list: ListState::default(),
+            desktop_list: ListState::default(),
+            keymap_list: ListState::default(),
+            timezone_list: ListState::default(),
+            disk_list: ListState::default(),
+            wifi_list: ListState::default(),
+            country_list: ListState::default(),
+            scheme_list: ListState::default(),
+            partition_list: ListState::default(),
+            filesystem_list: ListState::default(),
+            swap_list: ListState::default(),
+            bootloader_list: ListState::default(),
+            driver_list: ListState::default(),
+            package_list: ListState::default(),
+            action_list: ListState::default(),
+            list_hitbox: None,
+            next_button: None,
+            last_click: None,
+            popup: None,
+            show_help: false,
+            confirm_quit: false,
+            confirm_cancel: false,
+            erase_confirm: None,
+            confirm_finish: None,
+            viewing_log: false,
+            finish: None,
+            should_quit: false,
+            dirty: true,
+        }
+    }
+
+    /// Highlight `keymap` on the keyboard screen if it is available.
+    fn highlight_keymap(&mut self, keymap: &str) {
+        if let Some(index) = self.keymaps.iter().position(|k| k == keymap) {
+            self.selected_keymap = index;
+        }
+    }
+
+    /// Start every screen on the answer saved in `config`, where it is still
+    /// available. Call this after the keymaps, timezones and disks are loaded.
+    pub fn prefill(&mut self, config: &config::Config) {
+        if let Some(language) = LANGUAGES.iter().position(|l| l.locale == config.language) {
+            self.installer.language = language;
+        }
+        if let Some(keymap) = &config.keymap {
+            if self.keymaps.contains(keymap) {
+                self.highlight_keymap(keymap);
+                self.installer.keymap = Some(keymap.clone());
+            }
+        }
+        let zone = config.timezone.as_deref();
+        if let Some(zone) = zone.filter(|zone| self.timezones.iter().any(|z| z == zone)) {
+            let region = zone.split_once('/').map_or(zone, |(region, _)| region);
+            let regions = timezones::regions(&self.timezones);
+            if let Some(index) = regions.iter().position(|&r| r == region) {
+                self.selected_region = index;
+            }
+            self.installer.timezone = Some(zone.to_string());
+        }
+        if let Some(index) = self.disks.iter().position(|disk| Some(disk.path()) == config.disk) {
+            self.selected_disk = index;
+            self.installer.disk = config.disk.clone();
+        }
+        if let Some(hostname) = &config.hostname {
+            self.hostname.set_value(hostname);
+        }
+        if let Some(username) = &config.username {
+            self.username.set_value(username);
+        }
+        if let Some(desktop) = DESKTOPS.iter().position(|d| d.name == config.desktop) {
+            self.installer.desktop = desktop;
+        }
+    }
+
+    /// Take the list of available keymaps, highlighting the default layout.
+    pub fn load_keymaps(&mut self, keymaps: Vec<String>) {
+        self.selected_keymap = keymaps
+            .iter()
+            .position(|keymap| keymap == DEFAULT_KEYMAP)
+            .unwrap_or(0);
+        self.keymaps = keymaps;
+    }
+
+    /// Take the list of locales, falling back to the display languages' own
+    /// if it could not be read.
+    pub fn load_locales(&mut self, locales: io::Result<Vec<String>>) {
+        self.locales = locales.ok().filter(|locales| !locales.is_empty()).unwrap_or_else(|| {
+            let mut locales: Vec<String> = LANGUAGES.iter().map(|l| l.locale.into()).collect();
+            locales.sort();
+            locales.dedup();
+            locales
+        });
+        self.highlight_locale(self.installer.locale());
+    }
+
+    /// Highlight `locale` on the formats screen if it is available.
+    fn highlight_locale(&mut self, locale: &str) {
+        if let Some(index) = self.locales.iter().position(|l| l == locale) {
+            self.selected_locale = index;
+        }
+    }
+
+    /// Take the zoneinfo listing, falling back to UTC if it could not be read.
+    pub fn load_timezones(&mut self, zones: io::Result<Vec<String>>) {
+        self.timezones = zones
+            .ok()
+            .filter(|zones| !zones.is_empty())
+            .unwrap_or_else(|| vec![FALLBACK_TIMEZONE.to_string()]);
+        self.selected_region = 0;
+        self.timezone_region = None;
+    }
+
+    /// Take the result of disk detection, remembering the error if it failed.
+    pub fn load_disks(&mut self, disks: io::Result<Vec<DiskInfo>>) {
+        match disks {
+            Ok(disks) => {
+                self.disks = disks;
+                self.disk_error = None;
+            }
+            Err(err) => {
+                log::error!("disk detection failed: {err}");
+                self.disks.clear();
+                self.disk_error = Some(err.to_string());
+            }
+        }
+        self.selected_disk = 0;
+    }
+
+    /// Which disk's partitions have to be listed before mount points can be
+    /// given to them, if any.
+    pub fn needs_partitions(&self) -> Option<String> {
+        let disk = self.installer.disk.as_ref()?;
+        (self.assigning_mounts && self.partitions_disk.as_ref() != Some(disk)).then(|| disk.clone())
+    }
+
+    /// Take the partitions listed on `disk`, starting on the mount points
+    /// given before, and remember the error if listing failed.
+    pub fn load_partitions(&mut self, disk: String, partitions: io::Result<Vec<Partition>>) {
+        match partitions {
+            Ok(partitions) => {
+                self.partitions = partitions;
+                self.partitions_error = None;
+            }
+            Err(err) => {
+                log::error!("cannot list the partitions on {disk}: {err}");
+                self.partitions.clear();
+                self.partitions_error = Some(err.to_string());
+            }
+        }
+        self.partition_mounts = self
+            .partitions
+            .iter()
+            .map(|partition| {
+                let path = partition.path();
+                let assigned = self.installer.mounts.iter().find(|(p, _)| p.path() == path);
+                assigned.map(|&(_, mount)| mount)
+            })
+            .collect();
+        self.partitions_disk = Some(disk);
+        self.selected_partition = 0;
+        self.dirty = true;
+    }
+
+    /// Size of the chosen disk in MiB, if it is known.
+    pub fn disk_size_mib(&self) -> Option<u64> {
+        let disk = self.installer.disk.as_deref()?;
+        self.disks.iter().find(|info| info.path() == disk)?.size_mib()
+    }
+
+    /// Take the result of the Wi-Fi scan, remembering the error if it failed.
+    pub fn load_wifi(&mut self, networks: io::Result<Vec<WifiNetwork>>) {
+        match networks {
+            Ok(networks) => {
+                self.wifi_networks = networks;
+                self.wifi_error = None;
+            }
+            Err(err) => {
+                log::warn!("wireless scan failed: {err}");
+                self.wifi_networks.clear();
+                self.wifi_error = Some(err.to_string());
+            }
+        }
+        self.selected_wifi = 0;
+    }
+
+    /// Keep what the processor and graphics probes found for the hardware
+    /// screen; failed probes are logged and shown as unknown.
+    pub fn load_hardware(&mut self, cpu: io::Result<String>, gpus: io::Result<Vec<GpuVendor>>) {
+        self.hardware.cpu = cpu.map_err(|err| log::warn!("cannot tell the processor: {err}")).ok();
+        self.hardware.gpus = gpus.unwrap_or_else(|err| {
+            log::warn!("cannot list the graphics cards: {err}");
+            Vec::new()
+        });
+        log::info!("processor: {:?}, graphics: {:?}", self.hardware.cpu, self.hardware.gpus);
+    }
+
+    /// Indices into [`Bootloader::ALL`] of the ones this machine can start.
+    fn usable_bootloaders(&self) -> Vec<usize> {
+        let firmware = self.installer.firmware;
+        (0..Bootloader::ALL.len()).filter(|&i| Bootloader::ALL[i].supports(firmware)).collect()
+    }
+
+    /// The graphics drivers offered for this machine, the default first.
+    pub fn driver_options(&self) -> &'static [DriverOption] {
+        choices::driver_options(&self.hardware.gpus)
+    }
+
+    /// Suggest a swap size for `memory` MiB of memory, or the fallback if
+    /// it could not be found out.
+    pub fn load_memory(&mut self, memory: io::Result<u64>) {
+        self.suggested_swap_gib = match memory {
+            Ok(memory) => {
+                self.hardware.memory_mib = Some(memory);
+                memory::suggested_swap_gib(memory)
+            }
+            Err(err) => {
+                log::warn!("cannot tell how much memory there is: {err}");
+                FALLBACK_SWAP_GIB
+            }
+        };
+        self.swap_size.set_value(&format!("{}G", self.suggested_swap_gib));
+    }
+
+    /// The swap options on the swap screen, sized as typed.
+    pub fn swap_options(&self) -> Vec<SwapConfig> {
+        let size = validate_swap_size(self.swap_size.value()).unwrap_or(self.suggested_swap_gib);
+        SwapConfig::available(self.installer.partition_scheme, size)
+    }
+
+    /// Take the live system's mirrorlist, remembering the error if it could
+    /// not be read.
+    pub fn load_mirrors(&mut self, mirrors: io::Result<Vec<Mirror>>) {
+        match mirrors {
+            Ok(mirrors) => {
+                self.mirrors = mirrors;
+                self.mirror_error = None;
+            }
+            Err(err) => {
+                log::warn!("cannot read the mirrorlist: {err}");
+                self.mirrors.clear();
+                self.mirror_error = Some(err.to_string());
+            }
+        }
+        self.selected_country = 0;
+        self.picked_countries.clear();
+        self.ranked_mirrors = None;
+    }
+
+    /// The countries the mirror screen is filtered to: the ticked ones, or
+    /// else the highlighted one. None means every country.
+    pub fn mirror_countries(&self) -> Vec<&str> {
+        if !self.picked_countries.is_empty() {
+            return self.picked_countries.iter().map(String::as_str).collect();
+        }
+        self.highlighted_country().into_iter().collect()
+    }
+
+    /// The country highlighted on the mirror screen, unless it is "All
+    /// countries".
+    fn highlighted_country(&self) -> Option<&str> {
+        let index = self.selected_country.checked_sub(1)?;
+        mirrors::countries(&self.mirrors).get(index).copied()
+    }
+
+    /// Tick the highlighted country, or untick it if it already is. "All
+    /// countries" unticks every one.
+    fn toggle_country(&mut self) {
+        match self.highlighted_country().map(String::from) {
+            None => self.picked_countries.clear(),
+            Some(country) => match self.picked_countries.iter().position(|c| *c == country) {
+                Some(index) => {
+                    self.picked_countries.remove(index);
+                }
+                None => self.picked_countries.push(country),
+            },
+        }
+        // The ranking is for the countries it was run for.
+        self.ranked_mirrors = None;
+        self.ranking = Ranking::Idle;
+    }
+
+    /// Tick the highlighted locale to be generated too, or untick it if it
+    /// already is. Does nothing while the filter hides it.
+    fn toggle_locale(&mut self) {
+        if !self.filtered_indices().is_some_and(|shown| shown.contains(&self.selected_locale)) {
+            return;
+        }
+        let locale = &self.locales[self.selected_locale];
+        let extra = &mut self.installer.extra_locales;
+        match extra.iter().position(|ticked| ticked == locale) {
+            Some(index) => {
+                extra.remove(index);
+            }
+            None => extra.push(locale.clone()),
+        }
+    }
+
+    /// Tick the highlighted package, or untick it if it already is. Does
+    /// nothing while the filter hides it.
+    fn toggle_package(&mut self) {
+        if !self.filtered_indices().is_some_and(|shown| shown.contains(&self.selected_package)) {
+            return;
+        }
+        let name = PACKAGES[self.selected_package].name;
+        let extra = &mut self.installer.extra_packages;
+        match extra.iter().position(|package| package == name) {
+            Some(index) => {
+                extra.remove(index);
+            }
+            None => extra.push(name.to_string()),
+        }
+    }
+
+    /// The mirrors the mirror screen offers: reflector's ranking if it has
+    /// run, otherwise those of the chosen countries, active ones first.
+    pub fn shown_mirrors(&self) -> Vec<Mirror> {
+        if let Some(ranked) = &self.ranked_mirrors {
+            return ranked.clone();
+        }
+        let countries = self.mirror_countries();
+        let mut shown: Vec<Mirror> = self
+            .mirrors
+            .iter()
+            .filter(|mirror| {
+                countries.is_empty()
+                    || mirror.country.as_deref().is_some_and(|c| countries.contains(&c))
+            })
+            .cloned()
+            .collect();
+        shown.sort_by_key(|mirror| !mirror.active);
+        shown
+    }
+
+    pub fn advance(&mut self) {
+        let Some(next) = self.step.next() else {
+            return;
+        };
+        self.leave_step();
+        let from = self.step;
+        self.step = next;
+        if self.skips(self.step) {
+            self.step = self.step.next().unwrap_or(self.step);
+        }
+        log::info!("step {from:?} -> {:?}", self.step);
+        if let (Step::Disk, Some(error)) = (self.step, &self.disk_error) {
+            self.popup = Some(Popup {
+                title: "Disk detection failed".into(),
+                message: error.clone(),
+                back_to: from,
+                retry: None,
+            });
+        }
+        // Check again right before installing; the network may have gone
+        // since the network screen.
+        if self.step == Step::Summary && self.connectivity != Connectivity::Checking {
+            self.connectivity = Connectivity::Unchecked;
+        }
+        // The options depend on the partition scheme, which may have changed.
+        if self.step == Step::Swap {
+            let options = self.swap_options();
+            let current = options.iter().position(|swap| swap.same_kind(self.installer.swap));
+            self.selected_swap = current.unwrap_or(0);
+        }
+        if self.step == Step::Bootloader {
+            let usable = self.usable_bootloaders();
+            let current = usable.iter().find(|&&i| Bootloader::ALL[i] == self.installer.bootloader);
+            self.selected_bootloader = current.or(usable.first()).copied().unwrap_or(0);
+        }
+        if self.step == Step::Installing {
+            self.report_progress(InstallPhase::Partition, 0.0);
+            self.install_message.clear();
+            self.install_log.clear();
+            self.install_error = None;
+            self.install_from = 0;
+            self.install_attempts = 0;
+        }
+    }
+
+    pub fn back(&mut self) {
+        // Within the timezone step, go from the city list back to the regions.
+        if self.step == Step::Timezone && self.timezone_region.take().is_some() {
+            self.filter.clear();
+            return;
+        }
+        // Likewise from the mount points back to the schemes.
+        if self.step == Step::PartitionScheme && self.assigning_mounts {
+            self.assigning_mounts = false;
+            return;
+        }
+        let Some(prev) = self.step.prev() else {
+            return;
+        };
+        self.leave_step();
+        let from = self.step;
+        self.step = prev;
+        if self.skips(self.step) {
+            self.step = self.step.prev().unwrap_or(self.step);
+        }
+        log::info!("step {from:?} -> {:?} (back)", self.step);
+    }
+
+    /// Whether `step` does not apply to the answers given so far.
+    pub fn skips(&self, step: Step) -> bool {
+        match step {
+            Step::Wifi => self.wired,
+            Step::Filesystem => self.installer.partition_scheme != PartitionScheme::Erase,
+            _ => false,
+        }
+    }
+
+    /// Write the answers to `answers_path` and `export_path`, where set.
+    fn save_answers(&self) -> Result<(), String> {
+        let failed = |path: &PathBuf, err: io::Error| {
+            format!("Could not save answers to {}: {err}", path.display())
+        };
+        if let Some(path) = &self.answers_path {
+            config::save_config(&self.installer, path).map_err(|err| failed(path, err))?;
+        }
+        if let Some(path) = &self.export_path {
+            config::Config::from_state(&self.installer)
+                .save_json(path)
+                .map_err(|err| failed(path, err))?;
+        }
+        Ok(())
+    }
+
+    /// Clean up after the current step before moving to another one.
+    fn leave_step(&mut self) {
+        self.filter.clear();
+        match self.step {
+            // The password has been copied into `installer` if it was accepted;
+            // don't keep a second copy in the fields.
+            Step::UserAccount => {
+                self.password.zeroize();
+                self.password_confirm.zeroize();
+                self.root_password.zeroize();
+                self.root_password_confirm.zeroize();
+            }
+            Step::Disk => {
+                self.passphrase.zeroize();
+                self.passphrase_confirm.zeroize();
+            }
+            Step::Wifi => self.wifi_passphrase.zeroize(),
+            // Try saving again next time the summary is confirmed.
+            Step::Summary => {
+                self.answers_error = None;
+                self.command_review = None;
+                self.fstab_review = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Record how far the installation has got.
+    pub fn report_progress(&mut self, phase: InstallPhase, progress: f64) {
+        self.phase = phase;
+        self.progress = progress.clamp(0.0, 1.0);
+    }
+
+    pub fn on_enter(&mut self) {
+        // Nothing to pick when the filter hides every entry.
+        if let (Some(visible), Some(selected)) = (self.filtered_indices(), self.selected()) {
+            if !visible.contains(&selected) {
+                return;
+            }
+        }
+        match self.step {
+            Step::Completion => {
+                let action = FinishAction::ALL[self.selected_action];
+                log::info!("finishing with {action:?}");
+                match action {
+                    FinishAction::Reboot | FinishAction::PowerOff => {
+                        self.confirm_finish = Some(action)
+                    }
+                    FinishAction::ViewLog => self.viewing_log = true,
+                    FinishAction::Chroot | FinishAction::Exit => {
+                        self.finish = Some(action);
+                        self.should_quit = true;
+                    }
+                }
+                return;
+            }
+            // The only way into the irreversible part of the installation.
+            Step::Summary => {
+                match self.connectivity {
+                    Connectivity::Online => {}
+                    // The popup comes up if the answer is bad.
+                    Connectivity::Unchecked | Connectivity::Checking => return,
+                    Connectivity::Offline => return self.show_offline_popup(),
+                }
+                match self.time_sync {
+                    // A clock that would not sync has been warned about.
+                    TimeSync::Synced | TimeSync::Failed(_) => {}
+                    TimeSync::Unsynced | TimeSync::Syncing => return,
+                }
+                // A failed save is reported once; confirming again installs anyway.
+                if self.answers_error.is_none() {
+                    if let Err(error) = self.save_answers() {
+                        log::error!("{error}");
+                        self.answers_error = Some(error);
+                        return;
+                    }
+                }
+                if let Some(name) = self.disk_name() {
+                    match &self.erase_confirm {
+                        None => {
+                            self.erase_confirm = Some(TextInput::new());
+                            return;
+                        }
+                        Some(typed) if typed.value() != name => return,
+                        Some(_) => {}
+                    }
+                }
+                self.erase_confirm = None;
+                return self.advance();
+            }
+            Step::Installing => return,
+            Step::Mirrors => {
+                if self.ranking_rx.is_some() {
+                    return;
+                }
+                let shown = self.shown_mirrors();
+                self.installer.mirrorlist =
+                    (!shown.is_empty()).then(|| mirrors::render_mirrorlist(&shown));
+            }
+            Step::Wifi => {
+                let connected = match &self.wifi_status {
+                    WifiStatus::Connected(ssid) => Some(ssid),
+                    WifiStatus::Requested | WifiStatus::Connecting(_) => return,
+                    WifiStatus::Idle | WifiStatus::Failed(_) => None,
+                };
+                let highlighted = self.wifi_networks.get(self.selected_wifi);
+                // With nothing to join, carry on; the network check will tell.
+                if highlighted.is_some_and(|network| Some(&network.ssid) != connected) {
+                    self.wifi_status = WifiStatus::Requested;
+                    return;
+                }
+            }
+            // Suggest the formats and layout that go with the language until
+            // they are chosen.
+            Step::Language => {
+                if self.installer.format_locale.is_none() {
+                    self.highlight_locale(self.installer.locale());
+                }
+                if self.installer.keymap.is_none() {
+                    self.highlight_keymap(LANGUAGES[self.installer.language].keymap);
+                }
+            }
+            Step::Locale => match self.locales.get(self.selected_locale) {
+                Some(locale) => self.installer.format_locale = Some(locale.clone()),
+                None => return,
+            },
+            Step::Keyboard => match self.keymaps.get(self.selected_keymap) {
+                Some(keymap) => self.installer.keymap = Some(keymap.clone()),
+                None => return,
+            },
+            Step::Timezone => match &self.timezone_region {
+                None => {
+                    let regions = timezones::regions(&self.timezones);
+                    let Some(region) = regions.get(self.selected_region) else {
+                        return;
+                    };
+                    let cities = timezones::cities(&self.timezones, region);
+                    if cities.is_empty() {
+                        self.installer.timezone = Some(region.to_string());
+                    } else {
+                        // Start on the previously chosen city if it is in this region.
+                        self.selected_timezone = self
+                            .installer
+                            .timezone
+                            .as_deref()
+                            .and_then(|zone| zone.strip_prefix(region)?.strip_prefix('/'))
+                            .and_then(|city| cities.iter().position(|&c| c == city))
+                            .unwrap_or(0);
+                        self.timezone_region = Some(region.to_string());
+                        self.filter.clear();
+                        return;
+                    }
+                }
+                Some(region) => {
+                    let cities = timezones::cities(&self.timezones, region);
+                    let Some(city) = cities.get(self.selected_timezone) else {
+                        return;
+                    };
+                    self.installer.timezone = Some(format!("{region}/{city}"));
+                }
+            },
+            Step::Disk => {
+                let Some(disk) = self.disks.get(self.selected_disk) else {
+                    return;
+                };
+                if self.encryption_error().is_some() {
+                    return;
+                }
+                self.installer.disk = Some(disk.path());
+                self.installer.encryption = self.encrypt.then(|| EncryptionConfig {
+                    passphrase: Secret::from(self.passphrase.value()),
+                });
+            }
+            Step::PartitionScheme if self.assigning_mounts => {
+                if self.mount_error().is_some() {
+                    return;
+                }
+                self.installer.mounts = self
+                    .partitions
+                    .iter()
+                    .zip(&self.partition_mounts)
+                    .filter_map(|(partition, mount)| Some((partition.clone(), (*mount)?)))
+                    .collect();
+            }
+            Step::PartitionScheme => {
+                let scheme = PartitionScheme::ALL[self.selected_scheme];
+                if scheme == PartitionScheme::Erase && self.erase_error().is_some() {
+                    return;
+                }
+                self.installer.partition_scheme = scheme;
+                if scheme == PartitionScheme::Manual {
+                    self.assigning_mounts = true;
+                    return;
+                }
+            }
+            Step::Filesystem => {
+                if self.filesystem_error().is_some() {
+                    return;
+                }
+                self.installer.filesystem = Filesystem::ALL[self.selected_filesystem];
+            }
+            Step::Swap => {
+                if self.swap_error().is_some() {
+                    return;
+                }
+                self.installer.swap = self.swap_options()[self.selected_swap];
+            }
+            Step::Bootloader => {
+                let bootloader = Bootloader::ALL[self.selected_bootloader];
+                if !bootloader.supports(self.installer.firmware) {
+                    return;
+                }
+                self.installer.bootloader = bootloader;
+            }
+            Step::Graphics => {
+                self.installer.driver = Some(self.driver_options()[self.selected_driver]);
+            }
+            Step::ExtraPackages => {
+                let names = self.package_names.value().split_whitespace();
+                self.installer.typed_packages = names
+                    .filter(|name| validate_package_name(name).is_ok())
+                    .map(String::from)
+                    .collect();
+            }
+            Step::Hostname => {
+                let hostname = self.hostname.value();
+                if validate_hostname(hostname).is_err() {
+                    return;
+                }
+                self.installer.hostname = Some(hostname.to_string());
+            }
+            Step::UserAccount => {
+                if self.account_error().is_some() {
+                    return;
+                }
+                self.installer.username = Some(self.username.value().to_string());
+                self.installer.full_name = Some(self.full_name.value().to_string())
+                    .filter(|name| !name.is_empty());
+                self.installer.password = Some(Secret::from(self.password.value()));
+                self.installer.root_password =
+                    self.separate_root.then(|| Secret::from(self.root_password.value()));
+            }
+            _ => {}
+        }
+        self.advance();
+    }
+
+    /// The name of the disk installed to, e.g. `sda`, which has to be typed
+    /// out before it is written to.
+    pub fn disk_name(&self) -> Option<&str> {
+        let disk = self.installer.disk.as_deref()?;
+        Some(disk.rsplit('/').next().unwrap_or(disk))
+    }
+
+    /// Why the highlighted filesystem cannot be used with the chosen
+    /// options, if it can't.
+    pub fn filesystem_error(&self) -> Option<&'static str> {
+        let installer = &self.installer;
+        if Filesystem::ALL[self.selected_filesystem] != Filesystem::Btrfs {
+            return None;
+        }
+        if installer.btrfs_snapshots && !installer.btrfs_subvolumes {
+            return Some("Snapshots need the @ and @home subvolume layout");
+        }
+        None
+    }
+
+    /// Why the chosen disk is too small to be erased for the system, if it is.
+    pub fn erase_error(&self) -> Option<&'static str> {
+        let firmware = self.installer.firmware;
+        let layout = install::auto_layout(firmware, SwapConfig::None, self.disk_size_mib());
+        install::root_too_small(&layout).then_some("The disk is too small for the system")
+    }
+
+    /// Why the mount points given to the disk's partitions cannot be used,
+    /// if they can't.
+    pub fn mount_error(&self) -> Option<&'static str> {
+        if self.partitions.is_empty() {
+            return Some("The disk has no partitions to install to");
+        }
+        let assigned: Vec<MountPoint> = self.partition_mounts.iter().flatten().copied().collect();
+        if !assigned.contains(&MountPoint::Root) {
+            return Some("One partition has to be mounted at /");
+        }
+        // Several swap partitions are fine; anything else needs a place of its own.
+        let twice = MountPoint::ALL.iter().filter(|&&mount| mount != MountPoint::Swap).any(|mount| {
+            assigned.iter().filter(|m| *m == mount).count() > 1
+        });
+        if twice {
+            return Some("Two partitions are mounted at the same place");
+        }
+        if self.installer.firmware == Firmware::Uefi && !assigned.contains(&MountPoint::Boot) {
+            return Some("UEFI needs the EFI system partition mounted at /boot");
+        }
+        None
+    }
+
+    /// Why the highlighted swap option cannot be used as typed, if it can't.
+    pub fn swap_error(&self) -> Option<&'static str> {
+        let highlighted = self.swap_options()[self.selected_swap];
+        if highlighted.size_gib().is_some() {
+            if let Err(err) = validate_swap_size(self.swap_size.value()) {
+                return Some(err);
+            }
+        }
+        if self.installer.partition_scheme == PartitionScheme::Erase {
+            let layout =
+                install::auto_layout(self.installer.firmware, highlighted, self.disk_size_mib());
+            if install::root_too_small(&layout) {
+                return Some("Too little room would be left for the system");
+            }
+        }
+        // Snapshots of a subvolume holding an active swap file fail, so it
+        // gets a subvolume of its own.
+        if matches!(highlighted, SwapConfig::File { .. })
+            && self.installer.filesystem == Filesystem::Btrfs
+            && !self.installer.btrfs_subvolumes
+        {
+            return Some("A swap file on btrfs needs the subvolume layout");
+        }
+        None
+    }
+
+    /// Why the disk screen's passphrase cannot be used yet, if encryption
+    /// is on and it can't.
+    pub fn encryption_error(&self) -> Option<&'static str> {
+        if !self.encrypt {
+            return None;
+        }
+        if let Err(err) = validate_passphrase(self.passphrase.value()) {
+            return Some(err);
+        }
+        if self.passphrase.value() != self.passphrase_confirm.value() {
+            return Some("Passphrases do not match");
+        }
+        None
+    }
+
+    /// Why the user account screen cannot be submitted yet, if anything.
+    pub fn account_error(&self) -> Option<&'static str> {
+        if let Err(err) = validate_username(self.username.value()) {
+            return Some(err);
+        }
+        if self.existing_users.iter().any(|user| user == self.username.value()) {
+            return Some("Username is taken by a system account");
+        }
+        if self.password.value().is_empty() {
+            return Some("Password cannot be empty");
+        }
+        if self.password.value() != self.password_confirm.value() {
+            return Some("Passwords do not match");
+        }
+        if self.separate_root {
+            if self.root_password.value().is_empty() {
+                return Some("Root password cannot be empty");
+            }
+            if self.root_password.value() != self.root_password_confirm.value() {
+                return Some("Root passwords do not match");
+            }
+        }
+        None
+    }
+
+    /// How many places focus moves through on the user account screen.
+    fn account_focus_count(&self) -> usize {
+        let root_fields = if self.separate_root { ROOT_FIELDS.len() } else { 0 };
+        ACCOUNT_FIELDS.len() + 1 + root_fields
+    }
+
+    /// Whether focus is on the last of the current screen's fields, which
+    /// it always is on screens with only one.
+    fn on_last_field(&self) -> bool {
+        match self.step {
+            Step::UserAccount => self.account_focus + 1 == self.account_focus_count(),
+            Step::Disk if self.encrypt => self.disk_focus == 2,
+            _ => true,
+        }
+    }
+
+    /// The text field that receives typed characters on the current step.
+    fn focused_input(&mut self) -> Option<&mut TextInput> {
+        self.focused_field().map(|field| field(self))
+    }
+
+    /// How to reach [`Self::focused_input`], without borrowing it.
+    fn focused_field(&self) -> Option<fn(&mut AppState) -> &mut TextInput> {
+        match self.step {
+            Step::Keyboard if self.testing_keymap => Some(|app| &mut app.keymap_test),
+            Step::Hostname => Some(|app| &mut app.hostname),
+            Step::ExtraPackages => Some(|app| &mut app.package_names),
+            Step::Swap if self.swap_options()[self.selected_swap].size_gib().is_some() => {
+                Some(|app| &mut app.swap_size)
+            }
+            Step::Wifi if self.wifi_focus == 1 => Some(|app| &mut app.wifi_passphrase),
+            Step::Disk if self.encrypt => match self.disk_focus {
+                0 => None,
+                1 => Some(|app| &mut app.passphrase),
+                _ => Some(|app| &mut app.passphrase_confirm),
+            },
+            Step::UserAccount => match self.account_focus {
+                0 => Some(|app| &mut app.username),
+                1 => Some(|app| &mut app.full_name),
+                2 => Some(|app| &mut app.password),
+                3 => Some(|app| &mut app.password_confirm),
+                // The root toggle.
+                4 => None,
+                5 => Some(|app| &mut app.root_password),
+                _ => Some(|app| &mut app.root_password_confirm),
+            },
+            _ => None,
+        }
+    }
+
+    /// What the quit confirmation warns of, given how far the installation got.
+    pub fn quit_warning(&self) -> &'static str {
+        if self.install_rx.is_some() {
+            "The installation is still running. Quitting now may leave the disk in an \
+             inconsistent state."
+        } else if self.install_ran {
+            "The installation did not finish, but the disk has already been changed. It may \
+             not boot until the installation is run through."
+        } else {
+            "Nothing has been written to disk yet."
+        }
+    }
+
+    /// Where typed letters go right now, as [`Self::on_key`] decides it.
+    pub fn typing(&self) -> Typing {
+        if self.focused_field().is_some() {
+            Typing::Field
+        } else if self.filtered_indices().is_some() {
+            Typing::Filter
+        } else {
+            Typing::Keys
+        }
+    }
+
+    /// The keys that do something on the current screen as it is now.
+    pub fn keybindings(&self) -> Vec<(&'static str, &'static str)> {
+        self.step.keybindings(self.typing())
+    }
+
+    /// A key pressed with its modifiers. Ctrl+Q and Ctrl+C quit from any
+    /// screen, text fields included; everything else goes to [`Self::on_key`].
+    pub fn on_key_event(&mut self, key: KeyEvent) {
+        let quit = matches!(key.code, KeyCode::Char('q' | 'c'));
+        if !(quit && key.modifiers.contains(KeyModifiers::CONTROL)) {
+            return self.on_key(key.code);
+        }
+        self.dirty = true;
+        self.show_help = false;
+        if self.step == Step::Completion {
+            self.should_quit = true;
+        } else {
+            self.confirm_quit = true;
+        }
+    }
+
+    pub fn on_key(&mut self, code: KeyCode) {
+        self.dirty = true;
+        if self.confirm_quit {
+            match code {
+                KeyCode::Char('y') => self.should_quit = true,
+                KeyCode::Char('n') | KeyCode::Esc => self.confirm_quit = false,
+                _ => {}
+            }
+            return;
+        }
+        if self.confirm_cancel {
+            match code {
+                KeyCode::Char('y') => {
+                    log::info!("cancelling the installation before its next phase");
+                    self.install_cancel.store(true, Ordering::SeqCst);
+                    self.confirm_cancel = false;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => self.confirm_cancel = false,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(action) = self.confirm_finish {
+            match code {
+                KeyCode::Char('y') => {
+                    self.finish = Some(action);
+                    self.should_quit = true;
+                }
+                KeyCode::Char('n') | KeyCode::Esc => self.confirm_finish = None,
+                _ => {}
+            }
+            return;
+        }
+        if self.viewing_log {
+            match code {
+                KeyCode::Up | KeyCode::PageUp => self.install_log.page_up(),
+                KeyCode::Down | KeyCode::PageDown => self.install_log.page_down(),
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.viewing_log = false,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(typed) = &mut self.erase_confirm {
+            match code {
+                KeyCode::Char(c) => typed.insert_char(c),
+                KeyCode::Backspace => typed.backspace(),
+                KeyCode::Enter => self.on_enter(),
+                KeyCode::Esc => self.erase_confirm = None,
+                _ => {}
+            }
+            return;
+        }
+        // Any key dismisses a popup.
+        if let Some(popup) = self.popup.take() {
+            match (popup.retry, code) {
+                (Some(Retry::Install { from }), KeyCode::Char('r')) => {
+                    log::info!("retrying the installation from command {from}");
+                    self.install_from = from;
+                    self.install_error = None;
+                    return;
+                }
+                (Some(Retry::Install { .. }), KeyCode::Char('a') | KeyCode::Esc) => {}
+                (Some(Retry::Install { .. }), _) => {
+                    self.popup = Some(popup);
+                    return;
+                }
+                (Some(Retry::NetworkCheck), KeyCode::Char('r')) => {
+                    self.connectivity = Connectivity::Unchecked;
+                }
+                (Some(Retry::TimeSync), KeyCode::Char('r')) => self.time_sync = TimeSync::Unsynced,
+                _ => {}
+            }
+            log::info!("step {:?} -> {:?} (popup dismissed)", self.step, popup.back_to);
+            self.step = popup.back_to;
+            return;
+        }
+        // Any key closes the help without doing anything else.
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        // Text fields take `?` like any other character, but not F1.
+        let help = match code {
+            KeyCode::F(1) => true,
+            KeyCode::Char('?') => self.focused_input().is_none(),
+            _ => false,
+        };
+        if help {
+            self.show_help = true;
+            return;
+        }
+        // Like F1, these work in text fields too.
+        let base = Theme::from_name(self.theme.name).unwrap_or_default();
+        match code {
+            KeyCode::F(2) => {
+                log::info!("theme switched to {}", base.next().name);
+                return self.set_theme(base.next());
+            }
+            KeyCode::F(3) => {
+                self.accessible = !self.accessible;
+                log::info!("accessible drawing {}", if self.accessible { "on" } else { "off" });
+                return self.set_theme(base);
+            }
+            _ => {}
+        }
+        if let Some(input) = self.focused_input() {
+            match code {
+                KeyCode::Char(c) => return input.insert_char(c),
+                KeyCode::Backspace => return input.backspace(),
+                KeyCode::Left => return input.move_left(),
+                KeyCode::Right => return input.move_right(),
+                KeyCode::Home => return input.move_home(),
+                KeyCode::End => return input.move_end(),
+                _ => {}
+            }
+        }
+        if self.filtered_indices().is_some() {
+            match code {
+                KeyCode::Char(' ') if self.step == Step::Packages => return self.toggle_package(),
+                KeyCode::Char(' ') if self.step == Step::Locale => return self.toggle_locale(),
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace if !self.filter.is_empty() => {
+                    self.filter.pop();
+                }
+                KeyCode::Esc if !self.filter.is_empty() => self.filter.clear(),
+                _ => return self.on_list_key(code),
+            }
+            return self.clamp_to_filter();
+        }
+        self.on_list_key(code);
+    }
+
+    /// Keys shared by every screen once text entry has had its turn.
+    fn on_list_key(&mut self, code: KeyCode) {
+        match code {
+            // Nothing is left to lose once the installation is done.
+            KeyCode::Char('q') if self.step == Step::Completion => self.should_quit = true,
+            KeyCode::Char('q') => self.confirm_quit = true,
+            // Enter on any field but the last moves on to the next one.
+            KeyCode::Enter if self.on_last_field() => self.on_enter(),
+            KeyCode::Enter => match self.step {
+                Step::UserAccount => self.account_focus += 1,
+                _ => self.disk_focus += 1,
+            },
+            KeyCode::Tab if self.step == Step::UserAccount => {
+                self.account_focus = (self.account_focus + 1) % self.account_focus_count();
+            }
+            KeyCode::BackTab if self.step == Step::UserAccount => {
+                let count = self.account_focus_count();
+                self.account_focus = (self.account_focus + count - 1) % count;
+            }
+            KeyCode::Char(' ') if self.step == Step::PartitionScheme && self.assigning_mounts => {
+                if let Some(mount) = self.partition_mounts.get_mut(self.selected_partition) {
+                    *mount = MountPoint::cycle(*mount);
+                }
+            }
+            KeyCode::Char(' ') if self.step == Step::UserAccount => {
+                self.separate_root = !self.separate_root;
+                if !self.separate_root {
+                    self.root_password.zeroize();
+                    self.root_password_confirm.zeroize();
+                }
+            }
+            KeyCode::Tab | KeyCode::BackTab if self.step == Step::Wifi => {
+                self.wifi_focus = 1 - self.wifi_focus;
+            }
+            KeyCode::Tab | KeyCode::BackTab if self.step == Step::Keyboard => {
+                self.testing_keymap = !self.testing_keymap;
+            }
+            KeyCode::Char('s') if self.step == Step::Wifi => self.advance(),
+            KeyCode::Char('r') if self.step == Step::Mirrors && self.ranking_rx.is_none() => {
+                self.ranking = Ranking::Requested;
+            }
+            KeyCode::Char(' ') if self.step == Step::Mirrors && self.ranking_rx.is_none() => {
+                self.toggle_country();
+            }
+            KeyCode::Tab if self.step == Step::Disk && self.encrypt => {
+                self.disk_focus = (self.disk_focus + 1) % 3;
+            }
+            KeyCode::BackTab if self.step == Step::Disk && self.encrypt => {
+                self.disk_focus = (self.disk_focus + 2) % 3;
+            }
+            KeyCode::Char('e') if self.step == Step::Disk => {
+                self.encrypt = !self.encrypt;
+                self.disk_focus = usize::from(self.encrypt);
+                if !self.encrypt {
+                    self.passphrase.zeroize();
+                    self.passphrase_confirm.zeroize();
+                }
+            }
+            KeyCode::Char('r')
+                if matches!(self.step, Step::Network | Step::Summary)
+                    && self.connectivity != Connectivity::Checking =>
+            {
+                self.connectivity = Connectivity::Unchecked;
+            }
+            KeyCode::Char('s') if self.btrfs_highlighted() => {
+                self.installer.btrfs_subvolumes = !self.installer.btrfs_subvolumes;
+            }
+            KeyCode::Char('n') if self.btrfs_highlighted() => {
+                self.installer.btrfs_snapshots = !self.installer.btrfs_snapshots;
+            }
+            KeyCode::Esc
+                if self.step == Step::Installing
+                    && self.install_rx.is_some()
+                    && !self.cancelling() =>
+            {
+                self.confirm_cancel = true;
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Esc => self.back(),
+            // Typed text and filters have already taken letters, so the vi
+            // keys only reach lists nothing is being typed into.
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char('c') if self.step == Step::Summary => {
+                self.fstab_review = None;
+                self.command_review = match self.command_review.take() {
+                    Some(_) => None,
+                    None => Some(review(install::build_commands(&self.installer))),
+                };
+            }
+            KeyCode::Char('f') if self.step == Step::Summary => {
+                self.command_review = None;
+                self.fstab_review = match self.fstab_review.take() {
+                    Some(_) => None,
+                    None => {
+                        let fstab = install::generate_fstab(&self.installer);
+                        Some(review(fstab.lines().map(String::from).collect()))
+                    }
+                };
+            }
+            KeyCode::PageUp if self.step == Step::Summary => {
+                if let Some(review) = self.command_review.as_mut().or(self.fstab_review.as_mut()) {
+                    review.page_up();
+                }
+            }
+            KeyCode::PageDown if self.step == Step::Summary => {
+                if let Some(review) = self.command_review.as_mut().or(self.fstab_review.as_mut()) {
+                    review.page_down();
+                }
+            }
+            KeyCode::PageUp if self.step == Step::Installing => self.install_log.page_up(),
+            KeyCode::PageDown if self.step == Step::Installing => self.install_log.page_down(),
+            KeyCode::PageUp => self.move_selection(-PAGE_SIZE),
+            KeyCode::PageDown => self.move_selection(PAGE_SIZE),
+            _ => {}
+        }
+    }
+
+    /// Draw with `theme`, made plain if [`Self::accessible`] asks for it.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = if self.accessible { theme.accessible() } else { theme };
+        // Braille dots are missing from the fonts plain drawing is for.
+        self.spinner = if self.theme.plain { Spinner::ascii() } else { Spinner::braille() };
+    }
+
+    /// Whether btrfs is highlighted on the filesystem screen.
+    pub fn btrfs_highlighted(&self) -> bool {
+        self.step == Step::Filesystem
+            && Filesystem::ALL[self.selected_filesystem] == Filesystem::Btrfs
+    }
+
+    /// The terminal is now `width` by `height`; everything is laid out again.
+    pub fn on_resize(&mut self, width: u16, height: u16) {
+        log::debug!("terminal resized to {width}x{height}");
+        self.dirty = true;
+    }
+
+    pub fn on_mouse(&mut self, event: MouseEvent) {
+        if self.confirm_quit
+            || self.confirm_cancel
+            || self.confirm_finish.is_some()
+            || self.viewing_log
+            || self.erase_confirm.is_some()
+            || self.popup.is_some()
+            || self.show_help
+        {
+            return;
+        }
+        // Pointer motion is reported too; only clicks and scrolling change anything.
+        if !matches!(
+            event.kind,
+            MouseEventKind::ScrollDown
+                | MouseEventKind::ScrollUp
+                | MouseEventKind::Down(MouseButton::Left)
+        ) {
+            return;
+        }
+        self.dirty = true;
+        match event.kind {
+            MouseEventKind::ScrollDown => self.move_selection(1),
+            MouseEventKind::ScrollUp => self.move_selection(-1),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let at = Position::new(event.column, event.row);
+                if self.next_button.is_some_and(|button| button.contains(at)) {
+                    self.on_enter();
+                } else if let Some(row) = self
+                    .list_hitbox
+                    .and_then(|list| list.index_at(event.column, event.row))
+                {
+                    let index = match self.filtered_indices() {
+                        Some(visible) => visible.get(row).copied(),
+                        None => Some(row),
+                    };
+                    if let Some(index) = index {
+                        self.click_entry(index);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Highlight list entry `index`, or pick it if it was just clicked.
+    fn click_entry(&mut self, index: usize) {
+        let now = Instant::now();
+        let double = self.last_click.is_some_and(|(step, last, at)| {
+            step == self.step && last == index && now.duration_since(at) <= DOUBLE_CLICK
+        });
+        self.select(index);
+        if double {
+            self.last_click = None;
+            self.on_enter();
+        } else {
+            self.last_click = Some((self.step, index, now));
+        }
+    }
+
+    /// Names on the current screen's list if it can be filtered.
+    fn filterable_items(&self) -> Option<Vec<&str>> {
+        match (self.step, &self.timezone_region) {
+            (Step::Language, _) => Some(LANGUAGES.iter().map(|language| language.name).collect()),
+            (Step::Locale, _) => Some(self.locales.iter().map(String::as_str).collect()),
+            (Step::Keyboard, _) => Some(self.keymaps.iter().map(String::as_str).collect()),
+            (Step::Timezone, None) => Some(timezones::regions(&self.timezones)),
+            (Step::Timezone, Some(region)) => Some(timezones::cities(&self.timezones, region)),
+            (Step::Packages, _) => Some(PACKAGES.iter().map(|package| package.name).collect()),
+            _ => None,
+        }
+    }
+
+    /// Indices into the current list of the entries matching [`Self::filter`],
+    /// or `None` on screens that cannot be filtered.
+    pub fn filtered_indices(&self) -> Option<Vec<usize>> {
+        self.filterable_items()
+            .map(|items| filter_items(&items, &self.filter))
+    }
+
+    /// Keep the highlight on an entry that matches the filter.
+    fn clamp_to_filter(&mut self) {
+        let Some(visible) = self.filtered_indices() else {
+            return;
+        };
+        if let (Some(selected), Some(&first)) = (self.selected(), visible.first()) {
+            if !visible.contains(&selected) {
+                self.select(first);
+            }
+        }
+    }
+
+    /// The highlighted entry on the current list screen, if it has one.
+    fn selected(&self) -> Option<usize> {
+        match self.step {
+            Step::Language => Some(self.installer.language),
+            Step::Locale => Some(self.selected_locale),
+            Step::Keyboard => Some(self.selected_keymap),
+            Step::Timezone if self.timezone_region.is_none() => Some(self.selected_region),
+            Step::Timezone => Some(self.selected_timezone),
+            Step::Wifi => Some(self.selected_wifi),
+            Step::Mirrors => Some(self.selected_country),
+            Step::Disk => Some(self.selected_disk),
+            Step::PartitionScheme if self.assigning_mounts => Some(self.selected_partition),
+            Step::PartitionScheme => Some(self.selected_scheme),
+            Step::Filesystem => Some(self.selected_filesystem),
+            Step::Swap => Some(self.selected_swap),
+            Step::Bootloader => Some(self.selected_bootloader),
+            Step::Desktop => Some(self.installer.desktop),
+            Step::Graphics => Some(self.selected_driver),
+            Step::Packages => Some(self.selected_package),
+            Step::Completion => Some(self.selected_action),
+            _ => None,
+        }
+    }
+
+    /// Highlight entry `index` on the current list screen, if it has one.
+    fn select(&mut self, index: usize) {
+        let selected = match self.step {
+            Step::Language => &mut self.installer.language,
+            Step::Locale => &mut self.selected_locale,
+            Step::Keyboard => &mut self.selected_keymap,
+            Step::Timezone if self.timezone_region.is_none() => &mut self.selected_region,
+            Step::Timezone => &mut self.selected_timezone,
+            Step::Wifi => &mut self.selected_wifi,
+            // The ranking is for the country it was run for.
+            Step::Mirrors if self.ranking_rx.is_some() => return,
+            Step::Mirrors => {
+                // Ticked countries stay put while the highlight moves.
+                if index != self.selected_country && self.picked_countries.is_empty() {
+                    self.ranked_mirrors = None;
+                    self.ranking = Ranking::Idle;
+                }
+                &mut self.selected_country
+            }
+            Step::Disk => &mut self.selected_disk,
+            Step::PartitionScheme if self.assigning_mounts => &mut self.selected_partition,
+            Step::PartitionScheme => &mut self.selected_scheme,
+            Step::Filesystem => &mut self.selected_filesystem,
+            Step::Swap => &mut self.selected_swap,
+            Step::Bootloader if !self.usable_bootloaders().contains(&index) => return,
+            Step::Bootloader => &mut self.selected_bootloader,
+            Step::Desktop => &mut self.installer.desktop,
+            Step::Graphics => &mut self.selected_driver,
+            Step::Packages => &mut self.selected_package,
+            Step::Completion => &mut self.selected_action,
+            _ => return,
+        };
+        *selected = index;
+    }
+
+    /// Move the highlight on the current list screen, if it has one.
+    fn move_selection(&mut self, delta: isize) {
+        // Filterable lists move through the entries that are shown.
+        if let (Some(visible), Some(selected)) = (self.filtered_indices(), self.selected()) {
+            let mut position = visible.iter().position(|&i| i == selected).unwrap_or(0);
+            move_index(&mut position, visible.len(), delta);
+            if let Some(&index) = visible.get(position) {
+                self.select(index);
+            }
+            return;
+        }
+        match self.step {
+            Step::Keyboard => move_index(&mut self.selected_keymap, self.keymaps.len(), delta),
+            Step::Wifi => move_index(&mut self.selected_wifi, self.wifi_networks.len(), delta),
+            Step::Mirrors => {
+                let mut index = self.selected_country;
+                move_index(&mut index, mirrors::countries(&self.mirrors).len() + 1, delta);
+                self.select(index);
+            }
+            Step::Disk => move_index(&mut self.selected_disk, self.disks.len(), delta),
+            Step::PartitionScheme if self.assigning_mounts => {
+                move_index(&mut self.selected_partition, self.partitions.len(), delta)
+            }
+            Step::PartitionScheme => {
+                move_index(&mut self.selected_scheme, PartitionScheme::ALL.len(), delta)
+            }
+            Step::Filesystem => {
+                move_index(&mut self.selected_filesystem, Filesystem::ALL.len(), delta)
+            }
+            Step::Swap => {
+                let len = self.swap_options().len();
+                move_index(&mut self.selected_swap, len, delta)
+            }
+            Step::Bootloader => {
+                let usable = self.usable_bootloaders();
+                let mut position =
+                    usable.iter().position(|&i| i == self.selected_bootloader).unwrap_or(0);
+                move_index(&mut position, usable.len(), delta);
+                if let Some(&index) = usable.get(position) {
+                    self.selected_bootloader = index;
+                }
+            }
+            Step::Desktop => move_index(&mut self.installer.desktop, DESKTOPS.len(), delta),
+            Step::Graphics => {
+                let len = self.driver_options().len();
+                move_index(&mut self.selected_driver, len, delta)
+            }
+            Step::Completion => {
+                move_index(&mut self.selected_action, FinishAction::ALL.len(), delta)
+            }
+            _ => {}
+        }
+    }
+
+    /// Called on every timer tick of the main loop.
+    pub fn on_tick(&mut self) {
+        // The spinner only turns while something runs in the background;
+        // idle screens are left alone so they don't need redrawing, and so
+        // are unanimated ones, which show a static label instead.
+        if self.busy() && self.animate {
+            self.spinner.tick();
+            self.dirty = true;
+        }
+        // The installation's clock moves on without the spinner too.
+        if self.install_started.is_some() {
+            self.dirty = true;
+        }
+        self.drain_install_progress();
+        self.poll_network_check();
+        self.poll_time_sync();
+        self.poll_wifi_connect();
+        self.poll_mirror_ranking();
+    }
+
+    /// Whether an installation, a network check, a clock sync, a Wi-Fi
+    /// connection or reflector is running.
+    pub fn busy(&self) -> bool {
+        self.install_rx.is_some()
+            || self.network_rx.is_some()
+            || self.time_sync_rx.is_some()
+            || self.wifi_rx.is_some()
+            || self.ranking_rx.is_some()
+    }
+
+    /// Whether `r` on the mirror screen is waiting for reflector to be started.
+    pub fn needs_mirror_ranking(&self) -> bool {
+        self.ranking == Ranking::Requested
+    }
+
+    /// Follow the reflector run answering on `rx`.
+    pub fn attach_mirror_ranking(&mut self, rx: Receiver<Result<Vec<Mirror>, String>>) {
+        self.ranking_rx = Some(rx);
+        self.ranking = Ranking::Running;
+        self.dirty = true;
+    }
+
+    /// Pick up reflector's ranking if it has arrived.
+    fn poll_mirror_ranking(&mut self) {
+        let Some(rx) = &self.ranking_rx else { return };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Disconnected) => Err("reflector stopped unexpectedly".into()),
+            Err(TryRecvError::Empty) => return,
+        };
+        self.ranking_rx = None;
+        self.dirty = true;
+        match result {
+            Ok(ranked) => {
+                self.ranked_mirrors = Some(ranked);
+                self.ranking = Ranking::Idle;
+            }
+            Err(error) => self.ranking = Ranking::Failed(error),
+        }
+    }
+
+    /// The keymap highlighted on the keyboard screen, if the console does
+    /// not have it loaded yet; from then on it counts as loaded. What was
+    /// typed with the previous layout is cleared.
+    pub fn take_keymap_request(&mut self) -> Option<String> {
+        if self.step != Step::Keyboard {
+            return None;
+        }
+        let keymap = self.keymaps.get(self.selected_keymap)?;
+        if self.loaded_keymap.as_ref() == Some(keymap) {
+            return None;
+        }
+        self.loaded_keymap = Some(keymap.clone());
+        self.keymap_test.set_value("");
+        self.dirty = true;
+        self.loaded_keymap.clone()
+    }
+
+    /// The network to join and its passphrase, once Enter has asked for it.
+    /// The passphrase field is cleared as it is handed over.
+    pub fn take_wifi_request(&mut self) -> Option<(String, Secret)> {
+        if self.wifi_status != WifiStatus::Requested {
+            return None;
+        }
+        let ssid = self.wifi_networks.get(self.selected_wifi)?.ssid.clone();
+        let passphrase = Secret::from(self.wifi_passphrase.value());
+        self.wifi_passphrase.zeroize();
+        self.wifi_status = WifiStatus::Connecting(ssid.clone());
+        Some((ssid, passphrase))
+    }
+
+    /// Follow the connection attempt answering on `rx`.
+    pub fn attach_wifi_connect(&mut self, rx: Receiver<Result<(), String>>) {
+        self.wifi_rx = Some(rx);
+        self.dirty = true;
+    }
+
+    /// Pick up the outcome of the connection attempt if it has arrived.
+    fn poll_wifi_connect(&mut self) {
+        let Some(rx) = &self.wifi_rx else { return };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Disconnected) => Err("the attempt stopped unexpectedly".into()),
+            Err(TryRecvError::Empty) => return,
+        };
+        self.wifi_rx = None;
+        self.dirty = true;
+        let WifiStatus::Connecting(ssid) = &self.wifi_status else { return };
+        self.wifi_status = match result {
+            Ok(()) => {
+                // Whatever the network screen found out is stale now.
+                self.connectivity = Connectivity::Unchecked;
+                WifiStatus::Connected(ssid.clone())
+            }
+            Err(error) => WifiStatus::Failed(error),
+        };
+    }
+
+    /// Whether the network screen is waiting for a check to be started.
+    pub fn needs_network_check(&self) -> bool {
+        matches!(self.step, Step::Network | Step::Summary)
+            && self.connectivity == Connectivity::Unchecked
+    }
+
+    /// Follow the network check answering on `rx`.
+    pub fn attach_network_check(&mut self, rx: Receiver<CheckUpdate>) {
+        self.network_rx = Some(rx);
+        self.network_retry = None;
+        self.connectivity = Connectivity::Checking;
+        self.dirty = true;
+    }
+
+    /// Pick up the network check's answer if it has arrived.
+    fn poll_network_check(&mut self) {
+        let Some(rx) = &self.network_rx else { return };
+        let result = loop {
+            match rx.try_recv() {
+                Ok(CheckUpdate::Retrying { attempt, attempts }) => {
+                    self.network_retry = Some((attempt, attempts));
+                    self.dirty = true;
+                }
+                Ok(CheckUpdate::Finished(result)) => break result.map_err(|err| err.to_string()),
+                Err(TryRecvError::Disconnected) => {
+                    break Err("the check stopped unexpectedly".into());
+                }
+                Err(TryRecvError::Empty) => return,
+            }
+        };
+        self.network_rx = None;
+        self.network_retry = None;
+        self.dirty = true;
+        match result {
+            Ok(()) => {
+                self.connectivity = Connectivity::Online;
+                self.network_error = None;
+            }
+            Err(error) => {
+                self.connectivity = Connectivity::Offline;
+                self.network_error = Some(error);
+                if self.step == Step::Summary {
+                    self.show_offline_popup();
+                }
+            }
+        }
+    }
+
+    /// Whether the summary screen is online and waiting for the clock to
+    /// be synchronized.
+    pub fn needs_time_sync(&self) -> bool {
+        self.step == Step::Summary
+            && self.connectivity == Connectivity::Online
+            && self.time_sync == TimeSync::Unsynced
+    }
+
+    /// Follow the clock synchronization answering on `rx`.
+    pub fn attach_time_sync(&mut self, rx: Receiver<Result<(), TimeSyncError>>) {
+        self.time_sync_rx = Some(rx);
+        self.time_sync = TimeSync::Syncing;
+        self.dirty = true;
+    }
+
+    /// Treat the clock as synchronized without touching it, as a dry run
+    /// must leave the live system alone.
+    pub fn skip_time_sync(&mut self) {
+        self.time_sync = TimeSync::Synced;
+        self.dirty = true;
+    }
+
+    /// Pick up the clock synchronization's answer if it has arrived. A
+    /// failure is warned about, but does not stop the installation.
+    fn poll_time_sync(&mut self) {
+        let Some(rx) = &self.time_sync_rx else { return };
+        let result = match rx.try_recv() {
+            Ok(result) => result.map_err(|err| err.to_string()),
+            Err(TryRecvError::Disconnected) => Err("the sync stopped unexpectedly".into()),
+            Err(TryRecvError::Empty) => return,
+        };
+        self.time_sync_rx = None;
+        self.dirty = true;
+        self.time_sync = match result {
+            Ok(()) => TimeSync::Synced,
+            Err(error) => {
+                if self.step == Step::Summary {
+                    self.popup = Some(Popup {
+                        title: "Clock not synchronized".into(),
+                        message: format!(
+                            "The clock could not be set from the network ({error}). If it is \
+                             wrong, package signatures may fail to verify."
+                        ),
+                        back_to: Step::Summary,
+                        retry: Some(Retry::TimeSync),
+                    });
+                }
+                TimeSync::Failed(error)
+            }
+        };
+    }
+
+    /// Tell the user the installation cannot start without internet access.
+    fn show_offline_popup(&mut self) {
+        let reason = self.network_error.as_deref().unwrap_or("no connection");
+        self.popup = Some(Popup {
+            title: "No internet connection".into(),
+            message: format!("The packages cannot be downloaded ({reason})."),
+            back_to: Step::Summary,
+            retry: Some(Retry::NetworkCheck),
+        });
+    }
+
+    /// Whether the installing step has been reached but nothing is running yet.
+    pub fn needs_install_worker(&self) -> bool {
+        self.step == Step::Installing && self.install_rx.is_none() && self.install_error.is_none()
+    }
+
+    /// Whether cancelling the installation was confirmed and it has yet to
+    /// stop.
+    pub fn cancelling(&self) -> bool {
+        self.install_cancel.load(Ordering::SeqCst)
+    }
+
+    /// Follow the installation reporting on `rx`.
+    pub fn attach_install(&mut self, rx: Receiver<InstallEvent>) {
+        self.install_rx = Some(rx);
+        self.install_started = Some((Instant::now(), self.progress));
+        self.dirty = true;
+    }
+
+    /// How long the installation has been running at `now`, and roughly how
+    /// much longer it will take judging by the progress made so far. There
+    /// is no guess until some has been made.
+    pub fn install_timing(&self, now: Instant) -> Option<(Duration, Option<Duration>)> {
+        let (started, from) = self.install_started?;
+        let elapsed = now.saturating_duration_since(started);
+        let done = self.progress - from;
+        let left = (done > 0.0).then(|| elapsed.mul_f64((1.0 - self.progress) / done));
+        Some((elapsed, left))
+    }
+
+    /// Apply every event waiting on the channel from the installation thread.
+    fn drain_install_progress(&mut self) {
+        loop {
+            let Some(rx) = &self.install_rx else { return };
+            let event = rx.try_recv();
+            if !matches!(event, Err(TryRecvError::Empty)) {
+                self.dirty = true;
+            }
+            let ended = !matches!(
+                event,
+                Ok(InstallEvent::Progress(_) | InstallEvent::Log(_) | InstallEvent::Output(_))
+                    | Err(TryRecvError::Empty)
+            );
+            if ended {
+                // Nothing is left to cancel, and the next run starts afresh.
+                self.install_started = None;
+                self.confirm_cancel = false;
+                self.install_cancel.store(false, Ordering::SeqCst);
+            }
+            match event {
+                Ok(InstallEvent::Progress(percent)) => {
+                    let progress = f64::from(percent) / 100.0;
+                    self.report_progress(InstallPhase::at(progress), progress);
+                }
+                Ok(InstallEvent::Log(line)) => {
+                    self.install_ran = true;
+                    self.install_message = line.clone();
+                    self.install_log.push(line);
+                }
+                // The status line keeps naming the command that printed it.
+                Ok(InstallEvent::Output(line)) => self.install_log.push(line),
+                Ok(InstallEvent::Done) => {
+                    self.install_rx = None;
+                    return self.advance();
+                }
+                Ok(InstallEvent::Failed(error)) => {
+                    log::error!("installation failed: {error}");
+                    self.install_rx = None;
+                    self.popup = Some(Popup {
+                        title: "Installation failed".into(),
+                        message: error.clone(),
+                        back_to: Step::Summary,
+                        retry: None,
+                    });
+                    self.install_error = Some(error);
+                }
+                Ok(InstallEvent::CommandFailed(failure)) => {
+                    self.install_rx = None;
+                    // A retry that fails at the same command counts as another
+                    // attempt at it; failing further on starts the count over.
+                    self.install_attempts = if failure.index == self.install_from {
+                        self.install_attempts + 1
+                    } else {
+                        1
+                    };
+                    let mut message = failure.to_string();
+                    for line in &failure.stderr {
+                        message.push('\n');
+                        message.push_str(line);
+                    }
+                    let (attempts, max) = (self.install_attempts, self.max_install_attempts);
+                    let retry = failure.phase.retryable() && attempts < max;
+                    if failure.phase.retryable() {
+                        message.push_str(&if retry {
+                            format!("\n\nAttempt {attempts} of {max}.")
+                        } else {
+                            format!("\n\nGave up after {attempts} attempts.")
+                        });
+                    }
+                    self.popup = Some(Popup {
+                        title: "Installation failed".into(),
+                        message,
+                        back_to: Step::Summary,
+                        retry: retry.then_some(Retry::Install { from: failure.index }),
+                    });
+                    self.install_error = Some(failure.to_string());
+                }
+                Ok(InstallEvent::Cancelled(phases)) => {
+                    log::info!("installation cancelled after {phases:?}");
+                    self.install_rx = None;
+                    let message = if phases.is_empty() {
+                        "Nothing had been done to the disk yet.".to_string()
+                    } else {
+                        let done: Vec<&str> = phases.iter().map(|phase| phase.label()).collect();
+                        format!("Already done: {}. The disk has been changed.", done.join(", "))
+                    };
+                    self.popup = Some(Popup {
+                        title: "Installation cancelled".into(),
+                        message,
+                        back_to: Step::Summary,
+                        retry: None,
+                    });
+                    self.install_error = Some("Cancelled on request".into());
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    log::error!("installation thread exited without finishing");
+                    self.install_rx = None;
+                    let error = "The installation stopped unexpectedly".to_string();
+                    self.popup = Some(Popup {
+                        title: "Installation failed".into(),
+                        message: error.clone(),
+                        back_to: Step::Summary,
+                        retry: None,
+                    });
+                    self.install_error = Some(error);
+                }
+            }
+        }
+    }
+}
+
+/// Titles of the fields on the user account screen, in focus order.
+pub const ACCOUNT_FIELDS: [&str; 4] = ["Username", "Full name", "Password", "Confirm password"];
+
+/// Titles of the root password fields, shown side by side after the root
+/// toggle when root gets a password of its own.
+pub const ROOT_FIELDS: [&str; 2] = ["Root password", "Confirm"];
+
+/// What the hostname field starts on, unless saved answers say otherwise.
+const DEFAULT_HOSTNAME: &str = "endeavouros";
+
+/// Longest gap between the clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// How far PageUp/PageDown jump in long lists.
+const PAGE_SIZE: isize = 10;
+
+/// How many lines of the installation's task log are kept for scrolling back.
+const INSTALL_LOG_LINES: usize = 1000;
+
+/// `lines` to read through on the summary screen, shown from the first.
+fn review(lines: Vec<String>) -> LogBuffer {
+    let mut review = LogBuffer::with_capacity(lines.len());
+    for line in lines {
+        review.push(line);
+    }
+    review.scroll_to_top();
+    review
+}
+
+/// Move `index` by `delta` within a list of `len` entries without wrapping.
+fn move_index(index: &mut usize, len: usize, delta: isize) {
+    let last = len.saturating_sub(1);
+    *index = index.saturating_add_signed(delta).min(last);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::install::CommandFailure;
+    use crate::system::network::ConnError;
+
+    #[test]
+    fn step_next_ends_at_completion() {
+        assert_eq!(Step::Welcome.next(), Some(Step::Hardware));
+        assert_eq!(Step::Hardware.next(), Some(Step::Wifi));
+        assert_eq!(Step::Wifi.next(), Some(Step::Network));
+        assert_eq!(Step::Network.next(), Some(Step::Mirrors));
+        assert_eq!(Step::Mirrors.next(), Some(Step::Language));
+        assert_eq!(Step::Language.next(), Some(Step::Locale));
+        assert_eq!(Step::Locale.next(), Some(Step::Keyboard));
+        assert_eq!(Step::Keyboard.next(), Some(Step::Timezone));
+        assert_eq!(Step::Timezone.next(), Some(Step::Disk));
+        assert_eq!(Step::Disk.next(), Some(Step::PartitionScheme));
+        assert_eq!(Step::PartitionScheme.next(), Some(Step::Filesystem));
+        assert_eq!(Step::Filesystem.next(), Some(Step::Swap));
+        assert_eq!(Step::Swap.next(), Some(Step::Bootloader));
+        assert_eq!(Step::Bootloader.next(), Some(Step::Hostname));
+        assert_eq!(Step::Hostname.next(), Some(Step::UserAccount));
+        assert_eq!(Step::UserAccount.next(), Some(Step::Desktop));
+        assert_eq!(Step::Desktop.next(), Some(Step::Graphics));
+        assert_eq!(Step::Graphics.next(), Some(Step::Packages));
+        assert_eq!(Step::Packages.next(), Some(Step::ExtraPackages));
+        assert_eq!(Step::ExtraPackages.next(), Some(Step::Summary));
+        assert_eq!(Step::Summary.next(), Some(Step::Installing));
+        assert_eq!(Step::Installing.next(), Some(Step::Completion));
+        assert_eq!(Step::Completion.next(), None);
+    }
+
+    #[test]
+    fn all_steps_are_in_wizard_order() {
+        for pair in Step::ALL.windows(2) {
+            assert_eq!(pair[0].next(), Some(pair[1]));
+        }
+        assert_eq!(Step::ALL[0].prev(), None);
+        assert_eq!(Step::ALL[Step::ALL.len() - 1].next(), None);
+    }
+
+    #[test]
+    fn step_prev_ends_at_welcome_and_once_installing() {
+        assert_eq!(Step::Completion.prev(), None);
+        assert_eq!(Step::Installing.prev(), None);
+        assert_eq!(Step::Summary.prev(), Some(Step::ExtraPackages));
+        assert_eq!(Step::ExtraPackages.prev(), Some(Step::Packages));
+        assert_eq!(Step::Packages.prev(), Some(Step::Graphics));
+        assert_eq!(Step::Graphics.prev(), Some(Step::Desktop));
+        assert_eq!(Step::Desktop.prev(), Some(Step::UserAccount));
+        assert_eq!(Step::UserAccount.prev(), Some(Step::Hostname));
+        assert_eq!(Step::Hostname.prev(), Some(Step::Bootloader));
+        assert_eq!(Step::Bootloader.prev(), Some(Step::Swap));
+        assert_eq!(Step::Swap.prev(), Some(Step::Filesystem));
+        assert_eq!(Step::Filesystem.prev(), Some(Step::PartitionScheme));
+        assert_eq!(Step::PartitionScheme.prev(), Some(Step::Disk));
+        assert_eq!(Step::Disk.prev(), Some(Step::Timezone));
+        assert_eq!(Step::Timezone.prev(), Some(Step::Keyboard));
+        assert_eq!(Step::Keyboard.prev(), Some(Step::Locale));
+        assert_eq!(Step::Locale.prev(), Some(Step::Language));
+        assert_eq!(Step::Language.prev(), Some(Step::Mirrors));
+        assert_eq!(Step::Mirrors.prev(), Some(Step::Network));
+        assert_eq!(Step::Network.prev(), Some(Step::Wifi));
+        assert_eq!(Step::Wifi.prev(), Some(Step::Hardware));
+        assert_eq!(Step::Hardware.prev(), Some(Step::Welcome));
+        assert_eq!(Step::Welcome.prev(), None);
+    }
+
+    #[test]
+    fn every_step_lists_its_keys() {
+        let mut app = AppState::new();
+        for step in Step::ALL {
+            app.step = step;
+            let keys: Vec<&str> = app.keybindings().into_iter().map(|(key, _)| key).collect();
+            assert!(keys.contains(&"F1") || keys.contains(&"?/F1"), "{step:?}");
+            assert!(keys.iter().any(|key| key.contains("Ctrl+Q")), "{step:?}");
+            assert_eq!(keys.contains(&"Enter"), step != Step::Installing, "{step:?}");
+        }
+        let disk = Step::Disk.keybindings(Typing::Keys);
+        assert!(disk.contains(&("e", "turn encryption on or off")));
+        let enter = ("Enter", "type the disk's name, then begin installing");
+        assert!(Step::Summary.keybindings(Typing::Keys).contains(&enter));
+        let welcome = Step::Welcome.keybindings(Typing::Keys);
+        assert!(!welcome.iter().any(|(key, _)| key.contains("Esc")));
+    }
+
+    #[test]
+    fn only_keys_that_are_not_typed_are_listed() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        assert_eq!(app.typing(), Typing::Field);
+        let keys = app.keybindings();
+        assert!(keys.contains(&("Esc", "go back")) && keys.contains(&("Ctrl+Q", "quit")));
+        assert!(!keys.iter().any(|(key, _)| key.contains('q') || key.contains('←')));
+
+        app.step = Step::Welcome;
+        assert_eq!(app.typing(), Typing::Keys);
+        assert!(app.keybindings().contains(&("q/Ctrl+Q", "quit")));
+    }
+
+    #[test]
+    fn ctrl_q_quits_from_text_fields() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        app.on_key_event(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL));
+        assert!(app.confirm_quit);
+        assert_eq!(app.hostname.value(), "endeavouros");
+        app.on_key(KeyCode::Char('n'));
+        app.on_key_event(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+        assert!(!app.confirm_quit);
+        assert_eq!(app.hostname.value(), "endeavourosq");
+
+        app.step = Step::Completion;
+        app.on_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn f2_cycles_the_theme() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        app.on_key(KeyCode::F(2));
+        assert_eq!(app.theme, Theme::LIGHT);
+        assert_eq!(app.hostname.value(), "endeavouros");
+        app.on_key(KeyCode::F(2));
+        app.on_key(KeyCode::F(2));
+        assert_eq!(app.theme, Theme::MONOCHROME);
+        app.on_key(KeyCode::F(2));
+        assert_eq!(app.theme, Theme::DARK);
+
+        // Accessible mode stays plain through every theme.
+        app.on_key(KeyCode::F(3));
+        assert!(app.accessible && app.theme.plain);
+        assert_eq!(app.theme.muted, Theme::DARK.fg);
+        app.on_key(KeyCode::F(2));
+        assert_eq!(app.theme.accent, Theme::LIGHT.accent);
+        assert!(app.theme.plain);
+        app.on_key(KeyCode::F(3));
+        assert_eq!(app.theme, Theme::LIGHT);
+    }
+
+    #[test]
+    fn summary_shows_the_commands_on_c() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/sda".into());
+        app.on_key(KeyCode::Char('c'));
+        let review = app.command_review.as_ref().unwrap();
+        let commands: Vec<&str> = review.visible(usize::MAX).collect();
+        assert_eq!(commands, install::build_commands(&app.installer));
+        assert!(!review.is_following());
+
+        app.on_key(KeyCode::Char('c'));
+        assert!(app.command_review.is_none());
+        app.on_key(KeyCode::Char('c'));
+        app.on_key(KeyCode::Left);
+        assert_eq!(app.step, Step::ExtraPackages);
+        assert!(app.command_review.is_none());
+    }
+
+    #[test]
+    fn summary_shows_the_fstab_on_f() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/sda".into());
+        app.on_key(KeyCode::Char('c'));
+        app.on_key(KeyCode::Char('f'));
+        assert!(app.command_review.is_none());
+        let fstab: Vec<&str> = app.fstab_review.as_ref().unwrap().visible(usize::MAX).collect();
+        assert_eq!(fstab.join("\n") + "\n", install::generate_fstab(&app.installer));
+        assert!(fstab.contains(&"/dev/sda2 / ext4 rw,relatime 0 1"));
+
+        app.on_key(KeyCode::Char('c'));
+        assert!(app.fstab_review.is_none());
+        app.on_key(KeyCode::Char('f'));
+        app.on_key(KeyCode::Char('f'));
+        assert!(app.fstab_review.is_none() && app.command_review.is_none());
+    }
+
+    #[test]
+    fn question_mark_or_f1_opens_the_key_help() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        app.on_key(KeyCode::Char('?'));
+        assert!(app.show_help);
+        // Any key closes it, and does nothing else.
+        app.on_key(KeyCode::Char('e'));
+        assert!(!app.show_help);
+        assert!(!app.encrypt);
+
+        app.on_key(KeyCode::F(1));
+        assert!(app.show_help);
+        app.on_key(KeyCode::Esc);
+        assert!(!app.show_help);
+        assert_eq!(app.step, Step::Disk);
+
+        // A text field takes `?` as typed; F1 still works there.
+        app.step = Step::Hostname;
+        app.on_key(KeyCode::Char('?'));
+        assert!(!app.show_help);
+        assert_eq!(app.hostname.value(), "endeavouros?");
+        app.on_key(KeyCode::F(1));
+        assert!(app.show_help);
+    }
+
+    #[test]
+    fn advance_and_back_stop_at_the_ends() {
+        let mut app = AppState::new();
+        app.back();
+        assert_eq!(app.step, Step::Welcome);
+        for _ in 0..22 {
+            app.advance();
+        }
+        assert_eq!(app.step, Step::Completion);
+    }
+
+    #[test]
+    fn enter_does_not_wrap_from_completion() {
+        let mut app = AppState::new();
+        app.step = Step::Completion;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Completion);
+    }
+
+    #[test]
+    fn stepping_past_either_end_stays_put() {
+        let mut app = AppState::new();
+        app.back();
+        assert_eq!(app.step, Step::Welcome);
+        app.step = Step::Installing;
+        app.back();
+        assert_eq!(app.step, Step::Installing);
+        app.step = Step::Completion;
+        app.advance();
+        assert_eq!(app.step, Step::Completion);
+    }
+
+    #[test]
+    fn completion_menu_runs_the_highlighted_action() {
+        let mut app = AppState::new();
+        app.step = Step::Completion;
+        for _ in 0..3 {
+            app.on_key(KeyCode::Down);
+        }
+        assert_eq!(FinishAction::ALL[app.selected_action], FinishAction::ViewLog);
+        app.on_key(KeyCode::Enter);
+        assert!(app.viewing_log);
+        app.on_key(KeyCode::Char('q'));
+        assert!(!app.viewing_log && !app.should_quit);
+
+        // Rebooting and powering off are asked about first.
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.confirm_finish, Some(FinishAction::PowerOff));
+        app.on_key(KeyCode::Char('n'));
+        assert!(app.confirm_finish.is_none() && app.finish.is_none());
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Char('y'));
+        assert_eq!(app.finish, Some(FinishAction::Reboot));
+        assert!(app.should_quit);
+        assert_eq!(FinishAction::Reboot.command(), Some(["systemctl", "reboot"]));
+        assert_eq!(FinishAction::PowerOff.command(), Some(["systemctl", "poweroff"]));
+    }
+
+    #[test]
+    fn q_exits_straight_away_once_installed() {
+        let mut app = AppState::new();
+        app.step = Step::Completion;
+        app.on_key(KeyCode::Char('q'));
+        assert!(app.should_quit && !app.confirm_quit);
+        assert_eq!(app.finish, None);
+    }
+
+    #[test]
+    fn left_and_backspace_go_back() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        app.on_key(KeyCode::Left);
+        assert_eq!(app.step, Step::Timezone);
+        app.step = Step::Network;
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Wifi);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Hardware);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Welcome);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Welcome);
+    }
+
+    #[test]
+    fn up_and_down_move_language_within_bounds() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.installer.language, 0);
+        for _ in 0..LANGUAGES.len() + 1 {
+            app.on_key(KeyCode::Down);
+        }
+        assert_eq!(app.installer.language, LANGUAGES.len() - 1);
+    }
+
+    #[test]
+    fn down_down_up_selects_second_language() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.installer.language, 1);
+    }
+
+    #[test]
+    fn j_and_k_move_like_the_arrows() {
+        let mut app = AppState::new();
+        app.step = Step::Desktop;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char('j'));
+        assert_eq!(app.installer.desktop, 2);
+        app.on_key(KeyCode::Char('k'));
+        assert_eq!(app.installer.desktop, 1);
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.installer.desktop, 0);
+        assert!(app.keybindings().contains(&("↑/↓ or k/j", "move the highlight")));
+    }
+
+    #[test]
+    fn j_and_k_are_typed_where_text_is_expected() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.on_key(KeyCode::Char('j'));
+        assert_eq!(app.filter, "j");
+        assert_eq!(app.installer.language, 0);
+
+        app.step = Step::UserAccount;
+        app.on_key(KeyCode::Char('k'));
+        app.on_key(KeyCode::Char('j'));
+        assert_eq!(app.username.value(), "kj");
+    }
+
+    #[test]
+    fn language_maps_to_locale() {
+        let mut installer = InstallerState::default();
+        assert_eq!(installer.locale(), "en_US.UTF-8");
+        installer.language = 1;
+        assert_eq!(installer.locale(), "fr_FR.UTF-8");
+    }
+
+    #[test]
+    fn enter_keeps_chosen_language() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Locale);
+        assert_eq!(app.installer.language, 1);
+    }
+
+    #[test]
+    fn going_back_preserves_chosen_language() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::Language);
+        assert_eq!(app.installer.language, 1);
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> MouseEvent {
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn hitbox_maps_rows_to_items() {
+        let list = ListHitbox {
+            area: Rect::new(10, 5, 20, 4),
+            offset: 3,
+            len: 5,
+        };
+        assert_eq!(list.index_at(10, 5), Some(3));
+        assert_eq!(list.index_at(29, 6), Some(4));
+        // Rows past the last item, and clicks outside the list, hit nothing.
+        assert_eq!(list.index_at(15, 7), None);
+        assert_eq!(list.index_at(9, 5), None);
+        assert_eq!(list.index_at(30, 5), None);
+        assert_eq!(list.index_at(15, 9), None);
+    }
+
+    #[test]
+    fn double_click_picks_the_entry() {
+        let mut app = AppState::new();
+        app.step = Step::Desktop;
+        app.list_hitbox = Some(ListHitbox {
+            area: Rect::new(10, 5, 20, 7),
+            offset: 0,
+            len: DESKTOPS.len(),
+        });
+        let click = |app: &mut AppState, row| {
+            app.on_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 12, row));
+        };
+        // Clicks on different rows, or too far apart, only highlight.
+        click(&mut app, 6);
+        click(&mut app, 7);
+        assert_eq!(app.step, Step::Desktop);
+        let (step, index, at) = app.last_click.unwrap();
+        app.last_click = Some((step, index, at - DOUBLE_CLICK * 2));
+        click(&mut app, 7);
+        assert_eq!(app.step, Step::Desktop);
+
+        click(&mut app, 7);
+        assert_eq!(app.installer.desktop, 2);
+        assert_eq!(app.step, Step::Graphics);
+    }
+
+    #[test]
+    fn clicks_select_rows_and_press_next() {
+        let mut app = AppState::new();
+        app.step = Step::Desktop;
+        app.list_hitbox = Some(ListHitbox {
+            area: Rect::new(10, 5, 20, 7),
+            offset: 0,
+            len: DESKTOPS.len(),
+        });
+        app.next_button = Some(Rect::new(60, 22, 10, 1));
+        app.on_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 12, 7));
+        assert_eq!(app.installer.desktop, 2);
+        app.on_mouse(mouse(MouseEventKind::ScrollDown, 0, 0));
+        assert_eq!(app.installer.desktop, 3);
+        app.on_mouse(mouse(MouseEventKind::ScrollUp, 0, 0));
+        app.on_mouse(mouse(MouseEventKind::ScrollUp, 0, 0));
+        assert_eq!(app.installer.desktop, 1);
+        app.on_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 65, 22));
+        assert_eq!(app.step, Step::Graphics);
+    }
+
+    #[test]
+    fn q_asks_before_quitting() {
+        let mut app = AppState::new();
+        app.on_key(KeyCode::Char('q'));
+        assert!(app.confirm_quit);
+        assert!(!app.should_quit);
+
+        // Anything but an answer leaves the question up.
+        app.on_key(KeyCode::Enter);
+        assert!(app.confirm_quit);
+        assert_eq!(app.step, Step::Welcome);
+
+        app.on_key(KeyCode::Char('n'));
+        assert!(!app.confirm_quit);
+        assert!(!app.should_quit);
+
+        app.on_key(KeyCode::Char('q'));
+        app.on_key(KeyCode::Char('y'));
+        assert!(app.should_quit);
+    }
+
+    fn disk(name: &str) -> DiskInfo {
+        DiskInfo {
+            name: name.into(),
+            size: "512G".into(),
+            model: String::new(),
+        }
+    }
+
+    #[test]
+    fn keymap_defaults_to_us() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["de".into(), "fr".into(), "us".into()]);
+        assert_eq!(app.selected_keymap, 2);
+        app.step = Step::Keyboard;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.keymap.as_deref(), Some("us"));
+    }
+
+    #[test]
+    fn highlighted_keymap_is_loaded_to_try_it() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["de".into(), "fr".into(), "us".into()]);
+        assert_eq!(app.take_keymap_request(), None);
+        app.step = Step::Keyboard;
+        assert_eq!(app.take_keymap_request().as_deref(), Some("us"));
+        assert_eq!(app.take_keymap_request(), None);
+
+        app.on_key(KeyCode::Tab);
+        app.on_key(KeyCode::Char('z'));
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.take_keymap_request().as_deref(), Some("fr"));
+        // What was typed with the old layout proves nothing about the new one.
+        assert_eq!(app.keymap_test.value(), "");
+    }
+
+    #[test]
+    fn keymap_follows_chosen_language() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["de".into(), "fr".into(), "us".into()]);
+        app.step = Step::Language;
+        app.installer.language = 3;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Locale);
+        assert_eq!(app.keymaps[app.selected_keymap], "de");
+    }
+
+    #[test]
+    fn formats_follow_the_language_until_chosen() {
+        let mut app = AppState::new();
+        let locales = ["de_DE.UTF-8", "en_GB.UTF-8", "en_US.UTF-8"];
+        app.load_locales(Ok(locales.map(String::from).to_vec()));
+        assert_eq!(app.locales[app.selected_locale], "en_US.UTF-8");
+        app.step = Step::Language;
+        app.installer.language = 3;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Locale);
+        assert_eq!(app.locales[app.selected_locale], "de_DE.UTF-8");
+
+        // English words with British dates.
+        app.on_key(KeyCode::Esc);
+        app.installer.language = 0;
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.locales[app.selected_locale], "en_GB.UTF-8");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Keyboard);
+        assert_eq!(app.installer.format_locale(), "en_GB.UTF-8");
+        assert_eq!(app.installer.locale(), "en_US.UTF-8");
+
+        // Once chosen, going back to the language keeps it.
+        app.step = Step::Language;
+        app.installer.language = 3;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.locales[app.selected_locale], "en_GB.UTF-8");
+    }
+
+    #[test]
+    fn space_ticks_more_locales_to_generate() {
+        let mut app = AppState::new();
+        let locales = ["de_DE.UTF-8", "en_GB.UTF-8", "en_US.UTF-8"];
+        app.load_locales(Ok(locales.map(String::from).to_vec()));
+        app.step = Step::Locale;
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.installer.extra_locales, ["de_DE.UTF-8"]);
+        app.on_key(KeyCode::Char(' '));
+        assert!(app.installer.extra_locales.is_empty());
+
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        let generated = app.installer.generated_locales();
+        assert_eq!(generated, ["en_US.UTF-8", "en_GB.UTF-8", "de_DE.UTF-8"]);
+    }
+
+    #[test]
+    fn missing_locale_list_falls_back_to_the_languages() {
+        let mut app = AppState::new();
+        app.load_locales(Err(io::Error::from(io::ErrorKind::NotFound)));
+        assert!(app.locales.iter().any(|locale| locale == "fr_FR.UTF-8"));
+        assert_eq!(app.locales[app.selected_locale], "en_US.UTF-8");
+    }
+
+    #[test]
+    fn keyboard_screen_filters_then_captures_typed_text() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["de".into(), "fr".into(), "us".into()]);
+        app.step = Step::Keyboard;
+        app.on_key(KeyCode::Char('f'));
+        assert_eq!(app.filter, "f");
+        assert_eq!(app.filtered_indices(), Some(vec![1]));
+        assert_eq!(app.keymaps[app.selected_keymap], "fr");
+
+        app.on_key(KeyCode::Tab);
+        for c in "qwz".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.keymap_test.value(), "qw");
+        assert_eq!(app.filter, "f");
+        assert!(!app.should_quit);
+        assert_eq!(app.step, Step::Keyboard);
+
+        app.on_key(KeyCode::Tab);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.keymap.as_deref(), Some("fr"));
+    }
+
+    #[test]
+    fn page_keys_jump_through_timezones() {
+        let mut app = AppState::new();
+        app.step = Step::Timezone;
+        app.load_timezones(Ok((0..25).map(|i| format!("Region/City{i:02}")).collect()));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.timezone_region.as_deref(), Some("Region"));
+        app.on_key(KeyCode::PageDown);
+        assert_eq!(app.selected_timezone, 10);
+        app.on_key(KeyCode::PageDown);
+        app.on_key(KeyCode::PageDown);
+        assert_eq!(app.selected_timezone, 24);
+        app.on_key(KeyCode::PageUp);
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.selected_timezone, 13);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.timezone.as_deref(), Some("Region/City13"));
+        assert_eq!(app.step, Step::Disk);
+    }
+
+    fn zones(names: &[&str]) -> io::Result<Vec<String>> {
+        Ok(names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn timezone_is_picked_by_region_then_city() {
+        let mut app = AppState::new();
+        app.step = Step::Timezone;
+        app.load_timezones(zones(&["America/New_York", "Europe/Berlin", "Europe/Paris"]));
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Timezone);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(app.step, Step::Disk);
+    }
+
+    #[test]
+    fn back_from_cities_returns_to_regions() {
+        let mut app = AppState::new();
+        app.step = Step::Timezone;
+        app.load_timezones(zones(&["Europe/Paris"]));
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Timezone);
+        assert_eq!(app.timezone_region, None);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.step, Step::Keyboard);
+    }
+
+    #[test]
+    fn unreadable_zoneinfo_falls_back_to_utc() {
+        let mut app = AppState::new();
+        app.step = Step::Timezone;
+        app.load_timezones(Err(io::Error::new(io::ErrorKind::NotFound, "missing")));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.timezone.as_deref(), Some("UTC"));
+        assert_eq!(app.step, Step::Disk);
+    }
+
+    #[test]
+    fn enter_stores_highlighted_disk() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        app.disks = vec![disk("sda"), disk("nvme0n1")];
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.disk.as_deref(), Some("/dev/nvme0n1"));
+        assert_eq!(app.step, Step::PartitionScheme);
+    }
+
+    fn partition(name: &str, fstype: &str) -> Partition {
+        Partition {
+            name: name.into(),
+            size: "100G".into(),
+            fstype: fstype.into(),
+            ..Partition::default()
+        }
+    }
+
+    #[test]
+    fn partition_scheme_is_stored_on_enter() {
+        let mut app = AppState::new();
+        app.installer.firmware = Firmware::Bios;
+        app.installer.disk = Some("/dev/sda".into());
+        app.step = Step::PartitionScheme;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.partition_scheme, PartitionScheme::Manual);
+        assert_eq!(app.step, Step::PartitionScheme);
+        assert_eq!(app.needs_partitions().as_deref(), Some("/dev/sda"));
+        app.load_partitions("/dev/sda".into(), Ok(vec![partition("sda1", "ext4")]));
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.mounts, [(partition("sda1", "ext4"), MountPoint::Root)]);
+        // Only erasing the disk formats it, so there is no filesystem to pick.
+        assert_eq!(app.step, Step::Swap);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::PartitionScheme);
+        assert!(app.assigning_mounts);
+        app.on_key(KeyCode::Esc);
+        assert!(!app.assigning_mounts);
+
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Filesystem);
+    }
+
+    #[test]
+    fn mount_points_are_checked_before_moving_on() {
+        let mut app = AppState::new();
+        app.installer.firmware = Firmware::Uefi;
+        app.installer.disk = Some("/dev/sda".into());
+        app.installer.partition_scheme = PartitionScheme::Manual;
+        app.installer.mounts = vec![(partition("sda2", "ext4"), MountPoint::Home)];
+        app.step = Step::PartitionScheme;
+        app.assigning_mounts = true;
+        app.load_partitions("/dev/sda".into(), Err(io::Error::other("lsblk failed")));
+        assert_eq!(app.mount_error(), Some("The disk has no partitions to install to"));
+
+        let partitions = vec![partition("sda1", "vfat"), partition("sda2", "ext4")];
+        app.load_partitions("/dev/sda".into(), Ok(partitions));
+        assert_eq!(app.partition_mounts, [None, Some(MountPoint::Home)]);
+        assert_eq!(app.mount_error(), Some("One partition has to be mounted at /"));
+
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.partition_mounts, [Some(MountPoint::Root), None]);
+        assert_eq!(app.mount_error(), Some("UEFI needs the EFI system partition mounted at /boot"));
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.mount_error(), Some("Two partitions are mounted at the same place"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::PartitionScheme);
+
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.partition_mounts, [Some(MountPoint::Boot), Some(MountPoint::Root)]);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Swap);
+        let mounts: Vec<_> = app.installer.mounts.iter().map(|(p, m)| (p.path(), *m)).collect();
+        assert_eq!(
+            mounts,
+            [("/dev/sda1".into(), MountPoint::Boot), ("/dev/sda2".into(), MountPoint::Root)]
+        );
+    }
+
+    #[test]
+    fn erasing_needs_room_for_the_system() {
+        let mut app = AppState::new();
+        app.disks = vec![DiskInfo { size: "20G".into(), ..disk("sda") }];
+        app.installer.disk = Some("/dev/sda".into());
+        app.step = Step::PartitionScheme;
+        assert_eq!(app.erase_error(), None);
+
+        // 8 GiB of swap would leave root under the minimum.
+        app.step = Step::Swap;
+        app.installer.partition_scheme = PartitionScheme::Erase;
+        app.swap_size.set_value("8");
+        app.selected_swap = app
+            .swap_options()
+            .iter()
+            .position(|swap| matches!(swap, SwapConfig::Partition { .. }))
+            .unwrap();
+        assert_eq!(app.swap_error(), Some("Too little room would be left for the system"));
+
+        app.disks[0].size = "10G".into();
+        assert_eq!(app.erase_error(), Some("The disk is too small for the system"));
+        app.step = Step::PartitionScheme;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::PartitionScheme);
+    }
+
+    #[test]
+    fn btrfs_options_are_validated_before_moving_on() {
+        let mut app = AppState::new();
+        app.step = Step::Filesystem;
+        app.on_key(KeyCode::Char('n'));
+        assert!(!app.installer.btrfs_snapshots, "ext4 has no snapshots");
+
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char('n'));
+        assert!(app.installer.btrfs_snapshots);
+        assert!(app.filesystem_error().is_some());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Filesystem);
+
+        app.on_key(KeyCode::Char('s'));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.filesystem, Filesystem::Btrfs);
+        assert_eq!(app.step, Step::Swap);
+    }
+
+    #[test]
+    fn bootloader_choice_depends_on_firmware() {
+        let mut app = AppState::new();
+        app.step = Step::Bootloader;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.bootloader, Bootloader::Grub);
+        assert_eq!(app.step, Step::Hostname);
+
+        let mut app = AppState::new();
+        app.installer.firmware = Firmware::Bios;
+        app.step = Step::Swap;
+        app.advance();
+        assert_eq!(app.step, Step::Bootloader);
+        assert_eq!(Bootloader::ALL[app.selected_bootloader], Bootloader::Grub);
+        // systemd-boot is shown, but cannot be highlighted or picked.
+        app.on_key(KeyCode::Up);
+        assert_eq!(Bootloader::ALL[app.selected_bootloader], Bootloader::Grub);
+        app.list_hitbox = Some(ListHitbox { area: Rect::new(10, 5, 40, 2), offset: 0, len: 2 });
+        app.on_mouse(mouse(MouseEventKind::Down(MouseButton::Left), 12, 5));
+        assert_eq!(Bootloader::ALL[app.selected_bootloader], Bootloader::Grub);
+        app.selected_bootloader = 0;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Bootloader);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.bootloader, Bootloader::Grub);
+        assert_eq!(app.step, Step::Hostname);
+    }
+
+    #[test]
+    fn swap_file_on_btrfs_needs_subvolumes() {
+        let mut app = AppState::new();
+        app.step = Step::Swap;
+        app.selected_swap = 2;
+        assert_eq!(app.swap_error(), None);
+
+        app.installer.filesystem = Filesystem::Btrfs;
+        assert!(app.swap_error().is_some());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Swap);
+        app.installer.btrfs_subvolumes = true;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.swap, SwapConfig::File { size_gib: FALLBACK_SWAP_GIB });
+    }
+
+    #[test]
+    fn swap_size_is_typed_for_partitions_and_files() {
+        let mut app = AppState::new();
+        app.load_memory(Ok(16 * 1024));
+        assert_eq!(app.swap_size.value(), "8G");
+        app.step = Step::Swap;
+        // Nothing to type for no swap.
+        app.on_key(KeyCode::Char('1'));
+        assert_eq!(app.swap_size.value(), "8G");
+
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Backspace);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.swap_error(), Some("Swap size cannot be empty"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Swap);
+        type_text(&mut app, "12g");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.swap, SwapConfig::Partition { size_gib: 12 });
+        assert_eq!(app.step, Step::Bootloader);
+    }
+
+    #[test]
+    fn swap_screen_offers_what_the_partition_scheme_allows() {
+        let mut app = AppState::new();
+        app.installer.swap = SwapConfig::Zram;
+        app.step = Step::Filesystem;
+        app.advance();
+        assert_eq!(app.step, Step::Swap);
+        assert_eq!(app.selected_swap, 3);
+
+        app.installer.partition_scheme = PartitionScheme::Alongside;
+        app.installer.swap = SwapConfig::File { size_gib: 4 };
+        app.step = Step::Filesystem;
+        app.advance();
+        assert_eq!(app.swap_options(), [SwapConfig::None, SwapConfig::Zram]);
+        assert_eq!(app.selected_swap, 0);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.swap, SwapConfig::Zram);
+    }
+
+    #[test]
+    fn saved_config_prefills_every_screen() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["de".into(), "fr".into(), "us".into()]);
+        app.load_timezones(zones(&["America/New_York", "Europe/Berlin", "Europe/Paris"]));
+        app.load_disks(Ok(vec![disk("sda"), disk("nvme0n1")]));
+        app.prefill(&config::Config {
+            language: "fr_FR.UTF-8".into(),
+            keymap: Some("fr".into()),
+            timezone: Some("Europe/Paris".into()),
+            disk: Some("/dev/nvme0n1".into()),
+            hostname: Some("atelier".into()),
+            username: Some("marie".into()),
+            desktop: "XFCE".into(),
+        });
+        assert_eq!(app.installer.locale(), "fr_FR.UTF-8");
+        assert_eq!(app.selected_keymap, 1);
+        assert_eq!(app.selected_disk, 1);
+        assert_eq!(app.hostname.value(), "atelier");
+        assert_eq!(app.username.value(), "marie");
+        assert_eq!(app.installer.desktop().name, "XFCE");
+
+        // The timezone screen opens the saved region on the saved city.
+        app.step = Step::Timezone;
+        assert_eq!(app.selected_region, 1);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.selected_timezone, 1);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.timezone.as_deref(), Some("Europe/Paris"));
+    }
+
+    #[test]
+    fn unavailable_saved_answers_are_skipped() {
+        let mut app = AppState::new();
+        app.load_keymaps(vec!["us".into()]);
+        app.prefill(&config::Config {
+            language: "xx_XX.UTF-8".into(),
+            keymap: Some("fr".into()),
+            timezone: Some("Mars/Olympus".into()),
+            disk: Some("/dev/sdz".into()),
+            hostname: None,
+            username: None,
+            desktop: "Unity".into(),
+        });
+        assert_eq!(app.installer.language, 0);
+        assert_eq!(app.installer.keymap, None);
+        assert_eq!(app.installer.timezone, None);
+        assert_eq!(app.installer.disk, None);
+        assert_eq!(app.installer.desktop, 0);
+    }
+
+    #[test]
+    fn typing_filters_the_language_list() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        type_text(&mut app, "OL");
+        assert_eq!(app.filter, "OL");
+        // English no longer matches, so the highlight jumps into the results.
+        let visible = app.filtered_indices().unwrap();
+        let names: Vec<&str> = visible.iter().map(|&i| LANGUAGES[i].name).collect();
+        assert_eq!(names, ["Español", "Polski"]);
+        assert_eq!(app.installer.language, visible[0]);
+
+        app.on_key(KeyCode::PageDown);
+        assert_eq!(app.installer.language, visible[1]);
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.installer.language, visible[0]);
+
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.filter, "O");
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.filter, "");
+        assert_eq!(app.step, Step::Language);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::Mirrors);
+    }
+
+    #[test]
+    fn enter_ignores_a_filter_with_no_matches() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        type_text(&mut app, "zzz");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Language);
+    }
+
+    #[test]
+    fn timezone_filter_is_per_list() {
+        let mut app = AppState::new();
+        app.step = Step::Timezone;
+        app.load_timezones(zones(&["America/New_York", "Europe/Berlin", "Europe/Paris"]));
+        type_text(&mut app, "eur");
+        assert_eq!(app.selected_region, 1);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.timezone_region.as_deref(), Some("Europe"));
+        assert_eq!(app.filter, "");
+        type_text(&mut app, "par");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(app.step, Step::Disk);
+        assert_eq!(app.filter, "");
+    }
+
+    #[test]
+    fn failed_disk_detection_is_remembered() {
+        let mut app = AppState::new();
+        app.load_disks(Err(io::Error::new(io::ErrorKind::NotFound, "lsblk not found")));
+        assert!(app.disks.is_empty());
+        assert_eq!(app.disk_error.as_deref(), Some("lsblk not found"));
+    }
+
+    #[test]
+    fn failed_disk_detection_pops_up_and_goes_back() {
+        let mut app = AppState::new();
+        app.load_disks(Err(io::Error::new(io::ErrorKind::NotFound, "lsblk not found")));
+        app.step = Step::Timezone;
+        app.advance();
+        assert_eq!(app.step, Step::Disk);
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.message, "lsblk not found");
+
+        // The key that dismisses the popup does nothing else.
+        app.on_key(KeyCode::Char('q'));
+        assert!(!app.confirm_quit);
+        assert!(app.popup.is_none());
+        assert_eq!(app.step, Step::Timezone);
+    }
+
+    #[test]
+    fn enter_without_disks_stays_put() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Disk);
+        assert_eq!(app.installer.disk, None);
+    }
+
+    #[test]
+    fn hostname_must_be_valid_to_advance() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        app.hostname = TextInput::new();
+        for c in "Bad_Host".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Hostname);
+        assert_eq!(app.installer.hostname, None);
+
+        app.hostname = TextInput::new();
+        for c in "endeavour".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::UserAccount);
+        assert_eq!(app.installer.hostname.as_deref(), Some("endeavour"));
+    }
+
+    #[test]
+    fn hostname_starts_on_the_default() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.hostname.as_deref(), Some(DEFAULT_HOSTNAME));
+    }
+
+    #[test]
+    fn username_must_be_valid_to_advance() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        for c in "1alice".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::BackTab);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::UserAccount);
+
+        app.on_key(KeyCode::Tab);
+        app.on_key(KeyCode::Home);
+        app.on_key(KeyCode::Right);
+        app.on_key(KeyCode::Backspace);
+        assert_eq!(app.username.value(), "alice");
+        app.password = TextInput::masked();
+        app.password.insert_char('x');
+        app.password_confirm.set_value("x");
+        app.on_key(KeyCode::BackTab);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.username.as_deref(), Some("alice"));
+        assert_eq!(app.step, Step::Desktop);
+    }
+
+    fn type_text(app: &mut AppState, text: &str) {
+        for c in text.chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn tab_cycles_account_focus() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        app.on_key(KeyCode::BackTab);
+        assert_eq!(app.account_focus, 4);
+        for expected in [0, 1, 2, 3, 4, 0] {
+            app.on_key(KeyCode::Tab);
+            assert_eq!(app.account_focus, expected);
+        }
+        type_text(&mut app, "alice");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "Alice Liddell");
+        assert_eq!(app.username.value(), "alice");
+        assert_eq!(app.full_name.value(), "Alice Liddell");
+    }
+
+    #[test]
+    fn enter_moves_to_the_next_field_until_the_last() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        for expected in 1..=4 {
+            app.on_key(KeyCode::Enter);
+            assert_eq!(app.account_focus, expected);
+        }
+        // The last field confirms, here refusing the empty account.
+        app.on_key(KeyCode::Enter);
+        assert_eq!((app.step, app.account_focus), (Step::UserAccount, 4));
+
+        app.step = Step::Disk;
+        app.disks = vec![disk("sda")];
+        app.on_key(KeyCode::Char('e'));
+        app.disk_focus = 0;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.disk_focus, 1);
+        type_text(&mut app, "correct horse");
+        app.on_key(KeyCode::Enter);
+        type_text(&mut app, "correct horse");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::PartitionScheme);
+    }
+
+    #[test]
+    fn system_account_names_are_taken() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        app.existing_users = vec!["root".into(), "http".into()];
+        type_text(&mut app, "http");
+        app.account_focus = 2;
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.account_error(), Some("Username is taken by a system account"));
+        assert_eq!(app.step, Step::UserAccount);
+
+        app.username.set_value("httpd");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Desktop);
+    }
+
+    #[test]
+    fn passwords_must_match_to_advance() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        type_text(&mut app, "alice");
+        app.account_focus = 2;
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "hunter3");
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.account_error(), Some("Passwords do not match"));
+        assert_eq!(app.step, Step::UserAccount);
+
+        app.on_key(KeyCode::BackTab);
+        app.on_key(KeyCode::Backspace);
+        type_text(&mut app, "2");
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Desktop);
+        assert_eq!(app.installer.password.as_ref().map(Secret::expose), Some("hunter2"));
+        assert_eq!(app.installer.full_name, None);
+        assert_eq!(app.password.value(), "");
+        assert_eq!(app.password_confirm.value(), "");
+    }
+
+    #[test]
+    fn root_can_get_its_own_password() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        type_text(&mut app, "alice");
+        app.account_focus = 2;
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        app.on_key(KeyCode::Char(' '));
+        assert!(app.separate_root);
+        assert_eq!(app.account_error(), Some("Root password cannot be empty"));
+
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "toor");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "tor");
+        assert_eq!(app.account_error(), Some("Root passwords do not match"));
+        app.on_key(KeyCode::Tab);
+        assert_eq!(app.account_focus, 0);
+
+        app.root_password_confirm.set_value("toor");
+        app.on_key(KeyCode::BackTab);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Desktop);
+        assert_eq!(app.installer.root_password.as_ref().map(Secret::expose), Some("toor"));
+        assert_eq!(app.root_password.value(), "");
+    }
+
+    #[test]
+    fn root_is_locked_by_default() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        type_text(&mut app, "alice");
+        app.account_focus = 2;
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "hunter2");
+        app.on_key(KeyCode::Tab);
+        // Switching to a root password and back drops whatever was typed.
+        app.on_key(KeyCode::Char(' '));
+        app.root_password.set_value("toor");
+        app.on_key(KeyCode::Char(' '));
+        assert!(!app.separate_root);
+        assert_eq!(app.root_password.value(), "");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Desktop);
+        assert_eq!(app.installer.root_password, None);
+    }
+
+    #[test]
+    fn passphrases_must_match_to_encrypt() {
+        let mut app = AppState::new();
+        app.load_disks(Ok(vec![disk("sda")]));
+        app.step = Step::Disk;
+        app.on_key(KeyCode::Char('e'));
+        type_text(&mut app, "short");
+        assert_eq!(app.encryption_error(), Some("Passphrase must be at least 8 characters"));
+        type_text(&mut app, " but fine");
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "short but fin");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.encryption_error(), Some("Passphrases do not match"));
+        assert_eq!(app.step, Step::Disk);
+
+        type_text(&mut app, "e");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::PartitionScheme);
+        let encryption = app.installer.encryption.as_ref().unwrap();
+        assert_eq!(encryption.passphrase.expose(), "short but fine");
+        assert_eq!(app.passphrase.value(), "");
+    }
+
+    #[test]
+    fn encryption_is_off_until_toggled() {
+        let mut app = AppState::new();
+        app.load_disks(Ok(vec![disk("sda")]));
+        app.step = Step::Disk;
+        app.on_key(KeyCode::Char('e'));
+        app.on_key(KeyCode::Tab);
+        app.on_key(KeyCode::Tab);
+        assert_eq!(app.disk_focus, 0);
+        app.on_key(KeyCode::Char('e'));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::PartitionScheme);
+        assert_eq!(app.installer.encryption, None);
+    }
+
+    #[test]
+    fn esc_goes_back_from_text_screens() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        app.on_key(KeyCode::Left);
+        assert_eq!(app.step, Step::UserAccount);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::Hostname);
+    }
+
+    #[test]
+    fn desktop_choice_is_kept() {
+        let mut app = AppState::new();
+        app.step = Step::Desktop;
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Graphics);
+        assert_eq!(app.installer.desktop().name, "GNOME");
+    }
+
+    #[test]
+    fn driver_defaults_to_the_usual_one_for_the_card() {
+        let mut app = AppState::new();
+        app.load_hardware(Ok("cpu".into()), Ok(vec![GpuVendor::Intel, GpuVendor::Nvidia]));
+        app.step = Step::Graphics;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Packages);
+        assert_eq!(app.installer.driver.unwrap().name, "nvidia-open");
+
+        app.on_key(KeyCode::Left);
+        app.on_key(KeyCode::Char('j'));
+        app.on_key(KeyCode::Char('j'));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.installer.driver.unwrap().packages, ["mesa", "xf86-video-nouveau"]);
+        let commands = install::build_commands(&app.installer);
+        let pacstrap = commands.iter().find(|c| c.starts_with("pacstrap")).unwrap();
+        assert!(pacstrap.contains(" mesa xf86-video-nouveau"), "{pacstrap}");
+    }
+
+    #[test]
+    fn summary_is_confirmed_with_enter() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.on_key(KeyCode::Char('y'));
+        assert_eq!(app.step, Step::Summary);
+        app.on_key(KeyCode::Left);
+        assert_eq!(app.step, Step::ExtraPackages);
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+    }
+
+    #[test]
+    fn installing_waits_for_the_disk_name_to_be_typed() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.installer.disk = Some("/dev/sda".into());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.erase_confirm.as_ref().map(TextInput::value), Some(""));
+        // Letters go to the field rather than to the summary's keys.
+        for c in "sdb".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+
+        app.on_key(KeyCode::Esc);
+        assert!(app.erase_confirm.is_none() && app.command_review.is_none());
+        app.on_key(KeyCode::Enter);
+        for c in "sda".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+        assert!(app.erase_confirm.is_none());
+    }
+
+    #[test]
+    fn summary_waits_for_the_network_before_installing() {
+        let mut app = AppState::new();
+        app.connectivity = Connectivity::Online;
+        app.step = Step::ExtraPackages;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+        assert!(app.needs_network_check());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+
+        tx.send(CheckUpdate::Finished(Err(refused()))).unwrap();
+        app.on_tick();
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.retry, Some(Retry::NetworkCheck));
+        assert!(popup.message.contains("refused"), "{}", popup.message);
+        app.on_key(KeyCode::Char('r'));
+        assert!(app.popup.is_none());
+        assert!(app.needs_network_check());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        tx.send(CheckUpdate::Finished(Ok(()))).unwrap();
+        app.on_tick();
+        assert!(app.popup.is_none());
+
+        // Then the clock is set before anything is installed.
+        assert!(app.needs_time_sync());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_time_sync(rx);
+        assert!(app.busy());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+        tx.send(Ok(())).unwrap();
+        app.on_tick();
+        assert_eq!(app.time_sync, TimeSync::Synced);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+    }
+
+    #[test]
+    fn unsynced_clock_is_warned_about_but_can_be_ignored() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_time_sync(rx);
+        tx.send(Err(TimeSyncError::TimedOut(Duration::from_secs(15)))).unwrap();
+        app.on_tick();
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.retry, Some(Retry::TimeSync));
+        assert!(popup.message.contains("not synchronized after 15 seconds"));
+
+        app.on_key(KeyCode::Char('r'));
+        assert!(app.needs_time_sync());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_time_sync(rx);
+        tx.send(Err(TimeSyncError::Failed("no NTP servers".into()))).unwrap();
+        app.on_tick();
+        app.on_key(KeyCode::Esc);
+        assert!(app.popup.is_none());
+        assert!(!app.needs_time_sync());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+    }
+
+    #[test]
+    fn skipped_clock_sync_lets_the_install_start() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        assert!(app.needs_time_sync());
+        app.skip_time_sync();
+        assert!(!app.needs_time_sync());
+        assert!(!app.busy());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+    }
+
+    #[test]
+    fn command_output_fills_the_log_up_to_its_capacity() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::Log("pacstrap -K /mnt base".into())).unwrap();
+        for i in 0..INSTALL_LOG_LINES + 5 {
+            tx.send(InstallEvent::Output(format!("line {i}"))).unwrap();
+        }
+        app.attach_install(rx);
+        app.on_tick();
+        assert_eq!(app.install_message, "pacstrap -K /mnt base");
+        let log: Vec<&str> = app.install_log.visible(usize::MAX).collect();
+        assert_eq!(log.len(), INSTALL_LOG_LINES);
+        assert_eq!(log[0], "line 5");
+        assert_eq!(log.last(), Some(&"line 1004"));
+    }
+
+    #[test]
+    fn install_time_left_is_estimated_from_the_progress_made() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_install(rx);
+        let (started, _) = app.install_started.unwrap();
+        let later = started + Duration::from_secs(90);
+        assert_eq!(app.install_timing(later), Some((Duration::from_secs(90), None)));
+
+        tx.send(InstallEvent::Progress(30)).unwrap();
+        app.on_tick();
+        let (_, left) = app.install_timing(later).unwrap();
+        assert_eq!(left.map(|left| left.as_secs()), Some(210));
+
+        // A retry is timed from where it picked up.
+        app.install_started = Some((started, 0.2));
+        let (_, left) = app.install_timing(later).unwrap();
+        assert_eq!(left.map(|left| left.as_secs()), Some(630));
+
+        tx.send(InstallEvent::Done).unwrap();
+        app.on_tick();
+        assert_eq!(app.install_timing(later), None);
+    }
+
+    #[test]
+    fn failed_install_keeps_the_error_and_stops() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::Progress(40)).unwrap();
+        tx.send(InstallEvent::Failed("pacstrap exited with status 1".into()))
+            .unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        assert_eq!(app.step, Step::Installing);
+        assert_eq!(app.progress, 0.4);
+        assert_eq!(app.install_error.as_deref(), Some("pacstrap exited with status 1"));
+        assert!(!app.needs_install_worker());
+        assert_eq!(app.popup.as_ref().unwrap().title, "Installation failed");
+
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+    }
+
+    #[test]
+    fn esc_cancels_the_install_once_confirmed() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_install(rx);
+        app.on_key(KeyCode::Esc);
+        assert!(app.confirm_cancel);
+        app.on_key(KeyCode::Char('n'));
+        assert!(!app.confirm_cancel && !app.cancelling());
+
+        app.on_key(KeyCode::Esc);
+        app.on_key(KeyCode::Char('y'));
+        assert!(!app.confirm_cancel);
+        assert!(app.cancelling());
+        assert_eq!(app.step, Step::Installing);
+        // Asking again while it winds down does nothing.
+        app.on_key(KeyCode::Esc);
+        assert!(!app.confirm_cancel);
+
+        tx.send(InstallEvent::Cancelled(vec![InstallPhase::Partition])).unwrap();
+        app.on_tick();
+        assert!(!app.cancelling());
+        assert!(!app.needs_install_worker());
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.title, "Installation cancelled");
+        assert!(popup.message.contains("Already done: Partitioning disk."));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+    }
+
+    fn pacstrap_failure() -> CommandFailure {
+        CommandFailure {
+            index: 6,
+            phase: InstallPhase::Pacstrap,
+            command: "pacstrap -K /mnt base".into(),
+            code: Some(1),
+            stderr: vec!["error: failed retrieving file 'base.pkg.tar.zst'".into()],
+        }
+    }
+
+    #[test]
+    fn failed_command_can_be_retried() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::CommandFailed(pacstrap_failure())).unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.retry, Some(Retry::Install { from: 6 }));
+        assert!(popup.message.contains("`pacstrap -K /mnt base` exited with status 1"));
+        assert!(popup.message.contains("failed retrieving file 'base.pkg.tar.zst'\n\nAttempt 1"));
+        assert!(!app.needs_install_worker());
+
+        // Only retry or abort close it.
+        app.on_key(KeyCode::Enter);
+        assert!(app.popup.is_some());
+        app.on_key(KeyCode::Char('r'));
+        assert!(app.popup.is_none());
+        assert_eq!(app.step, Step::Installing);
+        assert_eq!(app.install_from, 6);
+        assert!(app.needs_install_worker());
+    }
+
+    #[test]
+    fn failed_command_can_be_abandoned() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::CommandFailed(pacstrap_failure())).unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        app.on_key(KeyCode::Char('a'));
+        assert_eq!(app.step, Step::Summary);
+        assert!(app.install_error.is_some());
+
+        // Starting over runs every command again.
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+        assert_eq!(app.install_from, 0);
+        assert!(app.needs_install_worker());
+    }
+
+    #[test]
+    fn retries_stop_at_the_attempt_limit() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        // Stands in for the worker main starts: it fails the same command
+        // every time.
+        let fail_again = |app: &mut AppState| {
+            let (tx, rx) = std::sync::mpsc::channel();
+            tx.send(InstallEvent::CommandFailed(pacstrap_failure())).unwrap();
+            app.attach_install(rx);
+            app.on_tick();
+            app.popup.clone().unwrap()
+        };
+        for attempt in 1..install::MAX_ATTEMPTS {
+            let popup = fail_again(&mut app);
+            assert_eq!(app.install_attempts, attempt);
+            assert!(popup.message.ends_with(&format!("Attempt {attempt} of 3.")));
+            app.on_key(KeyCode::Char('r'));
+            assert_eq!((app.step, app.install_from), (Step::Installing, 6));
+        }
+        let popup = fail_again(&mut app);
+        assert_eq!(popup.retry, None);
+        assert!(popup.message.ends_with("Gave up after 3 attempts."));
+        app.on_key(KeyCode::Char('r'));
+        assert_eq!(app.step, Step::Summary);
+
+        // A failure further on gets attempts of its own.
+        app.step = Step::Installing;
+        app.install_from = 6;
+        app.install_attempts = 2;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::CommandFailed(CommandFailure { index: 7, ..pacstrap_failure() }))
+            .unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        assert_eq!(app.install_attempts, 1);
+    }
+
+    #[test]
+    fn only_passing_failures_are_retried() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let failure =
+            CommandFailure { index: 0, phase: InstallPhase::Partition, ..pacstrap_failure() };
+        tx.send(InstallEvent::CommandFailed(failure)).unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        let popup = app.popup.clone().unwrap();
+        assert_eq!(popup.retry, None);
+        assert!(!popup.message.contains("Attempt"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+    }
+
+    fn networks() -> Vec<WifiNetwork> {
+        vec![
+            WifiNetwork { ssid: "Home".into(), signal: 80 },
+            WifiNetwork { ssid: "Café".into(), signal: 40 },
+        ]
+    }
+
+    #[test]
+    fn wifi_connects_before_moving_on() {
+        let mut app = AppState::new();
+        app.step = Step::Hardware;
+        app.load_wifi(Ok(networks()));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Wifi);
+        app.on_key(KeyCode::Tab);
+        type_text(&mut app, "letmein");
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.wifi_status, WifiStatus::Requested);
+        let (ssid, passphrase) = app.take_wifi_request().unwrap();
+        assert_eq!((ssid.as_str(), passphrase.expose()), ("Home", "letmein"));
+        assert_eq!(app.wifi_passphrase.value(), "");
+        assert_eq!(app.take_wifi_request(), None);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_wifi_connect(rx);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Wifi);
+        tx.send(Err("Secrets were required".into())).unwrap();
+        app.on_tick();
+        assert_eq!(app.wifi_status, WifiStatus::Failed("Secrets were required".into()));
+
+        app.on_key(KeyCode::Enter);
+        app.take_wifi_request().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_wifi_connect(rx);
+        tx.send(Ok(())).unwrap();
+        app.on_tick();
+        assert_eq!(app.wifi_status, WifiStatus::Connected("Home".into()));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Network);
+    }
+
+    #[test]
+    fn wifi_is_skipped_when_wired_or_asked() {
+        let mut app = AppState::new();
+        app.step = Step::Hardware;
+        app.wired = true;
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Network);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::Hardware);
+
+        app.wired = false;
+        app.load_wifi(Ok(networks()));
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Char('s'));
+        assert_eq!(app.step, Step::Network);
+        assert_eq!(app.wifi_status, WifiStatus::Idle);
+    }
+
+    fn refused() -> ConnError {
+        ConnError::Connect(io::Error::from(io::ErrorKind::ConnectionRefused))
+    }
+
+    #[test]
+    fn network_check_runs_on_entry_and_can_be_retried() {
+        let mut app = AppState::new();
+        app.step = Step::Hardware;
+        app.wired = true;
+        assert!(!app.needs_network_check());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Network);
+        assert!(app.needs_network_check());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        assert!(!app.needs_network_check());
+        app.on_key(KeyCode::Char('r'));
+        assert_eq!(app.connectivity, Connectivity::Checking);
+        tx.send(CheckUpdate::Retrying { attempt: 2, attempts: 3 }).unwrap();
+        app.on_tick();
+        assert_eq!(app.network_retry, Some((2, 3)));
+        assert_eq!(app.connectivity, Connectivity::Checking);
+        tx.send(CheckUpdate::Finished(Err(refused()))).unwrap();
+        app.on_tick();
+        assert_eq!(app.connectivity, Connectivity::Offline);
+        assert!(app.network_rx.is_none());
+        assert_eq!(app.network_retry, None);
+
+        app.on_key(KeyCode::Char('r'));
+        assert!(app.needs_network_check());
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        tx.send(CheckUpdate::Finished(Ok(()))).unwrap();
+        app.on_tick();
+        assert_eq!(app.connectivity, Connectivity::Online);
+        // Being offline doesn't stop the wizard.
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Mirrors);
+    }
+
+    #[test]
+    fn idle_tick_leaves_the_screen_clean() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.dirty = false;
+        app.on_tick();
+        assert!(!app.dirty);
+        assert_eq!(app.spinner, Spinner::braille());
+
+        app.on_key(KeyCode::Down);
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn ticks_redraw_while_installing() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (_tx, rx) = std::sync::mpsc::channel::<InstallEvent>();
+        app.attach_install(rx);
+        app.dirty = false;
+        app.on_tick();
+        assert!(app.dirty);
+        assert_eq!(app.spinner.frame(), crate::widgets::spinner::BRAILLE[1]);
+    }
+
+    #[test]
+    fn vanished_install_thread_is_a_failure() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        tx.send(InstallEvent::Progress(40)).unwrap();
+        app.attach_install(rx);
+        app.on_tick();
+        // The worker dies halfway through.
+        drop(tx);
+        app.on_tick();
+        assert_eq!(app.step, Step::Installing);
+        assert!(app.install_error.is_some());
+        assert!(!app.needs_install_worker());
+        assert_eq!(app.popup.as_ref().unwrap().title, "Installation failed");
+
+        // The popup leads back out rather than leaving the screen stuck.
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+    }
+
+    #[test]
+    fn quitting_warns_of_what_the_installation_did() {
+        let mut app = AppState::new();
+        assert_eq!(app.quit_warning(), "Nothing has been written to disk yet.");
+        app.step = Step::Installing;
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_install(rx);
+        assert!(app.quit_warning().contains("still running"));
+
+        tx.send(InstallEvent::Log("wipefs -a /dev/sda".into())).unwrap();
+        tx.send(InstallEvent::Cancelled(vec![InstallPhase::Partition])).unwrap();
+        app.on_tick();
+        // Both back on the summary and still on the failed install screen.
+        assert!(app.quit_warning().contains("already been changed"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+        assert!(app.quit_warning().contains("already been changed"));
+    }
+
+    #[test]
+    fn q_quits_while_installing() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (_tx, rx) = std::sync::mpsc::channel();
+        app.attach_install(rx);
+        app.on_tick();
+        app.on_key(KeyCode::Char('q'));
+        app.on_key(KeyCode::Char('y'));
+        assert!(app.should_quit);
+    }
+
+    #[test]
+    fn answers_are_saved_when_summary_is_confirmed() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("artemis-app-{}.toml", std::process::id()));
+        let json = dir.join(format!("artemis-app-{}.json", std::process::id()));
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.answers_path = Some(path.clone());
+        app.export_path = Some(json.clone());
+        app.installer.hostname = Some("atelier".into());
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let exported = std::fs::read_to_string(&json).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&json).unwrap();
+        assert!(saved.contains("hostname = \"atelier\""));
+        assert!(exported.contains("\"hostname\": \"atelier\""));
+    }
+
+    #[test]
+    fn failed_save_is_reported_before_installing_anyway() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.answers_path = Some(PathBuf::from("/nonexistent/dir/answers.toml"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+        assert!(app.answers_error.as_deref().unwrap().contains("/nonexistent/dir"));
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Installing);
+    }
+
+    #[test]
+    fn install_events_are_streamed_until_done() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.time_sync = TimeSync::Synced;
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Enter);
+        app.on_key(KeyCode::Esc);
+        assert_eq!(app.step, Step::Installing);
+        assert!(app.needs_install_worker());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (finish_tx, finish_rx) = std::sync::mpsc::channel::<()>();
+        let producer = std::thread::spawn(move || {
+            tx.send(InstallEvent::Log("Partitioning disk…".into())).unwrap();
+            tx.send(InstallEvent::Progress(10)).unwrap();
+            tx.send(InstallEvent::Log("Installing base system…".into())).unwrap();
+            tx.send(InstallEvent::Progress(50)).unwrap();
+            finish_rx.recv().unwrap();
+            tx.send(InstallEvent::Done).unwrap();
+        });
+        app.attach_install(rx);
+        assert!(!app.needs_install_worker());
+
+        // Both reports arrive while the producer is still running.
+        while app.progress < 0.5 {
+            app.on_tick();
+        }
+        assert_eq!(app.phase, InstallPhase::Pacstrap);
+        assert_eq!(app.install_message, "Installing base system…");
+        let log: Vec<&str> = app.install_log.visible(usize::MAX).collect();
+        assert_eq!(log, ["Partitioning disk…", "Installing base system…"]);
+        assert_eq!(app.step, Step::Installing);
+
+        finish_tx.send(()).unwrap();
+        producer.join().unwrap();
+        app.on_tick();
+        assert_eq!(app.step, Step::Completion);
+    }
+
+    fn mirrorlist() -> Vec<Mirror> {
+        mirrors::parse_mirrorlist(
+            "## Worldwide\n\
+             Server = https://world.example/$repo\n\
+             ## Germany\n\
+             #Server = https://de-1.example/$repo\n\
+             Server = https://de-2.example/$repo\n",
+        )
+    }
+
+    #[test]
+    fn mirrors_are_filtered_by_country() {
+        let mut app = AppState::new();
+        app.step = Step::Mirrors;
+        app.load_mirrors(Ok(mirrorlist()));
+        assert!(app.mirror_countries().is_empty());
+        assert_eq!(app.shown_mirrors().len(), 3);
+
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        assert_eq!(app.mirror_countries(), ["Germany"]);
+        let urls: Vec<String> = app.shown_mirrors().into_iter().map(|m| m.url).collect();
+        assert_eq!(urls, ["https://de-2.example/$repo", "https://de-1.example/$repo"]);
+
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Language);
+        assert_eq!(
+            app.installer.mirrorlist.as_deref(),
+            Some(
+                "## Germany\n\
+                 Server = https://de-2.example/$repo\n\
+                 Server = https://de-1.example/$repo\n"
+            )
+        );
+    }
+
+    #[test]
+    fn several_countries_can_be_ticked() {
+        let mut app = AppState::new();
+        app.step = Step::Mirrors;
+        let mut list = mirrorlist();
+        list.extend(mirrors::parse_mirrorlist("## France\nServer = https://fr.example/$repo\n"));
+        app.load_mirrors(Ok(list));
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.mirror_countries(), ["Worldwide", "France"]);
+        let urls: Vec<String> = app.shown_mirrors().into_iter().map(|m| m.url).collect();
+        assert_eq!(urls, ["https://world.example/$repo", "https://fr.example/$repo"]);
+
+        // Moving the highlight keeps the ticked countries' ranking.
+        app.ranked_mirrors = Some(Vec::new());
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.ranked_mirrors, Some(Vec::new()));
+        assert_eq!(app.mirror_countries(), ["Worldwide", "France"]);
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.ranked_mirrors, None);
+        assert_eq!(app.mirror_countries(), ["Worldwide"]);
+
+        // "All countries" clears the ticks.
+        app.selected_country = 0;
+        app.on_key(KeyCode::Char(' '));
+        assert!(app.mirror_countries().is_empty());
+        assert_eq!(app.shown_mirrors().len(), 4);
+    }
+
+    #[test]
+    fn space_ticks_packages_while_letters_filter_them() {
+        let mut app = AppState::new();
+        app.step = Step::Packages;
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.installer.extra_packages, ["base-devel"]);
+
+        for c in "vim".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        assert_eq!(app.filter, "vim");
+        assert_eq!(PACKAGES[app.selected_package].name, "neovim");
+        app.on_key(KeyCode::Down);
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.installer.extra_packages, ["base-devel", "vim"]);
+
+        // Nothing is ticked while the filter hides every package.
+        app.on_key(KeyCode::Char('x'));
+        app.on_key(KeyCode::Char(' '));
+        assert_eq!(app.installer.extra_packages, ["base-devel", "vim"]);
+
+        app.on_key(KeyCode::Esc);
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::ExtraPackages);
+        assert_eq!(app.installer.extra_packages, ["base-devel", "vim"]);
+    }
+
+    #[test]
+    fn ticking_a_package_twice_unticks_it() {
+        let mut app = AppState::new();
+        app.step = Step::Packages;
+        app.on_key(KeyCode::Char(' '));
+        app.on_key(KeyCode::Char(' '));
+        assert!(app.installer.extra_packages.is_empty());
+    }
+
+    #[test]
+    fn typed_package_names_are_kept_unless_invalid() {
+        let mut app = AppState::new();
+        app.step = Step::ExtraPackages;
+        for c in "tmux  Steam q ncdu".chars() {
+            app.on_key(KeyCode::Char(c));
+        }
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Summary);
+        assert_eq!(app.installer.typed_packages, ["tmux", "q", "ncdu"]);
+
+        // Emptying the field takes them all back out.
+        app.back();
+        app.package_names = TextInput::new();
+        app.on_key(KeyCode::Enter);
+        assert!(app.installer.typed_packages.is_empty());
+    }
+
+    #[test]
+    fn reflector_ranking_replaces_the_shown_mirrors() {
+        let mut app = AppState::new();
+        app.step = Step::Mirrors;
+        app.load_mirrors(Ok(mirrorlist()));
+        app.selected_country = 2;
+        assert!(!app.needs_mirror_ranking());
+        app.on_key(KeyCode::Char('r'));
+        assert!(app.needs_mirror_ranking());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_mirror_ranking(rx);
+        assert!(app.busy());
+        // Neither the country nor the step changes under a running ranking.
+        app.on_key(KeyCode::Up);
+        app.on_key(KeyCode::Enter);
+        assert_eq!((app.step, app.selected_country), (Step::Mirrors, 2));
+
+        let fastest = Mirror {
+            country: Some("Germany".into()),
+            url: "https://fast.example/$repo".into(),
+            active: true,
+        };
+        tx.send(Ok(vec![fastest.clone()])).unwrap();
+        app.on_tick();
+        assert_eq!(app.ranking, Ranking::Idle);
+        assert_eq!(app.shown_mirrors(), [fastest]);
+
+        // Another country drops the ranking.
+        app.on_key(KeyCode::Up);
+        assert_eq!(app.ranked_mirrors, None);
+        assert_eq!(app.shown_mirrors().len(), 1);
+    }
+
+    #[test]
+    fn failed_ranking_is_reported_and_unreadable_mirrorlist_is_kept() {
+        let mut app = AppState::new();
+        app.step = Step::Mirrors;
+        app.load_mirrors(Err(io::Error::from(io::ErrorKind::NotFound)));
+        assert!(app.mirror_error.is_some());
+        app.on_key(KeyCode::Char('r'));
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.attach_mirror_ranking(rx);
+        tx.send(Err("reflector failed: no mirrors".into())).unwrap();
+        app.on_tick();
+        assert_eq!(app.ranking, Ranking::Failed("reflector failed: no mirrors".into()));
+
+        app.on_key(KeyCode::Enter);
+        assert_eq!(app.step, Step::Language);
+        assert_eq!(app.installer.mirrorlist, None);
+    }
+}
@@ -0,0 +1,474 @@
+//! Fixed sets of options the wizard offers, and what each one installs.
+
+use crate::system::hardware::GpuVendor;
+
+/// A desktop environment or window manager the user can install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DesktopOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Packages pulled in when this option is chosen.
+    pub packages: &'static [&'static str],
+}
+
+pub const DESKTOPS: &[DesktopOption] = &[
+    DesktopOption {
+        name: "KDE Plasma",
+        description: "Feature-rich, highly customizable desktop",
+        packages: &["plasma-meta", "konsole", "dolphin", "sddm"],
+    },
+    DesktopOption {
+        name: "GNOME",
+        description: "Modern, streamlined desktop with its own workflow",
+        packages: &["gnome", "gnome-terminal", "gdm"],
+    },
+    DesktopOption {
+        name: "XFCE",
+        description: "Lightweight, traditional desktop",
+        packages: &["xfce4", "xfce4-goodies", "lightdm", "lightdm-gtk-greeter"],
+    },
+    DesktopOption {
+        name: "Cinnamon",
+        description: "Familiar desktop with a classic panel and menu",
+        packages: &["cinnamon", "gnome-terminal", "lightdm", "lightdm-slick-greeter"],
+    },
+    DesktopOption {
+        name: "i3",
+        description: "Keyboard-driven tiling window manager",
+        packages: &["i3-wm", "i3status", "dmenu", "xterm", "lightdm", "lightdm-gtk-greeter"],
+    },
+    DesktopOption {
+        name: "Sway",
+        description: "Tiling Wayland compositor compatible with i3",
+        packages: &["sway", "swaybg", "swaylock", "foot", "wmenu"],
+    },
+    DesktopOption {
+        name: "No desktop (base only)",
+        description: "Command line only; add a desktop yourself later",
+        packages: &[],
+    },
+];
+
+/// A set of graphics drivers offered for the detected card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DriverOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Packages added to the installation when this option is chosen.
+    pub packages: &'static [&'static str],
+}
+
+const NVIDIA_DRIVERS: &[DriverOption] = &[
+    DriverOption {
+        name: "nvidia-open",
+        description: "NVIDIA's open kernel modules, for GeForce 16 series cards and newer",
+        packages: &["nvidia-open", "nvidia-utils"],
+    },
+    DriverOption {
+        name: "nvidia",
+        description: "NVIDIA's proprietary driver, for older cards it still supports",
+        packages: &["nvidia", "nvidia-utils"],
+    },
+    DriverOption {
+        name: "nouveau",
+        description: "The community's open source driver; slower but works everywhere",
+        packages: &["mesa", "xf86-video-nouveau"],
+    },
+];
+
+const AMD_DRIVERS: &[DriverOption] = &[
+    DriverOption {
+        name: "mesa",
+        description: "Open source OpenGL and Vulkan drivers; right for nearly every card",
+        packages: &["mesa", "vulkan-radeon"],
+    },
+    DriverOption {
+        name: "mesa + xf86-video-amdgpu",
+        description: "Adds the X.Org driver, for features the generic one lacks",
+        packages: &["mesa", "vulkan-radeon", "xf86-video-amdgpu"],
+    },
+];
+
+const INTEL_DRIVERS: &[DriverOption] = &[
+    DriverOption {
+        name: "mesa",
+        description: "Open source OpenGL and Vulkan drivers; right for nearly every chip",
+        packages: &["mesa", "vulkan-intel"],
+    },
+    DriverOption {
+        name: "mesa + xf86-video-intel",
+        description: "Adds the old X.Org driver, which some older chips run better with",
+        packages: &["mesa", "vulkan-intel", "xf86-video-intel"],
+    },
+];
+
+const GENERIC_DRIVERS: &[DriverOption] = &[DriverOption {
+    name: "mesa",
+    description: "Generic open source drivers, for virtual machines and unknown cards",
+    packages: &["mesa"],
+}];
+
+/// The drivers worth offering for a machine with the graphics cards
+/// `gpus`, the sensible default first. With a second, discrete card it is
+/// the one that needs choosing for; the integrated one works with mesa,
+/// which every option brings in directly or through the desktop.
+pub fn driver_options(gpus: &[GpuVendor]) -> &'static [DriverOption] {
+    let has = |vendor| gpus.contains(&vendor);
+    if has(GpuVendor::Nvidia) {
+        NVIDIA_DRIVERS
+    } else if has(GpuVendor::Amd) {
+        AMD_DRIVERS
+    } else if has(GpuVendor::Intel) {
+        INTEL_DRIVERS
+    } else {
+        GENERIC_DRIVERS
+    }
+}
+
+/// An extra package offered on the packages screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageOption {
+    /// The package's name in the repositories, which is what gets installed.
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Commonly wanted packages that no desktop brings in, by name.
+pub const PACKAGES: &[PackageOption] = &[
+    PackageOption { name: "base-devel", description: "Compilers and tools for building packages" },
+    PackageOption { name: "bash-completion", description: "Tab completion for many commands" },
+    PackageOption { name: "chromium", description: "Open source web browser behind Chrome" },
+    PackageOption { name: "cups", description: "Printing system" },
+    PackageOption { name: "docker", description: "Container runtime" },
+    PackageOption { name: "fastfetch", description: "Shows system information in a terminal" },
+    PackageOption { name: "firefox", description: "Mozilla's web browser" },
+    PackageOption { name: "flatpak", description: "Sandboxed applications from Flathub" },
+    PackageOption { name: "gimp", description: "Image editor" },
+    PackageOption { name: "git", description: "Distributed version control" },
+    PackageOption { name: "htop", description: "Interactive process viewer" },
+    PackageOption { name: "libreoffice-fresh", description: "Office suite" },
+    PackageOption { name: "man-db", description: "Manual pages and the man command" },
+    PackageOption { name: "mpv", description: "Lightweight video player" },
+    PackageOption { name: "nano", description: "Simple terminal text editor" },
+    PackageOption { name: "neovim", description: "Extensible Vim-based text editor" },
+    PackageOption { name: "openssh", description: "SSH client and server" },
+    PackageOption { name: "pipewire", description: "Audio and video server" },
+    PackageOption { name: "rsync", description: "Fast file copying and backups" },
+    PackageOption { name: "thunderbird", description: "Mozilla's mail client" },
+    PackageOption { name: "tmux", description: "Terminal multiplexer" },
+    PackageOption { name: "ufw", description: "Uncomplicated firewall" },
+    PackageOption { name: "vim", description: "Vi improved, the terminal text editor" },
+    PackageOption { name: "vlc", description: "Media player for most formats" },
+    PackageOption { name: "wget", description: "Downloads files over HTTP and FTP" },
+    PackageOption { name: "zsh", description: "Z shell" },
+];
+
+/// How the chosen disk is divided up for the new system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartitionScheme {
+    /// Wipe the disk and lay out a fresh EFI and root partition.
+    #[default]
+    Erase,
+    /// The user says where the partitions already on the disk go.
+    Manual,
+    /// Put the root partition in free space and keep what is there.
+    Alongside,
+}
+
+impl PartitionScheme {
+    pub const ALL: [PartitionScheme; 3] =
+        [PartitionScheme::Erase, PartitionScheme::Manual, PartitionScheme::Alongside];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PartitionScheme::Erase => "Erase entire disk",
+            PartitionScheme::Manual => "Manual partitioning",
+            PartitionScheme::Alongside => "Install alongside existing OS",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            PartitionScheme::Erase => "Delete everything and use the whole disk",
+            PartitionScheme::Manual => "Use the partitions already on the disk",
+            PartitionScheme::Alongside => "Use free space and keep the systems already there",
+        }
+    }
+}
+
+/// Where an existing partition goes in the new system when partitioning
+/// by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MountPoint {
+    Root,
+    /// The EFI system partition on UEFI, or a separate `/boot`.
+    Boot,
+    Home,
+    Swap,
+}
+
+impl MountPoint {
+    pub const ALL: [MountPoint; 4] =
+        [MountPoint::Root, MountPoint::Boot, MountPoint::Home, MountPoint::Swap];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MountPoint::Root => "/",
+            MountPoint::Boot => "/boot",
+            MountPoint::Home => "/home",
+            MountPoint::Swap => "swap",
+        }
+    }
+
+    /// The choice after `current` when cycling through them, back to none
+    /// after the last.
+    pub fn cycle(current: Option<MountPoint>) -> Option<MountPoint> {
+        match current {
+            None => Some(Self::ALL[0]),
+            Some(mount) => {
+                let index = Self::ALL.iter().position(|&m| m == mount).unwrap_or(0);
+                Self::ALL.get(index + 1).copied()
+            }
+        }
+    }
+}
+
+/// Filesystem the root partition is formatted with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filesystem {
+    #[default]
+    Ext4,
+    Btrfs,
+    Xfs,
+}
+
+impl Filesystem {
+    pub const ALL: [Filesystem; 3] = [Filesystem::Ext4, Filesystem::Btrfs, Filesystem::Xfs];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Filesystem::Ext4 => "ext4",
+            Filesystem::Btrfs => "btrfs",
+            Filesystem::Xfs => "xfs",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Filesystem::Ext4 => "The dependable default",
+            Filesystem::Btrfs => "Copy-on-write, with subvolumes and snapshots",
+            Filesystem::Xfs => "Fast with large files",
+        }
+    }
+
+    /// The command that formats a partition with this filesystem, without
+    /// the device. Existing signatures are overwritten.
+    pub fn mkfs_command(self) -> &'static str {
+        match self {
+            Filesystem::Ext4 => "mkfs.ext4 -F",
+            Filesystem::Btrfs => "mkfs.btrfs -f",
+            Filesystem::Xfs => "mkfs.xfs -f",
+        }
+    }
+
+    /// The filesystem with `label`, as written in answers files.
+    pub fn from_label(label: &str) -> Option<Filesystem> {
+        Self::ALL.into_iter().find(|fs| fs.label() == label)
+    }
+}
+
+/// How the machine was started, which decides how it can boot the new system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Firmware {
+    #[default]
+    Uefi,
+    /// Legacy BIOS, or UEFI in compatibility mode.
+    Bios,
+}
+
+/// The program that starts the installed system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Bootloader {
+    #[default]
+    SystemdBoot,
+    Grub,
+}
+
+impl Bootloader {
+    pub const ALL: [Bootloader; 2] = [Bootloader::SystemdBoot, Bootloader::Grub];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Bootloader::SystemdBoot => "systemd-boot",
+            Bootloader::Grub => "GRUB",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Bootloader::SystemdBoot => "Simple and fast; UEFI only",
+            Bootloader::Grub => "Works everywhere and finds other systems",
+        }
+    }
+
+    /// The bootloaders that can start a system on `firmware`.
+    pub fn available(firmware: Firmware) -> &'static [Bootloader] {
+        match firmware {
+            Firmware::Uefi => &Self::ALL,
+            Firmware::Bios => &[Bootloader::Grub],
+        }
+    }
+
+    /// Whether it can start a system on `firmware`.
+    pub fn supports(self, firmware: Firmware) -> bool {
+        Self::available(firmware).contains(&self)
+    }
+
+    /// The bootloader called `label`, ignoring case, as written in answers files.
+    pub fn from_label(label: &str) -> Option<Bootloader> {
+        Self::ALL.into_iter().find(|b| b.label().eq_ignore_ascii_case(label))
+    }
+}
+
+/// Where the new system swaps to, if anywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwapConfig {
+    None,
+    /// A partition of this many GiB after the boot partition.
+    Partition { size_gib: u32 },
+    /// A file of this many GiB on the root filesystem.
+    File { size_gib: u32 },
+    /// Compressed swap in memory, set up by zram-generator. It suits most
+    /// machines and takes no disk space, so it is the default.
+    #[default]
+    Zram,
+}
+
+impl SwapConfig {
+    /// The options that work with `scheme`, the sized ones `size_gib` GiB.
+    /// Partitions and files are only made when the whole disk is erased.
+    pub fn available(scheme: PartitionScheme, size_gib: u32) -> Vec<SwapConfig> {
+        match scheme {
+            PartitionScheme::Erase => vec![
+                SwapConfig::None,
+                SwapConfig::Partition { size_gib },
+                SwapConfig::File { size_gib },
+                SwapConfig::Zram,
+            ],
+            PartitionScheme::Manual | PartitionScheme::Alongside => {
+                vec![SwapConfig::None, SwapConfig::Zram]
+            }
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SwapConfig::None => "No swap",
+            SwapConfig::Partition { .. } => "Swap partition",
+            SwapConfig::File { .. } => "Swap file",
+            SwapConfig::Zram => "zram",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            SwapConfig::None => "Fine with plenty of memory",
+            SwapConfig::Partition { .. } => "A partition of its own; allows hibernation",
+            SwapConfig::File { .. } => "A file on the root filesystem",
+            SwapConfig::Zram => "Compressed swap in memory; no disk space used",
+        }
+    }
+
+    /// How big the partition or file is, for the options that have one.
+    pub fn size_gib(self) -> Option<u32> {
+        match self {
+            SwapConfig::Partition { size_gib } | SwapConfig::File { size_gib } => Some(size_gib),
+            SwapConfig::None | SwapConfig::Zram => None,
+        }
+    }
+
+    /// Whether `other` is the same option, whatever its size.
+    pub fn same_kind(self, other: SwapConfig) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system::hardware::parse_gpus;
+
+    #[test]
+    fn only_base_option_has_no_packages() {
+        for desktop in DESKTOPS {
+            let is_base = desktop.name.starts_with("No desktop");
+            assert_eq!(desktop.packages.is_empty(), is_base, "{}", desktop.name);
+        }
+    }
+
+    #[test]
+    fn each_vendor_defaults_to_its_usual_driver() {
+        let default = |line: &str| driver_options(&parse_gpus(line))[0].packages;
+        let nvidia = "01:00.0 VGA compatible controller: NVIDIA Corporation AD107";
+        assert_eq!(default(nvidia), ["nvidia-open", "nvidia-utils"]);
+        let amd = "03:00.0 VGA compatible controller: Advanced Micro Devices, Inc. Navi 23";
+        assert_eq!(default(amd), ["mesa", "vulkan-radeon"]);
+        let intel = "00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 620";
+        assert_eq!(default(intel), ["mesa", "vulkan-intel"]);
+        let vm = "00:02.0 VGA compatible controller: VMware SVGA II Adapter";
+        assert_eq!(default(vm), ["mesa"]);
+        assert_eq!(default(""), ["mesa"]);
+        // Hybrid laptops are set up for their discrete card.
+        assert_eq!(driver_options(&[GpuVendor::Intel, GpuVendor::Nvidia]), NVIDIA_DRIVERS);
+        assert!(NVIDIA_DRIVERS.iter().any(|driver| driver.name == "nouveau"));
+    }
+
+    #[test]
+    fn mount_points_cycle_back_to_none() {
+        let mut mount = None;
+        let mut seen = Vec::new();
+        for _ in 0..MountPoint::ALL.len() + 1 {
+            mount = MountPoint::cycle(mount);
+            seen.push(mount.map(MountPoint::label));
+        }
+        assert_eq!(seen, [Some("/"), Some("/boot"), Some("/home"), Some("swap"), None]);
+    }
+
+    #[test]
+    fn mkfs_command_runs_the_matching_binary() {
+        let binary = |fs: Filesystem| fs.mkfs_command().split_whitespace().next().unwrap();
+        assert_eq!(binary(Filesystem::Ext4), "mkfs.ext4");
+        assert_eq!(binary(Filesystem::Btrfs), "mkfs.btrfs");
+        assert_eq!(binary(Filesystem::Xfs), "mkfs.xfs");
+    }
+
+    #[test]
+    fn bios_only_offers_grub() {
+        assert_eq!(Bootloader::available(Firmware::Bios), [Bootloader::Grub]);
+        assert!(!Bootloader::SystemdBoot.supports(Firmware::Bios));
+        assert!(Bootloader::Grub.supports(Firmware::Bios));
+        assert!(Bootloader::available(Firmware::Uefi).contains(&Bootloader::SystemdBoot));
+        assert_eq!(Bootloader::from_label("grub"), Some(Bootloader::Grub));
+    }
+
+    #[test]
+    fn filesystems_round_trip_their_labels() {
+        for fs in Filesystem::ALL {
+            assert_eq!(Filesystem::from_label(fs.label()), Some(fs));
+        }
+        assert_eq!(Filesystem::from_label("ntfs"), None);
+    }
+
+    #[test]
+    fn swap_on_disk_needs_an_erased_disk() {
+        let erase = SwapConfig::available(PartitionScheme::Erase, 8);
+        assert!(erase.contains(&SwapConfig::Partition { size_gib: 8 }));
+        assert_eq!(erase.iter().filter_map(|swap| swap.size_gib()).collect::<Vec<_>>(), [8, 8]);
+        assert_eq!(
+            SwapConfig::available(PartitionScheme::Alongside, 8),
+            [SwapConfig::None, SwapConfig::Zram]
+        );
+        assert!(SwapConfig::File { size_gib: 1 }.same_kind(SwapConfig::File { size_gib: 2 }));
+        assert!(!SwapConfig::File { size_gib: 1 }.same_kind(SwapConfig::Partition { size_gib: 1 }));
+    }
+}
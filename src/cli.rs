@@ -0,0 +1,246 @@
+//! Command-line arguments.
+
+use std::path::PathBuf;
+
+use log::LevelFilter;
+
+use crate::theme::Theme;
+
+pub const USAGE: &str = "usage: artemis [--dry-run] [--config <answers.toml>] \
+                         [--prefill <answers.json>] [--log-level <level>] \
+                         [--log-file <path>] [--verbose] \
+                         [--theme <name>] [--accessible] [--no-emoji] \
+                         [--check-host <host:port>] [--max-attempts <n>] \
+                         [--retries <n>] [--retry-delay <seconds>] [--help]";
+
+/// What `--help` prints.
+pub const HELP: &str = "\
+Artemis, the EndeavourOS installer.
+
+Options:
+  --dry-run              Go through the installation without running anything;
+                         the commands are logged and printed on exit instead.
+  --config <file>        Install unattended with the answers in a TOML file.
+  --prefill <file>       Start the wizard on the answers in a JSON export.
+  --log-level <level>    off, error, warn, info (default), debug or trace.
+  --verbose              Same as --log-level debug.
+  --log-file <path>      Where the log goes
+                         (default /var/log/artemis-install.log).
+  --theme <name>         dark (default), light, high-contrast or monochrome.
+                         The ARTEMIS_THEME environment variable works too,
+                         and F2 switches theme while running.
+  --accessible           No colors, emoji or animation; bold for emphasis.
+  --no-emoji             Keep the colors but leave emoji out of titles and
+                         draw hints brighter. F3 switches this while running.
+  --check-host <addr>    host:port connected to when checking for internet
+                         access (default archlinux.org:443).
+  --max-attempts <n>     Failures of a download after which the error screen
+                         stops offering to retry it (default 3).
+  --retries <n>          Automatic tries at the network check and each
+                         download before one counts as failed (default 3).
+  --retry-delay <secs>   Pause before the first retry, doubled for each one
+                         after it (default 2).
+  --help                 Show this help.";
+
+/// What the installer was asked to do on the command line.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Args {
+    /// Report the installation's commands instead of running them.
+    pub dry_run: bool,
+    /// Show the help text and exit.
+    pub help: bool,
+    /// Answers file for an unattended install; the wizard is skipped.
+    pub config: Option<PathBuf>,
+    /// JSON export whose answers the wizard starts on.
+    pub prefill: Option<PathBuf>,
+    /// How much detail goes into the log file.
+    pub log_level: LevelFilter,
+    /// Log file to write instead of the default.
+    pub log_file: Option<PathBuf>,
+    /// Color scheme asked for with `--theme`.
+    pub theme: Option<Theme>,
+    /// Draw plainly for screen readers and limited terminals.
+    pub accessible: bool,
+    /// Leave out emoji and dim text, but keep the theme's colors.
+    pub no_emoji: bool,
+    /// `host:port` the network check connects to, if not the default.
+    pub check_host: Option<String>,
+    /// Failures of a retryable command the error screen offers a retry
+    /// for, if not the default.
+    pub max_attempts: Option<u32>,
+    /// Tries at the network check and each download, if not the default.
+    pub retries: Option<u32>,
+    /// Seconds before the first retry, if not the default.
+    pub retry_delay: Option<u64>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            help: false,
+            config: None,
+            prefill: None,
+            log_level: LevelFilter::Info,
+            log_file: None,
+            theme: None,
+            accessible: false,
+            no_emoji: false,
+            check_host: None,
+            max_attempts: None,
+            retries: None,
+            retry_delay: None,
+        }
+    }
+}
+
+/// Parse the arguments that follow the program name.
+pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => parsed.dry_run = true,
+            "--help" | "-h" => parsed.help = true,
+            "--accessible" => parsed.accessible = true,
+            "--no-emoji" => parsed.no_emoji = true,
+            "--config" => {
+                let path = args.next().ok_or("--config needs a path")?;
+                parsed.config = Some(PathBuf::from(path));
+            }
+            "--prefill" => {
+                let path = args.next().ok_or("--prefill needs a path")?;
+                parsed.prefill = Some(PathBuf::from(path));
+            }
+            "--log-level" => {
+                let level = args.next().ok_or("--log-level needs a level")?;
+                parsed.log_level = level
+                    .parse()
+                    .map_err(|_| format!("unknown log level `{level}`"))?;
+            }
+            "--verbose" | "-v" => parsed.log_level = LevelFilter::Debug,
+            "--log-file" => {
+                let path = args.next().ok_or("--log-file needs a path")?;
+                parsed.log_file = Some(PathBuf::from(path));
+            }
+            "--theme" => {
+                let name = args.next().ok_or("--theme needs a name")?;
+                let theme = Theme::from_name(&name);
+                parsed.theme = Some(theme.ok_or_else(|| format!("unknown theme `{name}`"))?);
+            }
+            "--check-host" => {
+                let address = args.next().ok_or("--check-host needs a host:port")?;
+                parsed.check_host = Some(address);
+            }
+            "--max-attempts" => {
+                let count = args.next().ok_or("--max-attempts needs a number")?;
+                let max = count.parse().ok().filter(|&max: &u32| max > 0);
+                parsed.max_attempts =
+                    Some(max.ok_or_else(|| format!("`{count}` is not a positive number"))?);
+            }
+            "--retries" => {
+                let count = args.next().ok_or("--retries needs a number")?;
+                let tries = count.parse().ok().filter(|&tries: &u32| tries > 0);
+                parsed.retries =
+                    Some(tries.ok_or_else(|| format!("`{count}` is not a positive number"))?);
+            }
+            "--retry-delay" => {
+                let seconds = args.next().ok_or("--retry-delay needs a number of seconds")?;
+                parsed.retry_delay = Some(
+                    seconds.parse().map_err(|_| format!("`{seconds}` is not a number of seconds"))?,
+                );
+            }
+            other => return Err(format!("unknown argument `{other}`")),
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_strs(args: &[&str]) -> Result<Args, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_runs_the_wizard() {
+        assert_eq!(parse_strs(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn config_takes_a_path() {
+        let args = parse_strs(&["--config", "answers.toml"]).unwrap();
+        assert_eq!(args.config, Some(PathBuf::from("answers.toml")));
+        assert!(parse_strs(&["--config"]).is_err());
+        assert!(parse_strs(&["--colour"]).is_err());
+    }
+
+    #[test]
+    fn flags_are_recognised() {
+        let args =
+            parse_strs(&["--dry-run", "--config", "a.toml", "-h", "--accessible", "--no-emoji"])
+                .unwrap();
+        assert!(args.dry_run);
+        assert!(args.help);
+        assert!(args.accessible);
+        assert!(args.no_emoji);
+        assert_eq!(args.config, Some(PathBuf::from("a.toml")));
+    }
+
+    #[test]
+    fn prefill_takes_a_path() {
+        let args = parse_strs(&["--prefill", "old.json"]).unwrap();
+        assert_eq!(args.prefill, Some(PathBuf::from("old.json")));
+        assert!(parse_strs(&["--prefill"]).is_err());
+    }
+
+    #[test]
+    fn theme_is_looked_up_by_name() {
+        let args = parse_strs(&["--theme", "light"]).unwrap();
+        assert_eq!(args.theme, Some(Theme::LIGHT));
+        assert!(parse_strs(&["--theme", "neon"]).is_err());
+        assert!(parse_strs(&["--theme"]).is_err());
+    }
+
+    #[test]
+    fn check_host_takes_an_address() {
+        let args = parse_strs(&["--check-host", "mirror.local:80"]).unwrap();
+        assert_eq!(args.check_host.as_deref(), Some("mirror.local:80"));
+        assert!(parse_strs(&["--check-host"]).is_err());
+    }
+
+    #[test]
+    fn max_attempts_is_a_positive_number() {
+        assert_eq!(parse_strs(&["--max-attempts", "5"]).unwrap().max_attempts, Some(5));
+        assert!(parse_strs(&["--max-attempts", "0"]).is_err());
+        assert!(parse_strs(&["--max-attempts", "many"]).is_err());
+        assert!(parse_strs(&["--max-attempts"]).is_err());
+    }
+
+    #[test]
+    fn retries_and_their_delay_are_numbers() {
+        let args = parse_strs(&["--retries", "5", "--retry-delay", "0"]).unwrap();
+        assert_eq!((args.retries, args.retry_delay), (Some(5), Some(0)));
+        assert!(parse_strs(&["--retries", "0"]).is_err());
+        assert!(parse_strs(&["--retry-delay", "-1"]).is_err());
+        assert!(parse_strs(&["--retry-delay"]).is_err());
+    }
+
+    #[test]
+    fn verbose_logs_debug_to_the_chosen_file() {
+        let args = parse_strs(&["--verbose", "--log-file", "/tmp/a.log"]).unwrap();
+        assert_eq!(args.log_level, LevelFilter::Debug);
+        assert_eq!(args.log_file, Some(PathBuf::from("/tmp/a.log")));
+        assert!(parse_strs(&["--log-file"]).is_err());
+    }
+
+    #[test]
+    fn log_level_maps_to_a_filter() {
+        let args = parse_strs(&["--log-level", "debug"]).unwrap();
+        assert_eq!(args.log_level, LevelFilter::Debug);
+        assert_eq!(parse_strs(&["--log-level", "OFF"]).unwrap().log_level, LevelFilter::Off);
+        assert!(parse_strs(&["--log-level", "loud"]).is_err());
+        assert!(parse_strs(&["--log-level"]).is_err());
+    }
+}
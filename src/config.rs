@@ -0,0 +1,665 @@
+//! Saving the wizard's answers so an installation can be reproduced, and
+//! loading them back for unattended installs.
+
+use std::{
+    env, fmt, fs, io,
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+};
+
+use crate::{
+    app::{EncryptionConfig, InstallerState, LANGUAGES},
+    choices::{Bootloader, Filesystem, DESKTOPS},
+    secret::{self, Secret},
+    system::{keymaps, timezones},
+    validate::{
+        validate_disk, validate_hostname, validate_keymap, validate_passphrase,
+        validate_timezone, validate_username,
+    },
+};
+
+/// Where the answers are written when the summary is confirmed.
+pub const ANSWERS_PATH: &str = "/tmp/artemis-answers.toml";
+
+/// Render the user-facing answers as TOML. Unanswered questions are left
+/// out, and the password and encryption passphrase never leave memory.
+pub fn to_toml(state: &InstallerState) -> String {
+    let fields = [
+        ("language", Some(state.locale())),
+        ("keymap", state.keymap.as_deref()),
+        ("timezone", state.timezone.as_deref()),
+        ("disk", state.disk.as_deref()),
+        ("hostname", state.hostname.as_deref()),
+        ("username", state.username.as_deref()),
+        ("full_name", state.full_name.as_deref()),
+        ("desktop", Some(state.desktop().name)),
+        ("filesystem", Some(state.filesystem.label())),
+        ("bootloader", Some(state.bootloader.label())),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| Some(format!("{key} = {}\n", quote(value?))))
+        .collect()
+}
+
+/// Write the answers in `state` to `path` as TOML.
+pub fn save_config(state: &InstallerState, path: &Path) -> io::Result<()> {
+    fs::write(path, to_toml(state))
+}
+
+/// Where the JSON export goes unless [`JSON_PATH_VAR`] says otherwise.
+pub const JSON_PATH: &str = "/tmp/artemis-install.json";
+/// Environment variable overriding [`JSON_PATH`].
+pub const JSON_PATH_VAR: &str = "ARTEMIS_CONFIG_JSON";
+
+/// Where to write the JSON export.
+pub fn json_path() -> PathBuf {
+    env::var_os(JSON_PATH_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(JSON_PATH))
+}
+
+/// The user's selections, as exported to JSON for inspection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Locale of the display language, e.g. `en_US.UTF-8`.
+    pub language: String,
+    pub keymap: Option<String>,
+    pub timezone: Option<String>,
+    pub disk: Option<String>,
+    pub hostname: Option<String>,
+    pub username: Option<String>,
+    /// Name of the desktop, as listed in [`DESKTOPS`].
+    pub desktop: String,
+}
+
+impl Config {
+    pub fn from_state(state: &InstallerState) -> Self {
+        Self {
+            language: state.locale().to_string(),
+            keymap: state.keymap.clone(),
+            timezone: state.timezone.clone(),
+            disk: state.disk.clone(),
+            hostname: state.hostname.clone(),
+            username: state.username.clone(),
+            desktop: state.desktop().name.to_string(),
+        }
+    }
+
+    /// Render as a JSON object, with `null` for unanswered questions.
+    pub fn to_json(&self) -> String {
+        let fields = [
+            ("language", Some(&self.language)),
+            ("keymap", self.keymap.as_ref()),
+            ("timezone", self.timezone.as_ref()),
+            ("disk", self.disk.as_ref()),
+            ("hostname", self.hostname.as_ref()),
+            ("username", self.username.as_ref()),
+            ("desktop", Some(&self.desktop)),
+        ];
+        let members: Vec<String> = fields
+            .into_iter()
+            .map(|(key, value)| {
+                let value = value.map_or_else(|| "null".to_string(), |value| json_string(value));
+                format!("  \"{key}\": {value}")
+            })
+            .collect();
+        format!("{{\n{}\n}}\n", members.join(",\n"))
+    }
+
+    pub fn save_json(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    /// Read a JSON export written by [`Config::save_json`].
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        Self::from_json(&fs::read_to_string(path).map_err(ConfigError::Io)?)
+    }
+
+    /// Parse the object produced by [`Config::to_json`]. Keys this version
+    /// does not know are ignored.
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Config {
+            language: String::new(),
+            keymap: None,
+            timezone: None,
+            disk: None,
+            hostname: None,
+            username: None,
+            desktop: String::new(),
+        };
+        let (mut language, mut desktop) = (None, None);
+        for (key, value) in parse_json_object(text).map_err(ConfigError::Json)? {
+            match key.as_str() {
+                "language" => language = value,
+                "keymap" => config.keymap = value,
+                "timezone" => config.timezone = value,
+                "disk" => config.disk = value,
+                "hostname" => config.hostname = value,
+                "username" => config.username = value,
+                "desktop" => desktop = value,
+                _ => {}
+            }
+        }
+        config.language = language.ok_or(ConfigError::MissingKey("language"))?;
+        config.desktop = desktop.ok_or(ConfigError::MissingKey("desktop"))?;
+        Ok(config)
+    }
+}
+
+/// Parse a flat JSON object whose values are all strings or `null`.
+fn parse_json_object(text: &str) -> Result<Vec<(String, Option<String>)>, &'static str> {
+    let mut chars = text.chars().peekable();
+    let mut members = Vec::new();
+
+    skip_whitespace(&mut chars);
+    if chars.next() != Some('{') {
+        return Err("expected an object");
+    }
+    skip_whitespace(&mut chars);
+    if chars.next_if_eq(&'}').is_none() {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_json_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            if chars.next() != Some(':') {
+                return Err("expected `:` after a key");
+            }
+            skip_whitespace(&mut chars);
+            let value = match chars.peek().copied() {
+                Some('"') => Some(parse_json_string(&mut chars)?),
+                Some('n') if chars.by_ref().take(4).eq("null".chars()) => None,
+                _ => return Err("values must be strings or null"),
+            };
+            members.push((key, value));
+            skip_whitespace(&mut chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                _ => return Err("expected `,` or `}`"),
+            }
+        }
+    }
+    skip_whitespace(&mut chars);
+    if chars.next().is_some() {
+        return Err("unexpected text after the object");
+    }
+    Ok(members)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+/// Parse a JSON string starting at its opening quote.
+fn parse_json_string(chars: &mut impl Iterator<Item = char>) -> Result<String, &'static str> {
+    if chars.next() != Some('"') {
+        return Err("expected a string");
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next().ok_or("unterminated string")? {
+            '"' => return Ok(value),
+            '\\' => value.push(match chars.next().ok_or("unterminated string")? {
+                '"' => '"',
+                '\\' => '\\',
+                '/' => '/',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or("invalid \\u escape")?
+                }
+                _ => return Err("invalid escape sequence"),
+            }),
+            c => value.push(c),
+        }
+    }
+}
+
+/// Quote `value` as a JSON string.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Why an answers file could not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    /// A line that is not a `key = "value"` pair.
+    Syntax { line: usize, message: &'static str },
+    UnknownKey(String),
+    MissingKey(&'static str),
+    InvalidValue { key: &'static str, message: String },
+    /// The JSON export could not be parsed.
+    Json(&'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{err}"),
+            ConfigError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            ConfigError::UnknownKey(key) => write!(f, "unknown key `{key}`"),
+            ConfigError::MissingKey(key) => write!(f, "missing required key `{key}`"),
+            ConfigError::InvalidValue { key, message } => write!(f, "invalid `{key}`: {message}"),
+            ConfigError::Json(message) => write!(f, "malformed JSON: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Keys that must be present for an unattended install.
+const REQUIRED_KEYS: [&str; 3] = ["disk", "hostname", "username"];
+
+/// Read an answers file written by [`save_config`] (optionally with a
+/// `password`, `root_password` or `encryption_passphrase` added) into an
+/// [`InstallerState`]. Without a `root_password`, root is locked, so the
+/// user needs a `password` to log in with. The keymap and timezone must be
+/// ones this system has.
+pub fn load_config(path: &Path) -> Result<InstallerState, ConfigError> {
+    // The file may hold passwords, so its text is zeroed once parsed.
+    let mut text = fs::read_to_string(path).map_err(ConfigError::Io)?;
+    let state = parse_toml(&text);
+    secret::zeroize(&mut text);
+    let state = state?;
+    check_login(&state)?;
+    check_installed(&state, &keymaps::detect_keymaps(), Path::new(timezones::ZONEINFO))?;
+    Ok(state)
+}
+
+/// Check that the user `state` creates can log in, as the account screen
+/// does: an empty password is refused the same as a missing one.
+fn check_login(state: &InstallerState) -> Result<(), ConfigError> {
+    let password = state.password.as_ref().map(Secret::expose);
+    match (&state.username, password) {
+        (Some(_), None) => Err(ConfigError::MissingKey("password")),
+        (Some(_), Some("")) => Err(invalid("password", "must not be empty")),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the keymap `state` names is among `keymaps` and that its
+/// timezone has a file under `zoneinfo`. Either is let through when the
+/// system's list is missing, as the wizard does.
+fn check_installed(
+    state: &InstallerState,
+    keymaps: &[String],
+    zoneinfo: &Path,
+) -> Result<(), ConfigError> {
+    if let Some(keymap) = &state.keymap {
+        if !keymaps.is_empty() && !keymaps.contains(keymap) {
+            return Err(invalid("keymap", format!("unknown keymap {keymap}")));
+        }
+    }
+    if let Some(zone) = &state.timezone {
+        if zoneinfo.is_dir() && !zoneinfo.join(zone).is_file() {
+            return Err(invalid("timezone", format!("unknown timezone {zone}")));
+        }
+    }
+    Ok(())
+}
+
+/// Parse answers from the flat `key = "value"` subset of TOML that
+/// [`to_toml`] produces.
+pub fn parse_toml(text: &str) -> Result<InstallerState, ConfigError> {
+    let mut state = InstallerState::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let syntax = |message| ConfigError::Syntax { line: index + 1, message };
+        let (key, value) = line.split_once('=').ok_or(syntax("expected `key = \"value\"`"))?;
+        let value = unquote(value.trim()).map_err(syntax)?;
+        match key.trim() {
+            "language" => {
+                state.language = LANGUAGES
+                    .iter()
+                    .position(|language| language.locale == value)
+                    .ok_or_else(|| invalid("language", format!("unsupported locale {value}")))?;
+            }
+            "keymap" => {
+                validate_keymap(&value).map_err(|message| invalid("keymap", message))?;
+                state.keymap = Some(value);
+            }
+            "timezone" => {
+                validate_timezone(&value).map_err(|message| invalid("timezone", message))?;
+                state.timezone = Some(value);
+            }
+            "disk" => {
+                validate_disk(&value).map_err(|message| invalid("disk", message))?;
+                state.disk = Some(value);
+            }
+            "hostname" => {
+                validate_hostname(&value).map_err(|message| invalid("hostname", message))?;
+                state.hostname = Some(value);
+            }
+            "username" => {
+                validate_username(&value).map_err(|message| invalid("username", message))?;
+                state.username = Some(value);
+            }
+            "full_name" => state.full_name = Some(value).filter(|name| !name.is_empty()),
+            "password" => state.password = Some(Secret::new(value)),
+            "root_password" => state.root_password = Some(Secret::new(value)),
+            "encryption_passphrase" => {
+                validate_passphrase(&value)
+                    .map_err(|message| invalid("encryption_passphrase", message))?;
+                state.encryption = Some(EncryptionConfig { passphrase: Secret::new(value) });
+            }
+            "desktop" => {
+                state.desktop = DESKTOPS
+                    .iter()
+                    .position(|desktop| desktop.name == value)
+                    .ok_or_else(|| invalid("desktop", format!("unknown desktop {value}")))?;
+            }
+            "filesystem" => {
+                state.filesystem = Filesystem::from_label(&value)
+                    .ok_or_else(|| invalid("filesystem", format!("unsupported filesystem {value}")))?;
+            }
+            "bootloader" => {
+                state.bootloader = Bootloader::from_label(&value)
+                    .ok_or_else(|| invalid("bootloader", format!("unknown bootloader {value}")))?;
+            }
+            other => return Err(ConfigError::UnknownKey(other.to_string())),
+        }
+    }
+
+    let present = [&state.disk, &state.hostname, &state.username];
+    if let Some((key, _)) = REQUIRED_KEYS.iter().zip(present).find(|(_, value)| value.is_none()) {
+        return Err(ConfigError::MissingKey(key));
+    }
+    Ok(state)
+}
+
+fn invalid(key: &'static str, message: impl Into<String>) -> ConfigError {
+    ConfigError::InvalidValue { key, message: message.into() }
+}
+
+/// Read a TOML basic string, undoing [`quote`].
+fn unquote(quoted: &str) -> Result<String, &'static str> {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or("value must be a double-quoted string")?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or("invalid \\u escape")?;
+                    value.push(c);
+                }
+                _ => return Err("invalid escape sequence"),
+            },
+            '"' => return Err("unescaped quote inside string"),
+            c => value.push(c),
+        }
+    }
+    Ok(value)
+}
+
+/// Quote `value` as a TOML basic string.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated() -> InstallerState {
+        InstallerState {
+            language: 1,
+            keymap: Some("fr".into()),
+            timezone: Some("Europe/Paris".into()),
+            disk: Some("/dev/sda".into()),
+            hostname: Some("atelier".into()),
+            username: Some("marie".into()),
+            full_name: Some("Marie \"Curie\"".into()),
+            password: Some("hunter2".into()),
+            root_password: Some("toor".into()),
+            desktop: 1,
+            encryption: Some(EncryptionConfig { passphrase: "open sesame".into() }),
+            ..InstallerState::default()
+        }
+    }
+
+    #[test]
+    fn toml_has_answers_but_not_the_password() {
+        let toml = to_toml(&populated());
+        for line in [
+            "language = \"fr_FR.UTF-8\"",
+            "keymap = \"fr\"",
+            "timezone = \"Europe/Paris\"",
+            "disk = \"/dev/sda\"",
+            "hostname = \"atelier\"",
+            "username = \"marie\"",
+            "full_name = \"Marie \\\"Curie\\\"\"",
+            "desktop = \"GNOME\"",
+            "filesystem = \"ext4\"",
+            "bootloader = \"systemd-boot\"",
+        ] {
+            assert!(toml.lines().any(|l| l == line), "missing {line:?} in\n{toml}");
+        }
+        assert!(!toml.contains("password"));
+        assert!(!toml.contains("hunter2"));
+        assert!(!toml.contains("toor"));
+        assert!(!toml.contains("passphrase"));
+        assert!(!toml.contains("open sesame"));
+    }
+
+    #[test]
+    fn unanswered_fields_are_omitted() {
+        let toml = to_toml(&InstallerState::default());
+        assert!(!toml.contains("disk"));
+        assert!(toml.contains("language = \"en_US.UTF-8\""));
+    }
+
+    #[test]
+    fn saved_file_matches_rendered_toml() {
+        let path = std::env::temp_dir().join(format!("artemis-test-{}.toml", std::process::id()));
+        save_config(&populated(), &path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(written, to_toml(&populated()));
+    }
+
+    #[test]
+    fn saved_answers_load_back() {
+        let loaded = parse_toml(&to_toml(&populated())).unwrap();
+        assert_eq!(loaded.locale(), "fr_FR.UTF-8");
+        assert_eq!(loaded.keymap.as_deref(), Some("fr"));
+        assert_eq!(loaded.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(loaded.disk.as_deref(), Some("/dev/sda"));
+        assert_eq!(loaded.hostname.as_deref(), Some("atelier"));
+        assert_eq!(loaded.username.as_deref(), Some("marie"));
+        assert_eq!(loaded.full_name.as_deref(), Some("Marie \"Curie\""));
+        assert_eq!(loaded.desktop().name, "GNOME");
+        assert_eq!(loaded.password, None);
+        assert_eq!(loaded.root_password, None);
+        assert_eq!(loaded.encryption, None);
+    }
+
+    #[test]
+    fn missing_required_key_is_reported() {
+        let err = parse_toml("# answers\ndisk = \"/dev/sda\"\nusername = \"marie\"\n").unwrap_err();
+        assert!(matches!(err, ConfigError::MissingKey("hostname")));
+        assert_eq!(err.to_string(), "missing required key `hostname`");
+    }
+
+    #[test]
+    fn user_without_a_password_is_refused() {
+        let path = std::env::temp_dir().join(format!("artemis-login-{}.toml", std::process::id()));
+        let answers = "disk = \"/dev/sda\"\nhostname = \"atelier\"\nusername = \"marie\"\n";
+        fs::write(&path, answers).unwrap();
+        let missing = load_config(&path);
+        fs::write(&path, format!("{answers}root_password = \"toor\"\npassword = \"\"\n")).unwrap();
+        let empty = load_config(&path);
+        fs::remove_file(&path).unwrap();
+
+        let err = missing.unwrap_err();
+        assert!(matches!(err, ConfigError::MissingKey("password")));
+        assert_eq!(err.to_string(), "missing required key `password`");
+        assert!(matches!(empty, Err(ConfigError::InvalidValue { key: "password", .. })));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        assert!(matches!(
+            parse_toml("disk = /dev/sda"),
+            Err(ConfigError::Syntax { line: 1, .. })
+        ));
+        assert!(matches!(parse_toml("colour = \"red\""), Err(ConfigError::UnknownKey(_))));
+        assert!(matches!(
+            parse_toml("hostname = \"Bad Host\""),
+            Err(ConfigError::InvalidValue { key: "hostname", .. })
+        ));
+    }
+
+    #[test]
+    fn keymap_timezone_and_disk_are_checked() {
+        for (line, key) in [
+            ("keymap = \"de $(reboot)\"", "keymap"),
+            ("timezone = \"UTC; rm -rf /\"", "timezone"),
+            ("disk = \"sda\"", "disk"),
+            ("disk = \"/dev/sda; reboot\"", "disk"),
+        ] {
+            let err = parse_toml(line).unwrap_err();
+            assert!(matches!(err, ConfigError::InvalidValue { key: k, .. } if k == key), "{err}");
+        }
+    }
+
+    #[test]
+    fn keymap_and_timezone_must_be_installed() {
+        let zoneinfo = std::env::temp_dir().join(format!("artemis-zones-{}", std::process::id()));
+        fs::create_dir_all(zoneinfo.join("Europe")).unwrap();
+        fs::write(zoneinfo.join("Europe/Paris"), "").unwrap();
+        let keymaps = ["fr".to_string(), "us".to_string()];
+
+        let state = populated();
+        let checked = check_installed(&state, &keymaps, &zoneinfo);
+        let unknown_keymap = InstallerState { keymap: Some("xx".into()), ..populated() };
+        let unknown_keymap = check_installed(&unknown_keymap, &keymaps, &zoneinfo);
+        let mars = InstallerState { timezone: Some("Mars/Olympus".into()), ..populated() };
+        let unknown_zone = check_installed(&mars, &keymaps, &zoneinfo);
+        // Without the system's lists, there is nothing to check against.
+        let unchecked = check_installed(&mars, &[], &zoneinfo.join("missing"));
+        fs::remove_dir_all(&zoneinfo).unwrap();
+
+        assert!(checked.is_ok());
+        assert!(matches!(unknown_keymap, Err(ConfigError::InvalidValue { key: "keymap", .. })));
+        assert!(matches!(unknown_zone, Err(ConfigError::InvalidValue { key: "timezone", .. })));
+        assert!(unchecked.is_ok());
+    }
+
+    #[test]
+    fn config_exports_as_json_object() {
+        let mut state = populated();
+        state.timezone = None;
+        assert_eq!(
+            Config::from_state(&state).to_json(),
+            concat!(
+                "{\n",
+                "  \"language\": \"fr_FR.UTF-8\",\n",
+                "  \"keymap\": \"fr\",\n",
+                "  \"timezone\": null,\n",
+                "  \"disk\": \"/dev/sda\",\n",
+                "  \"hostname\": \"atelier\",\n",
+                "  \"username\": \"marie\",\n",
+                "  \"desktop\": \"GNOME\"\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn exported_json_loads_back() {
+        let config = Config::from_state(&populated());
+        assert_eq!(Config::from_json(&config.to_json()).unwrap(), config);
+
+        let path = std::env::temp_dir().join(format!("artemis-test-{}.json", std::process::id()));
+        config.save_json(&path).unwrap();
+        let loaded = Config::load_from(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), config);
+    }
+
+    #[test]
+    fn json_parsing_accepts_escapes_and_unknown_keys() {
+        let config = Config::from_json(
+            r#" { "language": "en_US.UTF-8", "hostname": "a\u0062c", "extra": null,
+                  "timezone": null, "desktop": "i3" } "#,
+        )
+        .unwrap();
+        assert_eq!(config.hostname.as_deref(), Some("abc"));
+        assert_eq!(config.timezone, None);
+        assert_eq!(config.desktop, "i3");
+    }
+
+    #[test]
+    fn corrupt_json_is_an_error() {
+        for text in ["", "{", r#"{"language": 3}"#, r#"{"language": "x"} trailing"#, "{\"a\" \"b\"}"] {
+            assert!(matches!(Config::from_json(text), Err(ConfigError::Json(_))), "{text:?}");
+        }
+        assert!(matches!(
+            Config::from_json(r#"{"desktop": "i3"}"#),
+            Err(ConfigError::MissingKey("language"))
+        ));
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a\"b\\c\n\u{1}"), "\"a\\\"b\\\\c\\n\\u0001\"");
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        assert_eq!(quote("a\\b\tc\u{1}"), "\"a\\\\b\\tc\\u0001\"");
+        assert_eq!(unquote(&quote("a\\b\tc\u{1}\"")).unwrap(), "a\\b\tc\u{1}\"");
+    }
+}
@@ -0,0 +1,37 @@
+//! Narrowing long lists down to the entries matching what the user typed.
+
+/// Indices of the `items` that contain `query`, ignoring case. An empty
+/// query matches everything.
+pub fn filter_items<S: AsRef<str>>(items: &[S], query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.as_ref().to_lowercase().contains(&query))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS: [&str; 4] = ["Europe", "America", "Français", "Asia"];
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(filter_items(&ITEMS, ""), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn matching_is_case_insensitive_substring() {
+        assert_eq!(filter_items(&ITEMS, "ER"), [1]);
+        assert_eq!(filter_items(&ITEMS, "a"), [1, 2, 3]);
+        assert_eq!(filter_items(&ITEMS, "FRANÇ"), [2]);
+    }
+
+    #[test]
+    fn no_match_gives_nothing() {
+        assert!(filter_items(&ITEMS, "zz").is_empty());
+    }
+}
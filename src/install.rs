@@ -0,0 +1,1679 @@
+//! The installation itself: the phases it goes through and how far along it is.
+//!
+//! The installation runs on a background thread and reports back to the UI
+//! over a channel, so long-running commands never block the event loop.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    app::InstallerState,
+    choices::{Bootloader, Filesystem, Firmware, MountPoint, PartitionScheme, SwapConfig},
+    retry::{retry, Backoff},
+    runner::{CommandRunner, RealRunner},
+    secret::Secret,
+};
+
+/// The stages of an installation, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallPhase {
+    Partition,
+    Format,
+    Pacstrap,
+    Configure,
+    Bootloader,
+}
+
+impl InstallPhase {
+    pub const ALL: [InstallPhase; 5] = [
+        InstallPhase::Partition,
+        InstallPhase::Format,
+        InstallPhase::Pacstrap,
+        InstallPhase::Configure,
+        InstallPhase::Bootloader,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InstallPhase::Partition => "Partitioning disk",
+            InstallPhase::Format => "Formatting partitions",
+            InstallPhase::Pacstrap => "Installing base system",
+            InstallPhase::Configure => "Configuring system",
+            InstallPhase::Bootloader => "Installing bootloader",
+        }
+    }
+
+    /// Whether a failure here is often passing, like a mirror timing out,
+    /// so running the phase's failed command again may well work.
+    pub fn retryable(self) -> bool {
+        self == InstallPhase::Pacstrap
+    }
+
+    /// The phase that is running once `progress` (0.0–1.0) of the whole
+    /// installation is done, assuming every phase takes the same time.
+    pub fn at(progress: f64) -> InstallPhase {
+        let index = (progress.clamp(0.0, 1.0) * Self::ALL.len() as f64) as usize;
+        Self::ALL[index.min(Self::ALL.len() - 1)]
+    }
+}
+
+/// What the installation thread tells the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstallEvent {
+    /// Overall progress, 0–100.
+    Progress(u8),
+    /// A line for the task log.
+    Log(String),
+    /// A line the running command printed, for the task log as well.
+    Output(String),
+    /// The installation finished successfully.
+    Done,
+    /// The installation stopped; nothing more will be sent.
+    Failed(String),
+    /// A command failed and the installation stopped there; nothing more
+    /// will be sent.
+    CommandFailed(CommandFailure),
+    /// The installation was cancelled and stopped before its next phase;
+    /// nothing more will be sent. Holds the phases that had already run,
+    /// which say what was done to the disk.
+    Cancelled(Vec<InstallPhase>),
+}
+
+/// How many times a retryable command may fail before the failure popup
+/// stops offering to retry it, unless `--max-attempts` says otherwise. Each
+/// failure already takes in the automatic retries [`Backoff`] allows.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// What a command of the plan reads on stdin: what must not show up in its
+/// command line, or is too long for one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// Passwords or a passphrase, zeroed once no longer needed.
+    Secret(Secret),
+    /// A file's contents, like the mirrorlist.
+    Text(String),
+}
+
+impl Input {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Input::Secret(secret) => secret.expose(),
+            Input::Text(text) => text,
+        }
+    }
+}
+
+/// A command of the plan with the phase it belongs to and what it reads
+/// on stdin.
+type Planned = (InstallPhase, String, Option<Input>);
+
+/// Mount point of the new system's root while it is being installed.
+pub const TARGET: &str = "/mnt";
+/// Packages every installation gets, before the desktop's.
+const BASE_PACKAGES: &[&str] = &["base", "linux", "linux-firmware", "sudo"];
+/// zram-generator's configuration as a printf format: one device using up
+/// to half the memory.
+const ZRAM_CONFIG: &str = "[zram0]\\nzram-size = ram / 2\\n";
+/// sed script uncommenting the sudoers line that lets the wheel group, and
+/// so the user, run anything with sudo.
+const SUDOERS_WHEEL: &str = "s/^# %wheel ALL=(ALL:ALL) ALL/%wheel ALL=(ALL:ALL) ALL/";
+/// The `LC_*` settings that follow the formats locale rather than the language.
+const FORMAT_SETTINGS: [&str; 6] =
+    ["LC_NUMERIC", "LC_TIME", "LC_MONETARY", "LC_PAPER", "LC_MEASUREMENT", "LC_ADDRESS"];
+/// systemd-boot's entry for the new system as a printf format, with the
+/// kernel's options to fill in.
+const LOADER_ENTRY: &str =
+    "title   EndeavourOS\\nlinux   /vmlinuz-linux\\ninitrd  /initramfs-linux.img\\noptions %s\\n";
+/// systemd-boot's settings: start that entry after a short wait.
+const LOADER_CONF: &str = "default endeavouros.conf\\ntimeout 3\\n";
+/// Name the unlocked root partition gets under `/dev/mapper`.
+const LUKS_NAME: &str = "root";
+
+/// Size of the EFI system partition erasing the disk creates, in MiB.
+const EFI_SIZE_MIB: u64 = 512;
+/// Size of the BIOS boot partition GRUB embeds itself in, in MiB.
+const BIOS_BOOT_SIZE_MIB: u64 = 1;
+/// Smallest root partition the system and a desktop fit in, in MiB.
+pub const MIN_ROOT_MIB: u64 = 16 * 1024;
+
+/// What a partition of the layout erasing the disk creates is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionRole {
+    Efi,
+    BiosBoot,
+    Swap,
+    Root,
+}
+
+impl PartitionRole {
+    pub fn label(self) -> &'static str {
+        match self {
+            PartitionRole::Efi => "EFI system",
+            PartitionRole::BiosBoot => "BIOS boot",
+            PartitionRole::Swap => "swap",
+            PartitionRole::Root => "root",
+        }
+    }
+
+    /// Where it is mounted in the new system, if anywhere.
+    pub fn mount_point(self) -> Option<&'static str> {
+        match self {
+            PartitionRole::Efi => Some("/boot"),
+            PartitionRole::Root => Some("/"),
+            PartitionRole::BiosBoot | PartitionRole::Swap => None,
+        }
+    }
+
+    /// sgdisk's type code for it.
+    fn type_code(self) -> &'static str {
+        match self {
+            PartitionRole::Efi => "ef00",
+            PartitionRole::BiosBoot => "ef02",
+            PartitionRole::Swap => "8200",
+            PartitionRole::Root => "8300",
+        }
+    }
+}
+
+/// A partition of the layout erasing the disk creates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutPartition {
+    pub number: u32,
+    pub role: PartitionRole,
+    /// Size in MiB. The root partition takes the rest of the disk, so its
+    /// size is only known with the disk's.
+    pub size_mib: Option<u64>,
+}
+
+/// The partitions erasing a disk of `disk_mib` MiB lays out: a boot
+/// partition for `firmware`, a swap partition if `swap` asks for one, and
+/// root in the rest.
+pub fn auto_layout(
+    firmware: Firmware,
+    swap: SwapConfig,
+    disk_mib: Option<u64>,
+) -> Vec<LayoutPartition> {
+    let mut layout = vec![match firmware {
+        Firmware::Uefi => (PartitionRole::Efi, EFI_SIZE_MIB),
+        Firmware::Bios => (PartitionRole::BiosBoot, BIOS_BOOT_SIZE_MIB),
+    }];
+    if let SwapConfig::Partition { size_gib } = swap {
+        layout.push((PartitionRole::Swap, u64::from(size_gib) * 1024));
+    }
+    let used: u64 = layout.iter().map(|(_, size)| size).sum();
+    let mut layout: Vec<LayoutPartition> = layout
+        .into_iter()
+        .map(|(role, size)| LayoutPartition { number: 0, role, size_mib: Some(size) })
+        .collect();
+    layout.push(LayoutPartition {
+        number: 0,
+        role: PartitionRole::Root,
+        size_mib: disk_mib.map(|disk| disk.saturating_sub(used)),
+    });
+    for (number, partition) in (1..).zip(&mut layout) {
+        partition.number = number;
+    }
+    layout
+}
+
+/// Whether `layout` leaves root less than [`MIN_ROOT_MIB`]; a layout for
+/// a disk of unknown size is given the benefit of the doubt.
+pub fn root_too_small(layout: &[LayoutPartition]) -> bool {
+    layout.iter().any(|partition| {
+        partition.role == PartitionRole::Root
+            && partition.size_mib.is_some_and(|size| size < MIN_ROOT_MIB)
+    })
+}
+
+/// The partition of `layout` with `role`.
+fn layout_number(layout: &[LayoutPartition], role: PartitionRole) -> Option<u32> {
+    layout.iter().find(|partition| partition.role == role).map(|partition| partition.number)
+}
+
+/// The shell commands an installation of `state` runs, in order.
+pub fn build_commands(state: &InstallerState) -> Vec<String> {
+    plan(state).into_iter().map(|(_, command, _)| command).collect()
+}
+
+/// [`build_commands`], with the phase each command belongs to and what it
+/// reads on stdin. Every
+/// answer and device name goes through [`shell_quote`], since the commands
+/// run with `sh -c` and answers files can say anything.
+fn plan(state: &InstallerState) -> Vec<Planned> {
+    let disk = state.disk.as_deref().unwrap_or("<disk>");
+    let disk_arg = shell_quote(disk);
+    let chroot = |command: String| format!("arch-chroot {TARGET} {command}");
+
+    let uefi = state.firmware == Firmware::Uefi;
+    let mut plan = match state.partition_scheme {
+        PartitionScheme::Erase => erase_disk(state, disk),
+        PartitionScheme::Manual => mount_existing(state),
+        // The new root goes in the largest free block; the existing EFI
+        // partition is shared with the other systems.
+        PartitionScheme::Alongside => {
+            let partition = format!("sgdisk -n 0:0:0 -t 0:8300 {disk_arg}");
+            let mut plan = vec![(InstallPhase::Partition, partition, None)];
+            let root = encrypt_root(state, &mut plan, &shell_quote("<new partition>"));
+            plan.push((
+                InstallPhase::Format,
+                format!("{} {root}", state.filesystem.mkfs_command()),
+                None,
+            ));
+            plan.push((InstallPhase::Format, format!("mount {root} {TARGET}"), None));
+            plan
+        }
+    };
+    let root_partition = match state.partition_scheme {
+        PartitionScheme::Erase => partition_path(disk, root_number(state)),
+        PartitionScheme::Alongside => "<new partition>".to_string(),
+        PartitionScheme::Manual => {
+            let root = state.mounts.iter().find(|(_, mount)| *mount == MountPoint::Root);
+            root.map_or("<root partition>".to_string(), |(partition, _)| partition.path())
+        }
+    };
+    let root_partition = shell_quote(&root_partition);
+    let luks_partition = match state.partition_scheme {
+        _ if state.encryption.is_none() => None,
+        PartitionScheme::Erase | PartitionScheme::Alongside => Some(root_partition.clone()),
+        PartitionScheme::Manual => None,
+    };
+    let boot_assigned = state.mounts.iter().any(|(_, mount)| *mount == MountPoint::Boot);
+    if uefi && state.partition_scheme != PartitionScheme::Erase && !boot_assigned {
+        let efi = shell_quote("<EFI partition>");
+        plan.push((InstallPhase::Format, format!("mount --mkdir {efi} {TARGET}/boot"), None));
+    }
+    let subvolumes = state.partition_scheme == PartitionScheme::Erase
+        && state.filesystem == Filesystem::Btrfs
+        && state.btrfs_subvolumes;
+    let snapshots = state.partition_scheme == PartitionScheme::Erase
+        && state.filesystem == Filesystem::Btrfs
+        && state.btrfs_snapshots;
+    let packages = BASE_PACKAGES.iter().chain(state.desktop().packages);
+    let mut packages: Vec<&str> = packages.copied().collect();
+    for package in state.driver.iter().flat_map(|driver| driver.packages) {
+        if !packages.contains(package) {
+            packages.push(package);
+        }
+    }
+    if snapshots {
+        packages.push("snapper");
+    }
+    if state.swap == SwapConfig::Zram {
+        packages.push("zram-generator");
+    }
+    if state.bootloader == Bootloader::Grub {
+        packages.push("grub");
+        if uefi {
+            packages.push("efibootmgr");
+        }
+    }
+    for package in state.extra_packages.iter().chain(&state.typed_packages) {
+        if !packages.contains(&package.as_str()) {
+            packages.push(package);
+        }
+    }
+    let packages: Vec<String> = packages.into_iter().map(shell_quote).collect();
+    plan.push((
+        InstallPhase::Pacstrap,
+        format!("pacstrap -K {TARGET} {}", packages.join(" ")),
+        None,
+    ));
+
+    let mut configure = vec![(format!("genfstab -U {TARGET} >> {TARGET}/etc/fstab"), None)];
+    if let Some(mirrorlist) = &state.mirrorlist {
+        // The chosen mirrorlist goes in on stdin, replacing the one pacstrap copied;
+        // tee is silenced so the whole list is not echoed into the install log.
+        let input = Input::Text(mirrorlist.clone());
+        configure.push((format!("tee {TARGET}/etc/pacman.d/mirrorlist > /dev/null"), Some(input)));
+    }
+    if let Some(zone) = &state.timezone {
+        let zone = shell_quote(&format!("/usr/share/zoneinfo/{zone}"));
+        configure.push((chroot(format!("ln -sf {zone} /etc/localtime")), None));
+        configure.push((chroot("hwclock --systohc".into()), None));
+    }
+    let uncomment: Vec<String> = state
+        .generated_locales()
+        .iter()
+        .map(|locale| format!("-e {}", shell_quote(&format!("s/^#{locale} UTF-8/{locale} UTF-8/"))))
+        .collect();
+    configure.push((chroot(format!("sed -i {} /etc/locale.gen", uncomment.join(" "))), None));
+    configure.push((chroot("locale-gen".into()), None));
+    let lang = shell_quote(&format!("LANG={}", state.locale()));
+    configure.push((format!("echo {lang} > {TARGET}/etc/locale.conf"), None));
+    if state.format_locale() != state.locale() {
+        let settings: String = FORMAT_SETTINGS
+            .iter()
+            .map(|setting| format!("{setting}={}\\n", state.format_locale()))
+            .collect();
+        let settings = shell_quote(&settings);
+        configure.push((format!("printf {settings} >> {TARGET}/etc/locale.conf"), None));
+    }
+    if state.swap == SwapConfig::Zram {
+        let config = format!("printf '{ZRAM_CONFIG}' > {TARGET}/etc/systemd/zram-generator.conf");
+        configure.push((config, None));
+    }
+    if let Some(keymap) = &state.keymap {
+        let keymap = shell_quote(&format!("KEYMAP={keymap}"));
+        configure.push((format!("echo {keymap} > {TARGET}/etc/vconsole.conf"), None));
+    }
+    if let Some(hostname) = &state.hostname {
+        let hostname = shell_quote(hostname);
+        configure.push((format!("echo {hostname} > {TARGET}/etc/hostname"), None));
+    }
+    if let Some(username) = &state.username {
+        let comment = state.full_name.as_deref().map(shell_quote);
+        let comment = comment.map_or(String::new(), |name| format!("-c {name} "));
+        let username = shell_quote(username);
+        configure.push((chroot(format!("useradd -m -G wheel {comment}{username}")), None));
+    }
+    if state.username.is_some() || state.root_password.is_some() {
+        // The passwords go in on stdin so they never show up in a command line.
+        let input = Input::Secret(chpasswd_input(state));
+        configure.push((chroot("chpasswd".into()), Some(input)));
+    }
+    if state.root_password.is_none() {
+        configure.push((chroot("passwd --lock root".into()), None));
+        configure.push((chroot(format!("sed -i '{SUDOERS_WHEEL}' /etc/sudoers")), None));
+    }
+    if snapshots {
+        configure.push((chroot("snapper --no-dbus -c root create-config /".into()), None));
+    }
+    if luks_partition.is_some() {
+        let hooks = "sed -i '/^HOOKS=/s/filesystems/encrypt filesystems/' /etc/mkinitcpio.conf";
+        configure.push((chroot(hooks.into()), None));
+        configure.push((chroot("mkinitcpio -P".into()), None));
+    }
+    let configure = configure.into_iter();
+    plan.extend(configure.map(|(command, input)| (InstallPhase::Configure, command, input)));
+    let mut bootloader = match (state.bootloader, uefi) {
+        // Unlike GRUB, systemd-boot finds no kernels itself; it gets an
+        // entry naming the kernel and the root to boot.
+        (Bootloader::SystemdBoot, _) => {
+            let mut options = match &luks_partition {
+                Some(partition) => format!(
+                    "cryptdevice=UUID=$(blkid -s UUID -o value {partition}):{LUKS_NAME} \
+                     root=/dev/mapper/{LUKS_NAME}"
+                ),
+                None => format!("root=UUID=$(blkid -s UUID -o value {root_partition})"),
+            };
+            if subvolumes {
+                options.push_str(" rootflags=subvol=@");
+            }
+            let loader = format!("{TARGET}/boot/loader");
+            vec![
+                chroot("bootctl install".into()),
+                format!("printf '{LOADER_CONF}' > {loader}/loader.conf"),
+                format!(
+                    "printf '{LOADER_ENTRY}' \"{options} rw\" > {loader}/entries/endeavouros.conf"
+                ),
+            ]
+        }
+        (Bootloader::Grub, true) => vec![chroot(
+            "grub-install --target=x86_64-efi --efi-directory=/boot --bootloader-id=EndeavourOS"
+                .into(),
+        )],
+        (Bootloader::Grub, false) => {
+            vec![chroot(format!("grub-install --target=i386-pc {disk_arg}"))]
+        }
+    };
+    if state.bootloader == Bootloader::Grub {
+        // The kernel has to be told which partition to unlock at boot.
+        if let Some(partition) = &luks_partition {
+            bootloader.push(chroot(format!(
+                "sed -i \"s|^GRUB_CMDLINE_LINUX=\\\"|&cryptdevice=UUID=$(blkid -s UUID -o value \
+                 {partition}):{LUKS_NAME} |\" /etc/default/grub"
+            )));
+        }
+        bootloader.push(chroot("grub-mkconfig -o /boot/grub/grub.cfg".into()));
+    }
+    let bootloader = bootloader.into_iter();
+    plan.extend(bootloader.map(|command| (InstallPhase::Bootloader, command, None)));
+    plan
+}
+
+/// Wipe `disk` and lay out a boot partition, an optional swap partition and
+/// the root partition, then mount them. The boot partition is the EFI system
+/// partition on UEFI, and a BIOS boot partition for GRUB to embed itself in
+/// otherwise.
+fn erase_disk(state: &InstallerState, disk: &str) -> Vec<Planned> {
+    let boot = shell_quote(&partition_path(disk, 1));
+    let root = shell_quote(&partition_path(disk, root_number(state)));
+    let disk_arg = shell_quote(disk);
+    let subvolumes = state.filesystem == Filesystem::Btrfs && state.btrfs_subvolumes;
+
+    let layout: Vec<String> = auto_layout(state.firmware, state.swap, None)
+        .into_iter()
+        .map(|LayoutPartition { number, role, size_mib }| {
+            let end = match size_mib {
+                Some(size) if size.is_multiple_of(1024) => format!("+{}G", size / 1024),
+                Some(size) => format!("+{size}M"),
+                None => "0".to_string(),
+            };
+            format!("-n {number}:0:{end} -t {number}:{}", role.type_code())
+        })
+        .collect();
+    let uefi = state.firmware == Firmware::Uefi;
+    let mut plan = vec![
+        (InstallPhase::Partition, format!("sgdisk --zap-all {disk_arg}"), None),
+        (InstallPhase::Partition, format!("sgdisk {} {disk_arg}", layout.join(" ")), None),
+    ];
+    if uefi {
+        plan.push((InstallPhase::Format, format!("mkfs.fat -F32 {boot}"), None));
+    }
+    let root = encrypt_root(state, &mut plan, &root);
+    let mkfs = format!("{} {root}", state.filesystem.mkfs_command());
+    plan.push((InstallPhase::Format, mkfs, None));
+    if subvolumes {
+        let mut names = vec![("@", ""), ("@home", "/home")];
+        if matches!(state.swap, SwapConfig::File { .. }) {
+            names.push(("@swap", "/swap"));
+        }
+        plan.push((InstallPhase::Format, format!("mount {root} {TARGET}"), None));
+        for (name, _) in &names {
+            let create = format!("btrfs subvolume create {TARGET}/{name}");
+            plan.push((InstallPhase::Format, create, None));
+        }
+        plan.push((InstallPhase::Format, format!("umount {TARGET}"), None));
+        for (name, mount_point) in names {
+            plan.push((
+                InstallPhase::Format,
+                format!("mount --mkdir -o subvol={name} {root} {TARGET}{mount_point}"),
+                None,
+            ));
+        }
+    } else {
+        plan.push((InstallPhase::Format, format!("mount {root} {TARGET}"), None));
+    }
+    if uefi {
+        plan.push((InstallPhase::Format, format!("mount --mkdir {boot} {TARGET}/boot"), None));
+    }
+
+    // Swap is turned on before genfstab so that it ends up in the fstab.
+    let swap = match state.swap {
+        // zram is set up by a generator at boot, and never in the fstab.
+        SwapConfig::None | SwapConfig::Zram => return plan,
+        SwapConfig::Partition { .. } => {
+            let layout = auto_layout(state.firmware, state.swap, None);
+            let number = layout_number(&layout, PartitionRole::Swap).unwrap_or(2);
+            let swap = shell_quote(&partition_path(disk, number));
+            plan.push((InstallPhase::Format, format!("mkswap {swap}"), None));
+            swap
+        }
+        SwapConfig::File { size_gib } => {
+            let file = if subvolumes {
+                format!("{TARGET}/swap/swapfile")
+            } else {
+                format!("{TARGET}/swapfile")
+            };
+            let create = match state.filesystem {
+                Filesystem::Btrfs => {
+                    format!("btrfs filesystem mkswapfile --size {size_gib}G {file}")
+                }
+                Filesystem::Ext4 | Filesystem::Xfs => {
+                    format!("mkswap --file {file} --size {size_gib}G")
+                }
+            };
+            plan.push((InstallPhase::Format, create, None));
+            file
+        }
+    };
+    plan.push((InstallPhase::Format, format!("swapon {swap}"), None));
+    plan
+}
+
+/// Number of the root partition when the disk is erased: after the boot
+/// partition, and after the swap partition if there is one.
+fn root_number(state: &InstallerState) -> u32 {
+    let layout = auto_layout(state.firmware, state.swap, None);
+    layout_number(&layout, PartitionRole::Root).unwrap_or(2)
+}
+
+/// Mount the partitions the user assigned: root first, then what goes
+/// inside it, and swap turned on. Without a root partition, which only
+/// happens with answers files, the plan keeps a placeholder and cannot run.
+fn mount_existing(state: &InstallerState) -> Vec<Planned> {
+    let assigned = |mount: MountPoint| {
+        let partitions = state.mounts.iter().filter(move |(_, m)| *m == mount);
+        partitions.map(|(partition, _)| shell_quote(&partition.path()))
+    };
+    let root = assigned(MountPoint::Root).next().unwrap_or_else(|| shell_quote("<root partition>"));
+    let mut plan = vec![(InstallPhase::Format, format!("mount {root} {TARGET}"), None)];
+    for mount in [MountPoint::Boot, MountPoint::Home] {
+        for partition in assigned(mount) {
+            let command = format!("mount --mkdir {partition} {TARGET}{}", mount.label());
+            plan.push((InstallPhase::Format, command, None));
+        }
+    }
+    for partition in assigned(MountPoint::Swap) {
+        plan.push((InstallPhase::Format, format!("swapon {partition}"), None));
+    }
+    plan
+}
+
+/// Options a filesystem is mounted with, as genfstab writes them.
+fn mount_options(fstype: &str) -> &'static str {
+    match fstype {
+        "ext4" => "rw,relatime",
+        "btrfs" => "rw,relatime,space_cache=v2",
+        "xfs" => "rw,relatime,attr2,inode64,noquota",
+        // Only root can read the EFI system partition.
+        "vfat" => "rw,relatime,fmask=0077,dmask=0077",
+        _ => "defaults",
+    }
+}
+
+/// The fstab installing `state` leads to, without touching any disk.
+/// Partitions already on the disk are named by UUID. Ones the installation
+/// creates have no UUID yet, so they go by device; genfstab writes the
+/// real file once they exist.
+pub fn generate_fstab(state: &InstallerState) -> String {
+    let disk = state.disk.as_deref().unwrap_or("<disk>");
+    let entry = |source: String, target: &str, fstype: &str, options: String| {
+        // fsck has nothing to do on btrfs, and root is checked first.
+        let pass = match (fstype, target) {
+            ("btrfs" | "swap", _) => 0,
+            (_, "/") => 1,
+            _ => 2,
+        };
+        format!("{source} {target} {fstype} {options} 0 {pass}\n")
+    };
+    let mut entries = Vec::new();
+    let new_root = |partition: String| match state.encryption {
+        Some(_) => format!("/dev/mapper/{LUKS_NAME}"),
+        None => partition,
+    };
+    match state.partition_scheme {
+        PartitionScheme::Erase | PartitionScheme::Alongside => {
+            let root = match state.partition_scheme {
+                PartitionScheme::Erase => new_root(partition_path(disk, root_number(state))),
+                _ => new_root("<new partition>".to_string()),
+            };
+            let fstype = state.filesystem.label();
+            let options = mount_options(fstype);
+            let subvolumes = state.partition_scheme == PartitionScheme::Erase
+                && state.filesystem == Filesystem::Btrfs
+                && state.btrfs_subvolumes;
+            if subvolumes {
+                let mut names = vec![("@", "/"), ("@home", "/home")];
+                if matches!(state.swap, SwapConfig::File { .. }) {
+                    names.push(("@swap", "/swap"));
+                }
+                for (name, target) in names {
+                    let options = format!("{options},subvol=/{name}");
+                    entries.push(entry(root.clone(), target, fstype, options));
+                }
+            } else {
+                entries.push(entry(root, "/", fstype, options.to_string()));
+            }
+            if state.firmware == Firmware::Uefi {
+                let boot = match state.partition_scheme {
+                    PartitionScheme::Erase => partition_path(disk, 1),
+                    _ => "<EFI partition>".to_string(),
+                };
+                entries.push(entry(boot, "/boot", "vfat", mount_options("vfat").to_string()));
+            }
+        }
+        PartitionScheme::Manual => {
+            let mut mounts: Vec<_> = state.mounts.iter().collect();
+            mounts.sort_by_key(|(_, mount)| MountPoint::ALL.iter().position(|m| m == mount));
+            if !mounts.iter().any(|(_, mount)| *mount == MountPoint::Root) {
+                entries.push(entry("<root partition>".into(), "/", "auto", "defaults".into()));
+            }
+            for (partition, mount) in mounts {
+                let source = match partition.uuid.as_str() {
+                    "" => partition.path(),
+                    uuid => format!("UUID={uuid}"),
+                };
+                if *mount == MountPoint::Swap {
+                    entries.push(entry(source, "none", "swap", "defaults".into()));
+                    continue;
+                }
+                let fstype = if partition.fstype.is_empty() { "auto" } else { &partition.fstype };
+                let options = mount_options(fstype).to_string();
+                entries.push(entry(source, mount.label(), fstype, options));
+            }
+            let boot_assigned = state.mounts.iter().any(|(_, mount)| *mount == MountPoint::Boot);
+            if state.firmware == Firmware::Uefi && !boot_assigned {
+                let options = mount_options("vfat").to_string();
+                entries.push(entry("<EFI partition>".into(), "/boot", "vfat", options));
+            }
+        }
+    }
+    match state.swap {
+        SwapConfig::Partition { .. } if state.partition_scheme == PartitionScheme::Erase => {
+            let layout = auto_layout(state.firmware, state.swap, None);
+            let number = layout_number(&layout, PartitionRole::Swap).unwrap_or(2);
+            entries.push(entry(partition_path(disk, number), "none", "swap", "defaults".into()));
+        }
+        SwapConfig::File { .. } => {
+            let subvolumes = state.filesystem == Filesystem::Btrfs && state.btrfs_subvolumes;
+            let file = if subvolumes { "/swap/swapfile" } else { "/swapfile" };
+            entries.push(entry(file.into(), "none", "swap", "defaults".into()));
+        }
+        // zram is set up by a generator at boot, and never in the fstab.
+        _ => {}
+    }
+
+    let header = "# <file system> <dir> <type> <options> <dump> <pass>\n".to_string();
+    header + &entries.concat()
+}
+
+/// Set `partition` up as a LUKS container if encryption was asked for,
+/// returning the device the root filesystem goes on. The passphrase goes
+/// in on stdin so it never shows up in a command line.
+fn encrypt_root(
+    state: &InstallerState,
+    plan: &mut Vec<Planned>,
+    partition: &str,
+) -> String {
+    let Some(encryption) = &state.encryption else {
+        return partition.to_string();
+    };
+    // cryptsetup reads the passphrase on stdin, not from its command line.
+    let passphrase = || Some(Input::Secret(encryption.passphrase.clone()));
+    plan.push((
+        InstallPhase::Format,
+        format!("cryptsetup luksFormat --batch-mode --key-file=- {partition}"),
+        passphrase(),
+    ));
+    plan.push((
+        InstallPhase::Format,
+        format!("cryptsetup open --key-file=- {partition} {LUKS_NAME}"),
+        passphrase(),
+    ));
+    format!("/dev/mapper/{LUKS_NAME}")
+}
+
+/// Device path of partition `number` on `disk`, e.g. `/dev/nvme0n1p2`.
+fn partition_path(disk: &str, number: u32) -> String {
+    if disk.ends_with(|c: char| c.is_ascii_digit()) {
+        format!("{disk}p{number}")
+    } else {
+        format!("{disk}{number}")
+    }
+}
+
+/// Quote `value` for a POSIX shell if it needs it.
+fn shell_quote(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !value.is_empty() && value.chars().all(safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Start installing `state` on a background thread from command `from` of
+/// its plan on, returning the channel its events arrive on. With `dry_run`,
+/// the commands are only reported. Setting `cancel` stops it before the
+/// next phase begins. Downloads that fail are tried again as `backoff` says.
+pub fn spawn_install(
+    state: &InstallerState,
+    dry_run: bool,
+    from: usize,
+    cancel: Arc<AtomicBool>,
+    backoff: Backoff,
+) -> Receiver<InstallEvent> {
+    let (tx, rx) = mpsc::channel();
+    let plan = plan(state);
+    let has_disk = state.disk.is_some();
+    let missing = devices_missing(state);
+    thread::spawn(move || match (has_disk, dry_run, missing) {
+        (false, _, _) => {
+            let _ = tx.send(InstallEvent::Failed("No target disk was selected".into()));
+        }
+        (true, true, _) => dry_run_install(&tx, &plan, from, &cancel, DRY_RUN_STEP_DELAY),
+        (true, false, Some(reason)) => {
+            let _ = tx.send(InstallEvent::Failed(reason.into()));
+        }
+        (true, false, None) => run_install(&tx, &plan, from, &cancel, &RealRunner, backoff),
+    });
+    rx
+}
+
+/// Why the plan for `state` still stands in for a device with a
+/// placeholder like `<new partition>`, which [`plan`] puts in for
+/// partitions only made or picked later, if it does. The disk is checked
+/// apart.
+fn devices_missing(state: &InstallerState) -> Option<&'static str> {
+    let assigned = |mount| state.mounts.iter().any(|(_, m)| *m == mount);
+    match state.partition_scheme {
+        PartitionScheme::Erase => None,
+        // The new partition only exists once sgdisk has made it.
+        PartitionScheme::Alongside => Some(ALONGSIDE_UNSUPPORTED),
+        PartitionScheme::Manual => {
+            let boot_known = state.firmware == Firmware::Bios || assigned(MountPoint::Boot);
+            let known = assigned(MountPoint::Root) && boot_known;
+            (!known).then_some("Assign a root (and, on UEFI, /boot) mount point first")
+        }
+    }
+}
+
+/// Why installing alongside the systems already on the disk stops before
+/// it starts.
+pub const ALONGSIDE_UNSUPPORTED: &str = "Installing alongside is not supported yet";
+
+/// What chpasswd reads on stdin: a `name:password` line for the user and
+/// for root, each if it has a password.
+fn chpasswd_input(state: &InstallerState) -> Secret {
+    let user = state.username.as_ref().zip(state.password.as_ref());
+    let root = state.root_password.as_ref().map(|password| ("root", password));
+    let lines: Vec<(&str, &str)> = user
+        .map(|(name, password)| (name.as_str(), password))
+        .into_iter()
+        .chain(root)
+        .map(|(name, password)| (name, password.expose()))
+        .collect();
+    // Sized up front, so no copy of the passwords is left behind by growing.
+    let size = lines.iter().map(|(name, password)| name.len() + password.len() + 2).sum();
+    let mut input = String::with_capacity(size);
+    for (name, password) in lines {
+        input.extend([name, ":", password, "\n"]);
+    }
+    Secret::new(input)
+}
+
+/// Pause between the commands of a dry run, so they can be followed on screen.
+const DRY_RUN_STEP_DELAY: Duration = Duration::from_millis(150);
+
+/// Report every command of `plan` from `from` on without running any of them.
+fn dry_run_install(
+    tx: &Sender<InstallEvent>,
+    plan: &[Planned],
+    from: usize,
+    cancel: &AtomicBool,
+    delay: Duration,
+) {
+    for (index, (_, command, _)) in plan.iter().enumerate().skip(from) {
+        if stop_if_cancelled(tx, plan, from, index, cancel) {
+            return;
+        }
+        log::info!("dry run, not running: {command}");
+        let progress = (index + 1) * 100 / plan.len();
+        let events = [
+            InstallEvent::Log(format!("[dry run] {command}")),
+            InstallEvent::Progress(progress as u8),
+        ];
+        if events.into_iter().any(|event| tx.send(event).is_err()) {
+            return;
+        }
+        thread::sleep(delay);
+    }
+    log::info!("dry run finished");
+    let _ = tx.send(InstallEvent::Done);
+}
+
+/// If `cancel` is set and command `index` of `plan` would start a new
+/// phase, or this run, tell the UI the installation was cancelled and
+/// return true. Stopping within a phase would leave it half done.
+fn stop_if_cancelled(
+    tx: &Sender<InstallEvent>,
+    plan: &[Planned],
+    from: usize,
+    index: usize,
+    cancel: &AtomicBool,
+) -> bool {
+    let phase_starts = index == from || plan[index - 1].0 != plan[index].0;
+    if !phase_starts || !cancel.load(Ordering::SeqCst) {
+        return false;
+    }
+    let done = &plan[..index];
+    let phases = InstallPhase::ALL
+        .into_iter()
+        .filter(|&phase| done.iter().any(|(ran, ..)| *ran == phase))
+        .collect();
+    log::info!("installation cancelled before `{}`", plan[index].1);
+    let _ = tx.send(InstallEvent::Cancelled(phases));
+    true
+}
+
+/// How many of a failed command's last stderr lines are shown.
+const STDERR_TAIL_LINES: usize = 5;
+
+/// A command of the installation that did not succeed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandFailure {
+    /// Position of the command in the plan, where a retry starts.
+    pub index: usize,
+    pub phase: InstallPhase,
+    pub command: String,
+    /// Exit code; `None` if it was killed by a signal or never started.
+    pub code: Option<i32>,
+    /// The last lines it wrote to stderr.
+    pub stderr: Vec<String>,
+}
+
+impl fmt::Display for CommandFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "`{}` exited with status {code}", self.command),
+            None => write!(f, "`{}` did not finish", self.command),
+        }
+    }
+}
+
+/// Run every command of `plan` from `from` on with `runner`, stopping at
+/// the first that fails or once `cancel` is set. A command of a retryable
+/// phase is run up to `backoff.attempts` times before it counts as failed.
+fn run_install(
+    tx: &Sender<InstallEvent>,
+    plan: &[Planned],
+    from: usize,
+    cancel: &AtomicBool,
+    runner: &dyn CommandRunner,
+    backoff: Backoff,
+) {
+    for (index, (phase, command, input)) in plan.iter().enumerate().skip(from) {
+        if stop_if_cancelled(tx, plan, from, index, cancel) {
+            return;
+        }
+        if tx.send(InstallEvent::Log(command.clone())).is_err() {
+            return;
+        }
+        let mut forward = |line: &str| {
+            let _ = tx.send(InstallEvent::Output(line.to_string()));
+        };
+        let attempts = if phase.retryable() { backoff.attempts } else { 1 };
+        let result = retry(attempts, backoff.base_delay, cancel, |attempt| {
+            if attempt > 1 {
+                let retrying = format!("Retrying ({attempt}/{attempts})… {command}");
+                let _ = tx.send(InstallEvent::Log(retrying));
+            }
+            run_command(runner, command, input.as_ref().map(Input::as_str), &mut forward).map_err(
+                |(code, stderr)| CommandFailure {
+                    index,
+                    phase: *phase,
+                    command: command.clone(),
+                    code,
+                    stderr,
+                },
+            )
+        });
+        if let Err(failure) = result {
+            log::error!("{failure}");
+            let _ = tx.send(InstallEvent::CommandFailed(failure));
+            return;
+        }
+        let progress = (index + 1) * 100 / plan.len();
+        if tx.send(InstallEvent::Progress(progress as u8)).is_err() {
+            return;
+        }
+    }
+    log::info!("installation finished");
+    let _ = tx.send(InstallEvent::Done);
+}
+
+/// Run `command` with `sh`, feeding it `input`, and log what it prints,
+/// handing each line to `on_line` as it comes. On failure, returns its exit
+/// code and the tail of its stderr.
+fn run_command(
+    runner: &dyn CommandRunner,
+    command: &str,
+    input: Option<&str>,
+    on_line: &mut dyn FnMut(&str),
+) -> Result<(), (Option<i32>, Vec<String>)> {
+    log::info!("running: {command}");
+    let output = runner
+        .run_streaming("sh", &["-c", command], input, on_line)
+        .map_err(|err| (None, vec![err.to_string()]))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines() {
+        log::debug!("stdout: {line}");
+    }
+    for line in stderr.lines() {
+        log::warn!("stderr: {line}");
+    }
+    log::info!("`{command}` finished: {}", output.status);
+    if output.status.success() {
+        return Ok(());
+    }
+    let lines: Vec<&str> = stderr.lines().filter(|line| !line.trim().is_empty()).collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].iter();
+    Err((output.status.code(), tail.map(|line| line.to_string()).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::EncryptionConfig;
+    use crate::runner::MockRunner;
+    use crate::system::disks::Partition;
+    use std::{io, process::Output};
+
+    /// Every command run just once, as most tests want.
+    const ONCE: Backoff = Backoff { attempts: 1, base_delay: Duration::ZERO };
+
+    #[test]
+    fn phase_follows_progress() {
+        assert_eq!(InstallPhase::at(0.0), InstallPhase::Partition);
+        assert_eq!(InstallPhase::at(0.2), InstallPhase::Format);
+        assert_eq!(InstallPhase::at(0.5), InstallPhase::Pacstrap);
+        assert!(InstallPhase::Pacstrap.retryable());
+        assert!(!InstallPhase::Partition.retryable());
+        assert_eq!(InstallPhase::at(0.99), InstallPhase::Bootloader);
+        assert_eq!(InstallPhase::at(1.0), InstallPhase::Bootloader);
+    }
+
+    fn answers() -> InstallerState {
+        InstallerState {
+            keymap: Some("de".into()),
+            timezone: Some("Europe/Berlin".into()),
+            disk: Some("/dev/nvme0n1".into()),
+            hostname: Some("werkstatt".into()),
+            username: Some("jan".into()),
+            full_name: Some("Jan O'Neil".into()),
+            password: Some("hunter2".into()),
+            ..InstallerState::default()
+        }
+    }
+
+    #[test]
+    fn commands_follow_the_answers() {
+        let commands = build_commands(&answers());
+        assert_eq!(commands[0], "sgdisk --zap-all /dev/nvme0n1");
+        assert!(commands.contains(&"mkfs.ext4 -F /dev/nvme0n1p2".to_string()));
+        assert!(commands.contains(&"mount --mkdir /dev/nvme0n1p1 /mnt/boot".to_string()));
+        assert!(commands.iter().any(|c| c.starts_with("pacstrap -K /mnt base linux")
+            && c.ends_with("plasma-meta konsole dolphin sddm zram-generator")));
+        assert!(commands.contains(
+            &"arch-chroot /mnt ln -sf /usr/share/zoneinfo/Europe/Berlin /etc/localtime".to_string()
+        ));
+        assert!(commands.contains(
+            &"arch-chroot /mnt useradd -m -G wheel -c 'Jan O'\\''Neil' jan".to_string()
+        ));
+        assert!(commands.contains(&"arch-chroot /mnt bootctl install".to_string()));
+        assert!(commands.last().unwrap().ends_with(" > /mnt/boot/loader/entries/endeavouros.conf"));
+        assert!(commands.iter().all(|c| !c.contains("hunter2")));
+        assert!(!commands.iter().any(|c| c.contains("mirrorlist")));
+    }
+
+    #[test]
+    fn answers_cannot_break_out_of_their_command() {
+        let state = InstallerState {
+            timezone: Some("UTC; rm -rf /".into()),
+            keymap: Some("de $(reboot)".into()),
+            hostname: Some("host`id`".into()),
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        for expected in [
+            "arch-chroot /mnt ln -sf '/usr/share/zoneinfo/UTC; rm -rf /' /etc/localtime",
+            "echo 'KEYMAP=de $(reboot)' > /mnt/etc/vconsole.conf",
+            "echo 'host`id`' > /mnt/etc/hostname",
+        ] {
+            assert!(commands.contains(&expected.to_string()), "missing {expected:?}");
+        }
+
+        // What the shell hands the command is the answer, as typed.
+        let runner = RealRunner;
+        let zone = commands.iter().find(|c| c.contains("zoneinfo")).unwrap();
+        let echoed = zone.replace("arch-chroot /mnt ln -sf", "printf '%s\\n'");
+        let output = runner.run("sh", &["-c", &echoed], None).unwrap();
+        assert_eq!(output.stdout, b"/usr/share/zoneinfo/UTC; rm -rf /\n/etc/localtime\n");
+    }
+
+    #[test]
+    fn formats_locale_is_added_to_locale_conf() {
+        let commands = build_commands(&answers());
+        assert!(!commands.iter().any(|c| c.contains("LC_")));
+
+        let state = InstallerState { format_locale: Some("de_DE.UTF-8".into()), ..answers() };
+        let commands = build_commands(&state);
+        let lang = commands.iter().position(|c| c.starts_with("echo LANG=en_US.UTF-8 >")).unwrap();
+        let formats = &commands[lang + 1];
+        assert!(formats.starts_with("printf 'LC_NUMERIC=de_DE.UTF-8\\nLC_TIME=de_DE.UTF-8\\n"));
+        assert!(formats.ends_with("' >> /mnt/etc/locale.conf"));
+    }
+
+    #[test]
+    fn every_chosen_locale_is_generated() {
+        let state = InstallerState {
+            format_locale: Some("de_DE.UTF-8".into()),
+            extra_locales: vec!["en_US.UTF-8".into(), "ja_JP.UTF-8".into()],
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        let sed = commands.iter().position(|c| c.ends_with(" /etc/locale.gen")).unwrap();
+        assert_eq!(
+            commands[sed],
+            "arch-chroot /mnt sed -i -e 's/^#en_US.UTF-8 UTF-8/en_US.UTF-8 UTF-8/' \
+             -e 's/^#de_DE.UTF-8 UTF-8/de_DE.UTF-8 UTF-8/' \
+             -e 's/^#ja_JP.UTF-8 UTF-8/ja_JP.UTF-8 UTF-8/' /etc/locale.gen"
+        );
+        assert_eq!(commands[sed + 1], "arch-chroot /mnt locale-gen");
+    }
+
+    #[test]
+    fn root_is_locked_unless_it_has_a_password() {
+        let commands = build_commands(&answers());
+        assert!(commands.contains(&"arch-chroot /mnt passwd --lock root".to_string()));
+        assert!(commands.iter().any(|c| c.contains("%wheel ALL=(ALL:ALL) ALL")));
+
+        let state = InstallerState { root_password: Some("toor".into()), ..answers() };
+        let commands = build_commands(&state);
+        let chpasswd = commands.iter().filter(|c| *c == "arch-chroot /mnt chpasswd").count();
+        assert_eq!(chpasswd, 1);
+        assert!(!commands.iter().any(|c| c.contains("passwd --lock") || c.contains("sudoers")));
+        assert!(commands.iter().all(|c| !c.contains("toor")));
+    }
+
+    #[test]
+    fn command_list_is_stable() {
+        assert_eq!(
+            build_commands(&answers()),
+            [
+                "sgdisk --zap-all /dev/nvme0n1",
+                "sgdisk -n 1:0:+512M -t 1:ef00 -n 2:0:0 -t 2:8300 /dev/nvme0n1",
+                "mkfs.fat -F32 /dev/nvme0n1p1",
+                "mkfs.ext4 -F /dev/nvme0n1p2",
+                "mount /dev/nvme0n1p2 /mnt",
+                "mount --mkdir /dev/nvme0n1p1 /mnt/boot",
+                "pacstrap -K /mnt base linux linux-firmware sudo plasma-meta konsole dolphin sddm \
+                 zram-generator",
+                "genfstab -U /mnt >> /mnt/etc/fstab",
+                "arch-chroot /mnt ln -sf /usr/share/zoneinfo/Europe/Berlin /etc/localtime",
+                "arch-chroot /mnt hwclock --systohc",
+                "arch-chroot /mnt sed -i -e 's/^#en_US.UTF-8 UTF-8/en_US.UTF-8 UTF-8/' \
+                 /etc/locale.gen",
+                "arch-chroot /mnt locale-gen",
+                "echo LANG=en_US.UTF-8 > /mnt/etc/locale.conf",
+                "printf '[zram0]\\nzram-size = ram / 2\\n' > /mnt/etc/systemd/zram-generator.conf",
+                "echo KEYMAP=de > /mnt/etc/vconsole.conf",
+                "echo werkstatt > /mnt/etc/hostname",
+                "arch-chroot /mnt useradd -m -G wheel -c 'Jan O'\\''Neil' jan",
+                "arch-chroot /mnt chpasswd",
+                "arch-chroot /mnt passwd --lock root",
+                "arch-chroot /mnt sed -i 's/^# %wheel ALL=(ALL:ALL) ALL/%wheel ALL=(ALL:ALL) ALL/' \
+                 /etc/sudoers",
+                "arch-chroot /mnt bootctl install",
+                "printf 'default endeavouros.conf\\ntimeout 3\\n' > /mnt/boot/loader/loader.conf",
+                "printf 'title   EndeavourOS\\nlinux   /vmlinuz-linux\\n\
+                 initrd  /initramfs-linux.img\\noptions %s\\n' \
+                 \"root=UUID=$(blkid -s UUID -o value /dev/nvme0n1p2) rw\" \
+                 > /mnt/boot/loader/entries/endeavouros.conf",
+            ]
+        );
+    }
+
+    #[test]
+    fn chosen_mirrorlist_is_written_after_pacstrap() {
+        let state = InstallerState {
+            mirrorlist: Some("Server = https://mirror.example/$repo/os/$arch\n".into()),
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        let pacstrap = commands.iter().position(|c| c.starts_with("pacstrap")).unwrap();
+        let tee = commands.iter().position(|c| c == "tee /mnt/etc/pacman.d/mirrorlist > /dev/null");
+        assert!(tee.unwrap() > pacstrap);
+    }
+
+    #[test]
+    fn erasing_with_swap_puts_root_third() {
+        let state = InstallerState {
+            filesystem: Filesystem::Btrfs,
+            swap: SwapConfig::Partition { size_gib: 4 },
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        assert!(commands.contains(
+            &"sgdisk -n 1:0:+512M -t 1:ef00 -n 2:0:+4G -t 2:8200 -n 3:0:0 -t 3:8300 /dev/nvme0n1"
+                .to_string()
+        ));
+        assert!(commands.contains(&"mkfs.btrfs -f /dev/nvme0n1p3".to_string()));
+        let swapon = commands.iter().position(|c| c == "swapon /dev/nvme0n1p2").unwrap();
+        let genfstab = commands.iter().position(|c| c.starts_with("genfstab")).unwrap();
+        assert!(swapon < genfstab);
+    }
+
+    #[test]
+    fn extra_packages_are_installed_once() {
+        // KDE Plasma already brings konsole in.
+        let extra_packages = vec!["git".to_string(), "konsole".to_string(), "htop".to_string()];
+        let state = InstallerState { extra_packages, ..answers() };
+        let commands = build_commands(&state);
+        let pacstrap = commands.iter().find(|c| c.starts_with("pacstrap")).unwrap();
+        assert!(pacstrap.ends_with(" git htop"));
+        assert_eq!(pacstrap.matches(" konsole").count(), 1);
+
+        // Typed names come after the ticked ones, and only once too.
+        let state = InstallerState {
+            extra_packages: vec!["git".to_string()],
+            typed_packages: vec!["tmux".to_string(), "git".to_string()],
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        let pacstrap = commands.iter().find(|c| c.starts_with("pacstrap")).unwrap();
+        assert!(pacstrap.ends_with(" git tmux"));
+    }
+
+    #[test]
+    fn swap_is_sized_as_chosen_or_left_to_zram() {
+        let state = InstallerState { swap: SwapConfig::File { size_gib: 12 }, ..answers() };
+        let commands = build_commands(&state);
+        assert!(commands.contains(&"mkswap --file /mnt/swapfile --size 12G".to_string()));
+        assert!(commands.contains(&"swapon /mnt/swapfile".to_string()));
+
+        let state = InstallerState { swap: SwapConfig::Zram, ..answers() };
+        let commands = build_commands(&state);
+        let pacstrap = commands.iter().find(|c| c.starts_with("pacstrap")).unwrap();
+        assert!(pacstrap.contains(" zram-generator"));
+        assert!(commands.contains(
+            &"printf '[zram0]\\nzram-size = ram / 2\\n' > /mnt/etc/systemd/zram-generator.conf"
+                .to_string()
+        ));
+        assert!(!commands.iter().any(|c| c.starts_with("swapon") || c.contains("8200")));
+    }
+
+    #[test]
+    fn btrfs_subvolumes_are_mounted_with_swap_file_and_snapshots() {
+        let state = InstallerState {
+            filesystem: Filesystem::Btrfs,
+            btrfs_subvolumes: true,
+            btrfs_snapshots: true,
+            swap: SwapConfig::File { size_gib: 4 },
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        for expected in [
+            "btrfs subvolume create /mnt/@home",
+            "mount --mkdir -o subvol=@ /dev/nvme0n1p2 /mnt",
+            "mount --mkdir -o subvol=@swap /dev/nvme0n1p2 /mnt/swap",
+            "btrfs filesystem mkswapfile --size 4G /mnt/swap/swapfile",
+            "swapon /mnt/swap/swapfile",
+            "arch-chroot /mnt snapper --no-dbus -c root create-config /",
+        ] {
+            assert!(commands.contains(&expected.to_string()), "missing {expected:?}");
+        }
+        assert!(commands.iter().any(|c| c.starts_with("pacstrap") && c.ends_with(" snapper")));
+    }
+
+    #[test]
+    fn grub_on_bios_uses_a_bios_boot_partition() {
+        let state = InstallerState {
+            firmware: Firmware::Bios,
+            bootloader: Bootloader::Grub,
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        assert!(commands[1].starts_with("sgdisk -n 1:0:+1M -t 1:ef02 -n 2:0:0"));
+        assert!(!commands.iter().any(|c| c.starts_with("mkfs.fat") || c.ends_with("/mnt/boot")));
+        assert!(commands.iter().any(|c| c.starts_with("pacstrap") && c.ends_with(" grub")));
+        assert!(commands.contains(&"arch-chroot /mnt grub-install --target=i386-pc /dev/nvme0n1".to_string()));
+        assert_eq!(commands.last().unwrap(), "arch-chroot /mnt grub-mkconfig -o /boot/grub/grub.cfg");
+
+        let uefi = InstallerState { bootloader: Bootloader::Grub, ..answers() };
+        let commands = build_commands(&uefi);
+        assert!(commands.iter().any(|c| c.contains("--target=x86_64-efi --efi-directory=/boot")));
+        assert!(commands.iter().any(|c| c.starts_with("pacstrap") && c.ends_with(" grub efibootmgr")));
+    }
+
+    #[test]
+    fn only_erasing_wipes_the_disk() {
+        for scheme in [PartitionScheme::Manual, PartitionScheme::Alongside] {
+            let state = InstallerState { partition_scheme: scheme, ..answers() };
+            let commands = build_commands(&state);
+            assert!(!commands.iter().any(|c| c.contains("--zap-all")), "{scheme:?}");
+            assert!(!commands.iter().any(|c| c.starts_with("mkfs.fat")), "{scheme:?}");
+        }
+        let manual = InstallerState { partition_scheme: PartitionScheme::Manual, ..answers() };
+        assert_eq!(build_commands(&manual)[0], "mount '<root partition>' /mnt");
+    }
+
+    fn partition(name: &str) -> Partition {
+        Partition { name: name.into(), ..Partition::default() }
+    }
+
+    #[test]
+    fn manual_partitioning_mounts_the_assigned_partitions() {
+        let state = InstallerState {
+            partition_scheme: PartitionScheme::Manual,
+            mounts: vec![
+                (partition("sda3"), MountPoint::Home),
+                (partition("sda1"), MountPoint::Boot),
+                (partition("sda4"), MountPoint::Swap),
+                (partition("sda2"), MountPoint::Root),
+            ],
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        assert_eq!(
+            commands[..4],
+            [
+                "mount /dev/sda2 /mnt",
+                "mount --mkdir /dev/sda1 /mnt/boot",
+                "mount --mkdir /dev/sda3 /mnt/home",
+                "swapon /dev/sda4",
+            ]
+        );
+        assert!(!commands.iter().any(|c| c.contains("<EFI partition>")));
+        assert!(!commands.iter().any(|c| c.starts_with("mkfs")));
+    }
+
+    #[test]
+    fn fstab_mounts_root_with_the_filesystem_options() {
+        let fstab = generate_fstab(&answers());
+        let lines: Vec<&str> = fstab.lines().collect();
+        assert!(lines[0].starts_with('#'));
+        assert_eq!(lines[1], "/dev/nvme0n1p2 / ext4 rw,relatime 0 1");
+        assert_eq!(lines[2], "/dev/nvme0n1p1 /boot vfat rw,relatime,fmask=0077,dmask=0077 0 2");
+        // zram never goes in the fstab.
+        assert_eq!(lines.len(), 3);
+
+        let xfs = InstallerState { filesystem: Filesystem::Xfs, ..answers() };
+        assert!(generate_fstab(&xfs)
+            .contains("/dev/nvme0n1p2 / xfs rw,relatime,attr2,inode64,noquota 0 1\n"));
+        let btrfs = InstallerState { filesystem: Filesystem::Btrfs, ..answers() };
+        assert!(generate_fstab(&btrfs)
+            .contains("/dev/nvme0n1p2 / btrfs rw,relatime,space_cache=v2 0 0\n"));
+
+        let encrypted = InstallerState {
+            encryption: Some(EncryptionConfig { passphrase: "correct horse".into() }),
+            firmware: Firmware::Bios,
+            ..answers()
+        };
+        assert_eq!(
+            generate_fstab(&encrypted).lines().skip(1).collect::<Vec<_>>(),
+            ["/dev/mapper/root / ext4 rw,relatime 0 1"]
+        );
+    }
+
+    #[test]
+    fn fstab_has_an_entry_per_btrfs_subvolume() {
+        let state = InstallerState {
+            filesystem: Filesystem::Btrfs,
+            btrfs_subvolumes: true,
+            swap: SwapConfig::File { size_gib: 4 },
+            ..answers()
+        };
+        let fstab = generate_fstab(&state);
+        let options = "rw,relatime,space_cache=v2";
+        for line in [
+            format!("/dev/nvme0n1p2 / btrfs {options},subvol=/@ 0 0"),
+            format!("/dev/nvme0n1p2 /home btrfs {options},subvol=/@home 0 0"),
+            format!("/dev/nvme0n1p2 /swap btrfs {options},subvol=/@swap 0 0"),
+            "/swap/swapfile none swap defaults 0 0".to_string(),
+        ] {
+            assert!(fstab.lines().any(|l| l == line), "{line} missing from\n{fstab}");
+        }
+    }
+
+    #[test]
+    fn fstab_names_existing_partitions_by_uuid() {
+        let with_uuid = |name: &str, fstype: &str, uuid: &str| Partition {
+            fstype: fstype.into(),
+            uuid: uuid.into(),
+            ..partition(name)
+        };
+        let state = InstallerState {
+            partition_scheme: PartitionScheme::Manual,
+            mounts: vec![
+                (with_uuid("sda3", "", ""), MountPoint::Swap),
+                (with_uuid("sda2", "ext4", "0f1e2d3c"), MountPoint::Root),
+                (with_uuid("sda1", "vfat", "1A2B-3C4D"), MountPoint::Boot),
+            ],
+            ..answers()
+        };
+        assert_eq!(
+            generate_fstab(&state).lines().skip(1).collect::<Vec<_>>(),
+            [
+                "UUID=0f1e2d3c / ext4 rw,relatime 0 1",
+                "UUID=1A2B-3C4D /boot vfat rw,relatime,fmask=0077,dmask=0077 0 2",
+                "/dev/sda3 none swap defaults 0 0",
+            ]
+        );
+
+        let swap = InstallerState { swap: SwapConfig::Partition { size_gib: 8 }, ..answers() };
+        assert!(generate_fstab(&swap).contains("/dev/nvme0n1p3 / ext4"));
+        assert!(generate_fstab(&swap).contains("/dev/nvme0n1p2 none swap defaults 0 0"));
+    }
+
+    #[test]
+    fn auto_layout_fits_the_disk() {
+        let root = |layout: &[LayoutPartition]| layout.last().unwrap().size_mib;
+
+        let big = auto_layout(Firmware::Uefi, SwapConfig::None, Some(500 * 1024));
+        let roles: Vec<_> = big.iter().map(|p| (p.number, p.role)).collect();
+        assert_eq!(roles, [(1, PartitionRole::Efi), (2, PartitionRole::Root)]);
+        assert_eq!(big[0].size_mib, Some(512));
+        assert_eq!(root(&big), Some(500 * 1024 - 512));
+        assert!(!root_too_small(&big));
+
+        let swap = SwapConfig::Partition { size_gib: 8 };
+        let medium = auto_layout(Firmware::Uefi, swap, Some(64 * 1024));
+        assert_eq!(medium[1].role, PartitionRole::Swap);
+        assert_eq!(medium[1].size_mib, Some(8 * 1024));
+        assert_eq!(root(&medium), Some(64 * 1024 - 512 - 8 * 1024));
+        assert!(!root_too_small(&medium));
+
+        let small = auto_layout(Firmware::Uefi, swap, Some(20 * 1024));
+        assert!(root_too_small(&small));
+        assert!(!root_too_small(&auto_layout(Firmware::Uefi, SwapConfig::None, Some(20 * 1024))));
+
+        let bios = auto_layout(Firmware::Bios, SwapConfig::Zram, Some(32 * 1024));
+        assert_eq!(bios[0].role, PartitionRole::BiosBoot);
+        assert_eq!(bios[0].size_mib, Some(1));
+        assert_eq!(root(&bios), Some(32 * 1024 - 1));
+
+        // Without the disk's size root is left to take whatever there is.
+        let unknown = auto_layout(Firmware::Uefi, swap, None);
+        assert_eq!(root(&unknown), None);
+        assert!(!root_too_small(&unknown));
+    }
+
+    #[test]
+    fn encryption_wraps_the_root_partition() {
+        let state = InstallerState {
+            bootloader: Bootloader::Grub,
+            encryption: Some(EncryptionConfig { passphrase: "correct horse".into() }),
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        let format = commands
+            .iter()
+            .position(|c| c == "cryptsetup luksFormat --batch-mode --key-file=- /dev/nvme0n1p2")
+            .unwrap();
+        let mkfs = commands.iter().position(|c| c == "mkfs.ext4 -F /dev/mapper/root").unwrap();
+        assert!(format < mkfs);
+        assert!(commands.contains(&"mount /dev/mapper/root /mnt".to_string()));
+        assert!(commands.contains(&"arch-chroot /mnt mkinitcpio -P".to_string()));
+        assert!(commands.iter().any(|c| c.contains("cryptdevice=UUID=")));
+        assert!(commands.iter().all(|c| !c.contains("correct horse")));
+
+        // systemd-boot's entry unlocks the partition and boots the mapping.
+        let state = InstallerState { bootloader: Bootloader::SystemdBoot, ..state };
+        let entry = build_commands(&state).pop().unwrap();
+        assert!(entry.contains(
+            "\"cryptdevice=UUID=$(blkid -s UUID -o value /dev/nvme0n1p2):root \
+             root=/dev/mapper/root rw\""
+        ));
+    }
+
+    #[test]
+    fn systemd_boot_gets_an_entry_for_the_new_root() {
+        let state = InstallerState {
+            filesystem: Filesystem::Btrfs,
+            btrfs_subvolumes: true,
+            ..answers()
+        };
+        let commands = build_commands(&state);
+        let bootctl = commands.iter().position(|c| c == "arch-chroot /mnt bootctl install");
+        let entry = &commands[bootctl.unwrap() + 2];
+        assert!(entry.starts_with("printf 'title   EndeavourOS\\nlinux   /vmlinuz-linux\\n"));
+        assert!(entry.contains(
+            "\"root=UUID=$(blkid -s UUID -o value /dev/nvme0n1p2) rootflags=subvol=@ rw\""
+        ));
+        assert!(entry.ends_with(" > /mnt/boot/loader/entries/endeavouros.conf"));
+
+        // A root partition picked by hand is the one booted.
+        let state = InstallerState {
+            partition_scheme: PartitionScheme::Manual,
+            mounts: vec![
+                (partition("sda2"), MountPoint::Root),
+                (partition("sda1"), MountPoint::Boot),
+            ],
+            ..answers()
+        };
+        let entry = build_commands(&state).pop().unwrap();
+        assert!(entry.contains("\"root=UUID=$(blkid -s UUID -o value /dev/sda2) rw\""));
+
+        // GRUB writes its own entries.
+        let grub = InstallerState { bootloader: Bootloader::Grub, ..answers() };
+        assert!(!build_commands(&grub).iter().any(|c| c.contains("/boot/loader")));
+    }
+
+    #[test]
+    fn partitions_are_named_after_the_disk() {
+        assert_eq!(partition_path("/dev/sda", 1), "/dev/sda1");
+        assert_eq!(partition_path("/dev/nvme0n1", 2), "/dev/nvme0n1p2");
+        assert_eq!(partition_path("/dev/mmcblk0", 1), "/dev/mmcblk0p1");
+    }
+
+    #[test]
+    fn dry_run_reports_every_command_then_finishes() {
+        let plan = plan(&answers());
+        let (tx, rx) = mpsc::channel();
+        dry_run_install(&tx, &plan, 0, &AtomicBool::new(false), Duration::ZERO);
+        drop(tx);
+        let events: Vec<InstallEvent> = rx.iter().collect();
+        let logged: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                InstallEvent::Log(line) => line.strip_prefix("[dry run] "),
+                _ => None,
+            })
+            .collect();
+        let commands = build_commands(&answers());
+        assert_eq!(logged, commands.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(events[events.len() - 2], InstallEvent::Progress(100));
+        assert_eq!(events.last(), Some(&InstallEvent::Done));
+    }
+
+    #[test]
+    fn installing_runs_the_plan_in_order() {
+        let state = InstallerState { root_password: Some("toor".into()), ..answers() };
+        let runner = MockRunner::new();
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan(&state), 0, &AtomicBool::new(false), &runner, ONCE);
+        drop(tx);
+        assert_eq!(rx.iter().last(), Some(InstallEvent::Done));
+
+        let calls = runner.calls();
+        let lines: Vec<String> = calls.iter().map(|(line, _)| line.clone()).collect();
+        let expected: Vec<String> = build_commands(&state)
+            .into_iter()
+            .map(|command| format!("sh -c {command}"))
+            .collect();
+        assert_eq!(lines, expected);
+        let fed: Vec<_> = calls.iter().filter(|(_, input)| input.is_some()).collect();
+        assert_eq!(fed.len(), 1);
+        assert!(fed[0].0.ends_with(" chpasswd"));
+        assert_eq!(fed[0].1.as_deref(), Some("jan:hunter2\nroot:toor\n"));
+    }
+
+    #[test]
+    fn command_output_is_forwarded_line_by_line() {
+        let plan = [
+            (InstallPhase::Format, "mkfs.ext4 -F /dev/sda2".to_string(), None),
+            (InstallPhase::Pacstrap, "pacstrap -K /mnt base".to_string(), None),
+        ];
+        let runner = MockRunner::new().print_on("pacstrap", "installing base\ninstalling linux\n");
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 0, &AtomicBool::new(false), &runner, ONCE);
+        drop(tx);
+        let events: Vec<InstallEvent> = rx.iter().collect();
+        let pacstrap = events.iter().position(|e| *e == InstallEvent::Log(plan[1].1.clone()));
+        assert_eq!(
+            events[pacstrap.unwrap() + 1..pacstrap.unwrap() + 3],
+            [
+                InstallEvent::Output("installing base".into()),
+                InstallEvent::Output("installing linux".into()),
+            ]
+        );
+        assert_eq!(events.iter().filter(|e| matches!(e, InstallEvent::Output(_))).count(), 2);
+    }
+
+    #[test]
+    fn failed_command_reports_its_status_and_stderr() {
+        let plan = [
+            (InstallPhase::Format, "cat".to_string(), Some(Input::Secret("fed in".into()))),
+            (InstallPhase::Pacstrap, "pacstrap -K /mnt base".to_string(), None),
+            (InstallPhase::Configure, "echo never".to_string(), None),
+        ];
+        let runner = MockRunner::new().fail_on("pacstrap", 3, "one\ntwo\n\n");
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 0, &AtomicBool::new(false), &runner, ONCE);
+        drop(tx);
+        let events: Vec<InstallEvent> = rx.iter().collect();
+        assert_eq!(events[1], InstallEvent::Progress(33));
+        let Some(InstallEvent::CommandFailed(failure)) = events.last() else {
+            panic!("no failure in {events:?}");
+        };
+        assert_eq!(failure.index, 1);
+        assert_eq!(failure.phase, InstallPhase::Pacstrap);
+        assert_eq!(failure.code, Some(3));
+        assert_eq!(failure.stderr, ["one", "two"]);
+        assert!(failure.to_string().ends_with("exited with status 3"));
+        assert!(!events.contains(&InstallEvent::Log("echo never".into())));
+
+        assert_eq!(runner.calls().len(), 2);
+
+        // A retry starts at the failed command.
+        let runner = MockRunner::new();
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 1, &AtomicBool::new(false), &runner, ONCE);
+        drop(tx);
+        assert_eq!(rx.iter().last(), Some(InstallEvent::Done));
+        let calls = runner.calls();
+        assert_eq!(calls[0].0, "sh -c pacstrap -K /mnt base");
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[test]
+    fn failing_downloads_are_retried_before_giving_up() {
+        let plan = [
+            (InstallPhase::Format, "mkfs.ext4 -F /dev/sda2".to_string(), None),
+            (InstallPhase::Pacstrap, "pacstrap -K /mnt base".to_string(), None),
+        ];
+        let backoff = Backoff { attempts: 3, base_delay: Duration::ZERO };
+        let runner = MockRunner::new().fail_on("pacstrap", 1, "mirror timed out\n");
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 0, &AtomicBool::new(false), &runner, backoff);
+        drop(tx);
+        let events: Vec<InstallEvent> = rx.iter().collect();
+        assert!(events.contains(&InstallEvent::Log(format!("Retrying (2/3)… {}", plan[1].1))));
+        assert!(events.contains(&InstallEvent::Log(format!("Retrying (3/3)… {}", plan[1].1))));
+        assert!(matches!(events.last(), Some(InstallEvent::CommandFailed(_))));
+        assert_eq!(runner.calls().len(), 4);
+
+        // Only downloads are worth trying again.
+        let runner = MockRunner::new().fail_on("mkfs", 1, "no such device\n");
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 0, &AtomicBool::new(false), &runner, backoff);
+        drop(tx);
+        let retried = |e: &InstallEvent| matches!(e, InstallEvent::Log(l) if l.contains("Retry"));
+        assert!(!rx.iter().any(|e| retried(&e)));
+        assert_eq!(runner.calls().len(), 1);
+    }
+
+    /// Cancels the installation when asked to run a command containing
+    /// `pattern`, as if the user confirmed just then.
+    struct CancelOn<'a> {
+        pattern: &'a str,
+        cancel: &'a AtomicBool,
+        runner: MockRunner,
+    }
+
+    impl CommandRunner for CancelOn<'_> {
+        fn run(&self, program: &str, args: &[&str], input: Option<&str>) -> io::Result<Output> {
+            if args.iter().any(|arg| arg.contains(self.pattern)) {
+                self.cancel.store(true, Ordering::SeqCst);
+            }
+            self.runner.run(program, args, input)
+        }
+    }
+
+    #[test]
+    fn cancelling_stops_before_the_next_phase() {
+        let plan = plan(&answers());
+        let cancel = AtomicBool::new(false);
+        let runner = CancelOn { pattern: "mkfs", cancel: &cancel, runner: MockRunner::new() };
+        let (tx, rx) = mpsc::channel();
+        run_install(&tx, &plan, 0, &cancel, &runner, ONCE);
+        drop(tx);
+        let events: Vec<InstallEvent> = rx.iter().collect();
+        assert_eq!(
+            events.last(),
+            Some(&InstallEvent::Cancelled(vec![InstallPhase::Partition, InstallPhase::Format]))
+        );
+        // The formatting that had begun is finished; nothing after it runs.
+        let started = plan
+            .iter()
+            .filter(|(phase, ..)| matches!(phase, InstallPhase::Partition | InstallPhase::Format))
+            .count();
+        assert_eq!(runner.runner.calls().len(), started);
+        assert!(runner.runner.calls().iter().all(|(line, _)| !line.contains("pacstrap")));
+
+        // Cancelled before it starts, a dry run reports that nothing was done.
+        let (tx, rx) = mpsc::channel();
+        dry_run_install(&tx, &plan, 0, &AtomicBool::new(true), Duration::ZERO);
+        drop(tx);
+        assert_eq!(rx.iter().collect::<Vec<_>>(), [InstallEvent::Cancelled(Vec::new())]);
+    }
+
+    #[test]
+    fn secrets_go_in_on_stdin() {
+        let state = InstallerState {
+            root_password: Some("toor".into()),
+            encryption: Some(EncryptionConfig { passphrase: "correct horse".into() }),
+            mirrorlist: Some("Server = https://mirror.example/$repo\n".into()),
+            ..answers()
+        };
+        let plan = plan(&state);
+        let inputs: Vec<(&str, &Input)> = plan
+            .iter()
+            .filter_map(|(_, command, input)| Some((command.as_str(), input.as_ref()?)))
+            .collect();
+        let passphrase = Input::Secret("correct horse".into());
+        assert_eq!(
+            inputs,
+            [
+                ("cryptsetup luksFormat --batch-mode --key-file=- /dev/nvme0n1p2", &passphrase),
+                ("cryptsetup open --key-file=- /dev/nvme0n1p2 root", &passphrase),
+                (
+                    "tee /mnt/etc/pacman.d/mirrorlist > /dev/null",
+                    &Input::Text("Server = https://mirror.example/$repo\n".into())
+                ),
+                ("arch-chroot /mnt chpasswd", &Input::Secret("jan:hunter2\nroot:toor\n".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn placeholders_are_known_without_reading_the_commands() {
+        let partitions = |mounts: &[(&str, MountPoint)]| {
+            mounts.iter().map(|(name, mount)| (partition(name), *mount)).collect()
+        };
+        let root = [("sda2", MountPoint::Root)];
+        let root_and_boot = [("sda2", MountPoint::Root), ("sda1", MountPoint::Boot)];
+        for (scheme, firmware, mounts) in [
+            (PartitionScheme::Erase, Firmware::Uefi, &[][..]),
+            (PartitionScheme::Alongside, Firmware::Uefi, &[]),
+            (PartitionScheme::Manual, Firmware::Uefi, &[]),
+            (PartitionScheme::Manual, Firmware::Uefi, &root),
+            (PartitionScheme::Manual, Firmware::Bios, &root),
+            (PartitionScheme::Manual, Firmware::Uefi, &root_and_boot),
+        ] {
+            let state = InstallerState {
+                partition_scheme: scheme,
+                firmware,
+                mounts: partitions(mounts),
+                full_name: None,
+                ..answers()
+            };
+            let placeholders = build_commands(&state).iter().any(|c| c.contains("'<"));
+            let missing = devices_missing(&state);
+            assert_eq!(missing.is_some(), placeholders, "{scheme:?} {firmware:?} {mounts:?}");
+        }
+
+        // Answers can have angle brackets in them too.
+        let state = InstallerState { full_name: Some("Ann <ann@example.org>".into()), ..answers() };
+        assert!(build_commands(&state).iter().any(|c| c.contains('<')));
+        assert_eq!(devices_missing(&state), None);
+    }
+
+    #[test]
+    fn install_without_a_disk_fails() {
+        let rx = spawn_install(&InstallerState::default(), true, 0, Arc::default(), ONCE);
+        assert!(matches!(rx.recv(), Ok(InstallEvent::Failed(_))));
+        assert!(rx.recv().is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! Logging to a file. The TUI owns the terminal, so nothing may be written
+//! to stdout or stderr while it runs; the log file is the record of what
+//! happened during an installation.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Where the installer writes its log unless `--log-file` says otherwise.
+pub const LOG_PATH: &str = "/var/log/artemis-install.log";
+
+/// Size past which the log is moved aside to `<path>.1` on startup, so
+/// repeated runs keep one previous log without growing forever.
+const ROTATE_BYTES: u64 = 1024 * 1024;
+
+/// A logger that appends one line per record to a file.
+pub struct FileLogger {
+    file: Mutex<File>,
+    level: LevelFilter,
+}
+
+impl FileLogger {
+    /// Open `path` for appending, creating it if needed, after rotating it
+    /// if it has grown too big.
+    pub fn new(path: &Path, level: LevelFilter) -> io::Result<Self> {
+        rotate(path, ROTATE_BYTES)?;
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+            level,
+        })
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let line = format!(
+            "{}.{:03} {:<5} {}: {}\n",
+            elapsed.as_secs(),
+            elapsed.subsec_millis(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if let Ok(mut file) = self.file.lock() {
+            // There is nowhere left to report a failing log write.
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Move the log at `path` to `<path>.1`, replacing any older one, if it is
+/// bigger than `limit` bytes.
+fn rotate(path: &Path, limit: u64) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.len() > limit => {
+            let mut rotated = path.as_os_str().to_owned();
+            rotated.push(".1");
+            fs::rename(path, rotated)
+        }
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Send everything logged at `level` or above to the file at `path`.
+pub fn init(path: &Path, level: LevelFilter) -> Result<(), Box<dyn std::error::Error>> {
+    log::set_boxed_logger(Box::new(FileLogger::new(path, level)?))?;
+    log::set_max_level(level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn temp_log(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("artemis-{name}-{}.log", std::process::id()))
+    }
+
+    #[test]
+    fn global_logger_writes_to_the_file() {
+        let path = temp_log("init");
+        init(&path, LevelFilter::Info).unwrap();
+        log::info!("known message");
+        log::logger().flush();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(written.lines().any(|line| line.ends_with("INFO  artemis::logging::tests: known message")));
+    }
+
+    #[test]
+    fn big_logs_are_moved_aside() {
+        let path = temp_log("rotate");
+        let rotated = path.with_extension("log.1");
+        std::fs::write(&path, "0123456789").unwrap();
+        rotate(&path, 100).unwrap();
+        assert!(path.exists());
+        rotate(&path, 5).unwrap();
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap(), "0123456789");
+        std::fs::remove_file(&rotated).unwrap();
+        rotate(&path, 5).unwrap();
+    }
+
+    #[test]
+    fn records_below_the_level_are_dropped() {
+        let path = temp_log("level");
+        let logger = FileLogger::new(&path, LevelFilter::Warn).unwrap();
+        for (level, text) in [(Level::Info, "chatter"), (Level::Error, "failure")] {
+            logger.log(&Record::builder().level(level).args(format_args!("{text}")).build());
+        }
+        logger.flush();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!written.contains("chatter"));
+        assert!(written.contains("ERROR"));
+        assert!(written.contains("failure"));
+    }
+}
@@ -1,169 +1,243 @@
 use crossterm::{
-    event::{self, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode},
-    cursor,
 };
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
-    Terminal,
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
 };
-use std::{io, thread, time::Duration};
+
+mod app;
+mod choices;
+mod cli;
+mod config;
+mod filter;
+mod install;
+mod logging;
+mod retry;
+mod runner;
+mod secret;
+mod system;
+mod theme;
+mod ui;
+mod validate;
+mod widgets;
+
+use app::{AppState, FinishAction, Step};
+use choices::{Bootloader, Firmware};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = cli::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("artemis: {err}\n{}", cli::USAGE);
+        std::process::exit(2);
+    });
+    if args.help {
+        println!("{}\n\n{}", cli::USAGE, cli::HELP);
+        return Ok(());
+    }
+    let log_file = args.log_file.clone().unwrap_or_else(|| PathBuf::from(logging::LOG_PATH));
+    if let Err(err) = logging::init(&log_file, args.log_level) {
+        eprintln!("artemis: not logging to {}: {err}", log_file.display());
+    }
+    log::info!("artemis {} starting", env!("CARGO_PKG_VERSION"));
+
+    // Read the answers before taking over the terminal, so problems are
+    // reported on a normal console.
+    let answers = args.config.map(|path| {
+        config::load_config(&path).unwrap_or_else(|err| {
+            log::error!("cannot use {}: {err}", path.display());
+            eprintln!("artemis: cannot use {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    // The boot loader and the partitions it needs depend on how this
+    // machine was booted.
+    let firmware = if system::firmware::is_uefi(Path::new(system::firmware::EFIVARS)) {
+        Firmware::Uefi
+    } else {
+        Firmware::Bios
+    };
+    log::info!("firmware: {firmware:?}");
+
+    install_panic_hook();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
+    execute!(stdout, crossterm::terminal::EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut step = 0;
-    let mut spinner = 0;
+    let mut app = AppState::new();
+    // --accessible is monochrome unless a theme is named, but always plain.
+    let theme = args
+        .theme
+        .or(args.accessible.then_some(theme::Theme::MONOCHROME))
+        .or_else(theme::Theme::from_env)
+        .unwrap_or_default();
+    app.accessible = args.accessible || args.no_emoji;
+    app.animate = !args.accessible;
+    if let Some(max) = args.max_attempts {
+        app.max_install_attempts = max;
+    }
+    if let Some(retries) = args.retries {
+        app.backoff.attempts = retries;
+    }
+    if let Some(seconds) = args.retry_delay {
+        app.backoff.base_delay = Duration::from_secs(seconds);
+    }
+    app.set_theme(theme);
+    app.answers_path = Some(PathBuf::from(config::ANSWERS_PATH));
+    app.export_path = Some(config::json_path());
+    app.load_keymaps(system::keymaps::detect_keymaps());
+    app.load_locales(system::locales::list_locales(&[
+        Path::new(system::locales::LOCALE_GEN),
+        Path::new(system::locales::SUPPORTED),
+    ]));
+    app.load_disks(system::disks::detect_disks());
+    app.wired = system::wifi::wired_connected();
+    if !app.wired {
+        app.load_wifi(system::wifi::scan());
+    }
+    app.existing_users = system::users::existing_users(Path::new(system::users::PASSWD));
+    app.load_memory(system::memory::total_memory(Path::new(system::memory::MEMINFO)));
+    app.load_hardware(
+        system::hardware::cpu_model(Path::new(system::hardware::CPUINFO)),
+        system::hardware::gpus(system::hardware::LSPCI),
+    );
+    app.load_mirrors(system::mirrors::read_mirrorlist(Path::new(
+        system::mirrors::MIRRORLIST,
+    )));
+    app.load_timezones(system::timezones::list_timezones(Path::new(
+        system::timezones::ZONEINFO,
+    )));
+    // Start from the last run's answers; a missing or broken file just
+    // means starting fresh.
+    let prefill = args.prefill.unwrap_or_else(config::json_path);
+    match config::Config::load_from(&prefill) {
+        Ok(saved) => {
+            log::info!("prefilling answers from {}", prefill.display());
+            app.prefill(&saved);
+        }
+        Err(err) => log::info!("not prefilling from {}: {err}", prefill.display()),
+    }
+    app.installer.firmware = firmware;
+    if let Some(mut answers) = answers {
+        answers.firmware = firmware;
+        if !Bootloader::available(firmware).contains(&answers.bootloader) {
+            log::warn!("{} needs UEFI; installing GRUB instead", answers.bootloader.label());
+            answers.bootloader = Bootloader::Grub;
+        }
+        app.installer = answers;
+        app.step = Step::Installing;
+    }
+    let check_host = args
+        .check_host
+        .clone()
+        .unwrap_or_else(|| system::network::CHECK_HOST.to_string());
+    let mut last_tick = Instant::now();
 
     loop {
-        terminal.draw(|f| {
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Percentage(10),
-                    Constraint::Percentage(75),
-                    Constraint::Percentage(15),
-                ])
-                .split(f.area());
-
-            let header = Paragraph::new(center_text(
-                "🚀 EndeavourOS Installer",
-                f.size().width as usize,
-                Style::default().fg(Color::LightCyan).add_modifier(Modifier::BOLD),
-            ))
-            .block(
-                Block::default()
-                    .borders(Borders::BOTTOM)
-                    .border_style(Style::default().fg(Color::White)),
-            );
-            f.render_widget(header, chunks[0]);
-
-            let content = match step {
-                0 => welcome_screen(f.size().width as usize),
-                1 => language_selection_screen(f.size().width as usize),
-                _ => completion_screen(f.size().width as usize),
-            };
-            f.render_widget(content, chunks[1]);
-
-            let footer = Paragraph::new(center_text(
-                &spinner_animation(spinner),
-                f.size().width as usize,
-                Style::default().fg(Color::Gray),
-            ))
-            .block(Block::default().borders(Borders::TOP).border_style(Style::default().fg(Color::White)));
-            f.render_widget(footer, chunks[2]);
-        })?;
+        ui::redraw(&mut terminal, &mut app)?;
 
-        if let Event::Key(key) = event::read()? {
-            match key.code {
-                KeyCode::Char('q') => break,
-                KeyCode::Enter => {
-                    step = (step + 1) % 3;
+        // Wait for input no longer than it takes the spinner to turn.
+        let tick_rate = app.spinner.interval();
+        let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) => app.on_key_event(key),
+                Event::Mouse(mouse) => app.on_mouse(mouse),
+                Event::Resize(width, height) => {
+                    // Redraw straight away rather than waiting for the tick;
+                    // the old frame is laid out for the old size.
+                    app.on_resize(width, height);
+                    ui::redraw(&mut terminal, &mut app)?;
                 }
                 _ => {}
             }
         }
-
-        spinner = (spinner + 1) % 4;
-        thread::sleep(Duration::from_millis(200));
+        if last_tick.elapsed() >= tick_rate {
+            app.on_tick();
+            last_tick = Instant::now();
+        }
+        if let Some(keymap) = app.take_keymap_request() {
+            if args.dry_run {
+                log::info!("dry run, not running: loadkeys {keymap}");
+            } else if let Err(err) = system::keymaps::load_keymap(&keymap) {
+                log::warn!("loadkeys {keymap}: {err}");
+            }
+        }
+        if let Some((ssid, passphrase)) = app.take_wifi_request() {
+            app.attach_wifi_connect(system::wifi::spawn_connect(ssid, passphrase));
+        }
+        if app.needs_mirror_ranking() {
+            let countries = app.mirror_countries().into_iter().map(String::from).collect();
+            app.attach_mirror_ranking(system::mirrors::spawn_rank(countries));
+        }
+        if let Some(disk) = app.needs_partitions() {
+            let partitions = system::disks::list_partitions(&disk);
+            app.load_partitions(disk, partitions);
+        }
+        if app.needs_network_check() {
+            let rx = system::network::spawn_check(check_host.clone(), app.backoff);
+            app.attach_network_check(rx);
+        }
+        if app.needs_time_sync() {
+            if args.dry_run {
+                log::info!("dry run, not running: timedatectl set-ntp true");
+                app.skip_time_sync();
+            } else {
+                app.attach_time_sync(system::clock::spawn_sync());
+            }
+        }
+        if app.needs_install_worker() {
+            let (from, cancel) = (app.install_from, app.install_cancel.clone());
+            let (installer, backoff) = (&app.installer, app.backoff);
+            let rx = install::spawn_install(installer, args.dry_run, from, cancel, backoff);
+            app.attach_install(rx);
+        }
+        if app.should_quit {
+            break;
+        }
     }
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), crossterm::terminal::LeaveAlternateScreen)?;
+    restore_terminal()?;
+    if args.dry_run {
+        println!("Dry run; these commands would have run:");
+        for command in install::build_commands(&app.installer) {
+            println!("  {command}");
+        }
+    }
+    if let Some([program, arg]) = app.finish.and_then(FinishAction::command) {
+        if args.dry_run {
+            log::info!("dry run, not running: {program} {arg}");
+            println!("Dry run; not running `{program} {arg}`");
+        } else {
+            log::info!("running {program} {arg}");
+            let status = Command::new(program).arg(arg).status()?;
+            if !status.success() {
+                log::warn!("{program} {arg} exited with {status}");
+            }
+        }
+    }
     Ok(())
 }
 
-fn center_text<'a>(text: &str, width: usize, style: Style) -> Vec<Line<'a>> {
-    let padding = (width.saturating_sub(text.len())) / 2;
-    vec![Line::from(Span::styled(
-        format!("{:width$}{}", "", text, width = padding),
-        style,
-    ))]
-}
-
-fn spinner_animation(frame: usize) -> String {
-    let spinner_frames = ["|", "/", "-", "\\"];
-    spinner_frames[frame].to_string()
-}
-
-fn welcome_screen(width: usize) -> Paragraph<'static> {
-    Paragraph::new(vec![
-        center_line("Welcome to EndeavourOS!", width, Color::Magenta, Modifier::BOLD),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line(
-            "This installer will guide you through the installation process.",
-            width,
-            Color::Gray,
-            Modifier::empty(),
-        ),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line(
-            "Press 'Enter' to proceed to the next step.",
-            width,
-            Color::LightGreen,
-            Modifier::empty(),
-        ),
-    ])
-    .block(Block::default().borders(Borders::ALL).title("🌟 Welcome"))
-}
-
-fn language_selection_screen(width: usize) -> Paragraph<'static> {
-    Paragraph::new(vec![
-        center_line("Select your language:", width, Color::Cyan, Modifier::BOLD),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line("→ English", width, Color::LightGreen, Modifier::empty()),
-        center_line("  Français", width, Color::Gray, Modifier::empty()),
-        center_line("  Español", width, Color::Gray, Modifier::empty()),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line(
-            "Use arrow keys to navigate and 'Enter' to select.",
-            width,
-            Color::Gray,
-            Modifier::empty(),
-        ),
-    ])
-    .block(Block::default().borders(Borders::ALL).title("🌐 Language Selection"))
-}
-
-fn completion_screen(width: usize) -> Paragraph<'static> {
-    Paragraph::new(vec![
-        center_line(
-            "Installation Complete! 🎉",
-            width,
-            Color::LightGreen,
-            Modifier::BOLD,
-        ),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line(
-            "You can now restart your system and enjoy EndeavourOS.",
-            width,
-            Color::Gray,
-            Modifier::empty(),
-        ),
-        center_line("", width, Color::Reset, Modifier::empty()),
-        center_line("Press 'Q' to exit.", width, Color::LightCyan, Modifier::empty()),
-    ])
-    .block(Block::default().borders(Borders::ALL).title("✅ Completion"))
+/// Leave raw mode and the alternate screen, handing the terminal back to the shell.
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), DisableMouseCapture, crossterm::terminal::LeaveAlternateScreen)
 }
 
-fn center_line<'a>(
-    text: &str,
-    width: usize,
-    fg: Color,
-    modifier: Modifier,
-) -> Line<'a> {
-    let padding = (width.saturating_sub(text.len())) / 2;
-    Line::from(Span::styled(
-        format!("{:width$}{}", "", text, width = padding),
-        Style::default().fg(fg).add_modifier(modifier),
-    ))
+/// Restore the terminal before a panic message is printed, so a crash does
+/// not leave the user's shell in raw mode on the alternate screen.
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        previous(info);
+    }));
 }
@@ -0,0 +1,138 @@
+//! Trying network operations again, waiting longer each time, since a
+//! mirror timing out is often over by the next try.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a wait sleeps before looking at the cancel flag again.
+const CANCEL_CHECK: Duration = Duration::from_millis(100);
+
+/// How often to try and how long to wait before the first retry; every
+/// wait after that is twice the one before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    pub attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self { attempts: 3, base_delay: Duration::from_secs(2) }
+    }
+}
+
+impl Backoff {
+    /// The pause before attempt `attempt`, counting from 1: none before
+    /// the first, then the base delay, doubling from there.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        match attempt {
+            0 | 1 => Duration::ZERO,
+            _ => self.base_delay.saturating_mul(1 << (attempt - 2).min(16)),
+        }
+    }
+}
+
+/// Run `op` up to `attempts` times until it succeeds, pausing as
+/// [`Backoff::delay`] says between tries. `op` is given the number of the
+/// attempt, from 1, so it can report retrying. Once every attempt has
+/// failed, or `cancel` is set while waiting, the last error is returned.
+pub fn retry<T, E: fmt::Display>(
+    attempts: u32,
+    base_delay: Duration,
+    cancel: &AtomicBool,
+    mut op: impl FnMut(u32) -> Result<T, E>,
+) -> Result<T, E> {
+    let backoff = Backoff { attempts: attempts.max(1), base_delay };
+    let mut attempt = 1;
+    loop {
+        match op(attempt) {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= backoff.attempts => return Err(err),
+            Err(err) => {
+                attempt += 1;
+                let delay = backoff.delay(attempt);
+                log::warn!(
+                    "{err}; retrying ({attempt}/{}) in {} s",
+                    backoff.attempts,
+                    delay.as_secs_f32()
+                );
+                if !wait(delay, cancel) {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// Sleep for `delay` a slice at a time, stopping early if `cancel` is set.
+/// Whether the whole delay passed uncancelled.
+fn wait(delay: Duration, cancel: &AtomicBool) -> bool {
+    let until = Instant::now() + delay;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return false;
+        }
+        let left = until.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return true;
+        }
+        thread::sleep(left.min(CANCEL_CHECK));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn succeeding_on_the_third_try_is_ok() {
+        let mut tried = Vec::new();
+        let result = retry(5, Duration::ZERO, &AtomicBool::new(false), |attempt| {
+            tried.push(attempt);
+            if attempt < 3 {
+                Err("mirror timed out")
+            } else {
+                Ok(attempt)
+            }
+        });
+        assert_eq!(result, Ok(3));
+        assert_eq!(tried, [1, 2, 3]);
+    }
+
+    #[test]
+    fn running_out_of_attempts_returns_the_last_error() {
+        let never = AtomicBool::new(false);
+        let result: Result<(), String> =
+            retry(3, Duration::ZERO, &never, |attempt| Err(format!("failure {attempt}")));
+        assert_eq!(result, Err("failure 3".to_string()));
+        // Asking for no attempts still makes one.
+        assert_eq!(retry(0, Duration::ZERO, &never, Err::<(), _>), Err(1));
+    }
+
+    #[test]
+    fn cancelling_cuts_the_wait_short() {
+        let cancel = AtomicBool::new(false);
+        let started = Instant::now();
+        let result = thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                cancel.store(true, Ordering::Relaxed);
+            });
+            retry(3, Duration::from_secs(60), &cancel, Err::<(), _>)
+        });
+        // No further attempt is made once cancelled.
+        assert_eq!(result, Err(1));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn delays_double_after_the_first_retry() {
+        let backoff = Backoff { attempts: 5, base_delay: Duration::from_secs(2) };
+        let delays: Vec<u64> = (1..=5).map(|attempt| backoff.delay(attempt).as_secs()).collect();
+        assert_eq!(delays, [0, 2, 4, 8, 16]);
+    }
+}
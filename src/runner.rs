@@ -0,0 +1,264 @@
+//! Running external programs, behind a trait so the installation can be
+//! tested without touching the machine.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    process::{Child, Command, Output, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+use crate::secret;
+
+/// Something that runs a program to completion and hands back what it
+/// printed and how it exited.
+pub trait CommandRunner {
+    /// Run `program` with `args`, writing `input` to its stdin if given.
+    /// Only failing to start it is an error; a non-zero exit is in the
+    /// returned status.
+    fn run(&self, program: &str, args: &[&str], input: Option<&str>) -> io::Result<Output>;
+
+    /// Like [`CommandRunner::run`], but also hands each line the program
+    /// prints, on stdout or stderr, to `on_line`. Unless overridden, the
+    /// lines come once it has finished.
+    fn run_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: Option<&str>,
+        on_line: &mut dyn FnMut(&str),
+    ) -> io::Result<Output> {
+        let output = self.run(program, args, input)?;
+        for stream in [&output.stdout, &output.stderr] {
+            String::from_utf8_lossy(stream).lines().for_each(&mut *on_line);
+        }
+        Ok(output)
+    }
+}
+
+/// Runs programs for real with [`Command`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealRunner;
+
+impl CommandRunner for RealRunner {
+    fn run(&self, program: &str, args: &[&str], input: Option<&str>) -> io::Result<Output> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let feeder = feed_stdin(&mut child, input);
+        let output = child.wait_with_output()?;
+        finish_feeding(feeder)?;
+        Ok(output)
+    }
+
+    /// Reads stdout and stderr on a thread each, so the lines arrive as
+    /// they are printed whichever stream they are on.
+    fn run_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: Option<&str>,
+        on_line: &mut dyn FnMut(&str),
+    ) -> io::Result<Output> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let feeder = feed_stdin(&mut child, input);
+        let (tx, rx) = mpsc::channel();
+        let readers = [
+            child.stdout.take().map(|out| forward_lines(out, false, tx.clone())),
+            child.stderr.take().map(|err| forward_lines(err, true, tx)),
+        ];
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        // Ends once both streams are closed and their senders dropped.
+        for (is_stderr, line) in rx {
+            on_line(&line);
+            let buffer = if is_stderr { &mut stderr } else { &mut stdout };
+            buffer.extend_from_slice(line.as_bytes());
+            buffer.push(b'\n');
+        }
+        for reader in readers.into_iter().flatten() {
+            let _ = reader.join();
+        }
+        let status = child.wait()?;
+        finish_feeding(feeder)?;
+        Ok(Output { status, stdout, stderr })
+    }
+}
+
+/// The thread writing a program's stdin.
+type Feeder = thread::JoinHandle<io::Result<()>>;
+
+/// Write `input` to `child`'s stdin on a thread of its own, closing it
+/// after. Writing it all first could deadlock: a program echoing its input,
+/// like `tee`, stops reading once nobody empties its stdout.
+fn feed_stdin(child: &mut Child, input: Option<&str>) -> Option<Feeder> {
+    let (mut stdin, mut input) = (child.stdin.take()?, input?.to_string());
+    Some(thread::spawn(move || {
+        let written = stdin.write_all(input.as_bytes());
+        // Passwords come this way.
+        secret::zeroize(&mut input);
+        written
+    }))
+}
+
+/// Wait for [`feed_stdin`]'s thread. A program that exits without reading
+/// all of its input is not an error here; its exit status tells.
+fn finish_feeding(feeder: Option<Feeder>) -> io::Result<()> {
+    match feeder.map(|feeder| feeder.join()) {
+        Some(Ok(Err(err))) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+        Some(Err(_)) => Err(io::Error::other("writing to stdin panicked")),
+        _ => Ok(()),
+    }
+}
+
+/// Send each line read from `stream` down `tx`, marked with whether it is
+/// stderr, until the stream closes.
+fn forward_lines(
+    stream: impl Read + Send + 'static,
+    is_stderr: bool,
+    tx: mpsc::Sender<(bool, String)>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { return };
+            if tx.send((is_stderr, line)).is_err() {
+                return;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+pub use mock::MockRunner;
+
+#[cfg(test)]
+mod mock {
+    use std::{
+        cell::RefCell,
+        io,
+        os::unix::process::ExitStatusExt,
+        process::{ExitStatus, Output},
+    };
+
+    use super::CommandRunner;
+
+    /// Records what it is asked to run and answers with canned results:
+    /// success, unless the command line contains a pattern given to
+    /// [`MockRunner::fail_on`].
+    #[derive(Debug, Default)]
+    pub struct MockRunner {
+        calls: RefCell<Vec<(String, Option<String>)>>,
+        failures: Vec<(String, i32, String)>,
+        printed: Vec<(String, String)>,
+    }
+
+    impl MockRunner {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Make commands containing `pattern` exit with `code`, printing
+        /// `stderr`.
+        pub fn fail_on(mut self, pattern: &str, code: i32, stderr: &str) -> Self {
+            self.failures.push((pattern.to_string(), code, stderr.to_string()));
+            self
+        }
+
+        /// Make commands containing `pattern` print `stdout`.
+        pub fn print_on(mut self, pattern: &str, stdout: &str) -> Self {
+            self.printed.push((pattern.to_string(), stdout.to_string()));
+            self
+        }
+
+        /// Every command line run so far, with what went to its stdin.
+        pub fn calls(&self) -> Vec<(String, Option<String>)> {
+            self.calls.borrow().clone()
+        }
+    }
+
+    impl CommandRunner for MockRunner {
+        fn run(&self, program: &str, args: &[&str], input: Option<&str>) -> io::Result<Output> {
+            let line = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>();
+            let line = line.join(" ");
+            self.calls.borrow_mut().push((line.clone(), input.map(String::from)));
+            let failure = self.failures.iter().find(|(pattern, ..)| line.contains(pattern));
+            let (code, stderr) = failure.map_or((0, ""), |(_, code, stderr)| (*code, stderr));
+            let printed = self.printed.iter().find(|(pattern, _)| line.contains(pattern));
+            Ok(Output {
+                // Wait statuses keep the exit code in the second byte.
+                status: ExitStatus::from_raw(code << 8),
+                stdout: printed.map_or(Vec::new(), |(_, stdout)| stdout.as_bytes().to_vec()),
+                stderr: stderr.as_bytes().to_vec(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_runner_feeds_stdin_and_reports_the_exit() {
+        let output = RealRunner.run("sh", &["-c", "cat; echo oops >&2; exit 4"], Some("hi")).unwrap();
+        assert_eq!(output.stdout, b"hi");
+        assert_eq!(output.stderr, b"oops\n");
+        assert_eq!(output.status.code(), Some(4));
+        assert!(RealRunner.run("artemis-no-such-program", &[], None).is_err());
+    }
+
+    #[test]
+    fn input_larger_than_a_pipe_is_echoed_back() {
+        // Well past the 64 KiB a pipe holds, so writing it all before
+        // reading would never finish.
+        let input = "Server = https://mirror.example/$repo/os/$arch\n".repeat(10_000);
+        let output = RealRunner.run("cat", &[], Some(&input)).unwrap();
+        assert_eq!(output.stdout.len(), input.len());
+        let mut lines = 0;
+        let mut count = |_: &str| lines += 1;
+        let output = RealRunner.run_streaming("cat", &[], Some(&input), &mut count).unwrap();
+        assert_eq!(lines, 10_000);
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn streaming_hands_over_every_line_as_printed() {
+        let mut lines = Vec::new();
+        let script = "cat; echo two; echo oops >&2; exit 3";
+        let output = RealRunner
+            .run_streaming("sh", &["-c", script], Some("one\n"), &mut |line| {
+                lines.push(line.to_string())
+            })
+            .unwrap();
+        // Which stream comes first is up to the threads reading them.
+        lines.sort();
+        assert_eq!(lines, ["one", "oops", "two"]);
+        assert_eq!(output.stdout, b"one\ntwo\n");
+        assert_eq!(output.stderr, b"oops\n");
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[test]
+    fn mock_runner_records_calls_and_fails_on_request() {
+        let runner = MockRunner::new().fail_on("pacstrap", 1, "no mirror\n");
+        let ok = runner.run("genfstab", &["-U", "/mnt"], None).unwrap();
+        assert!(ok.status.success());
+        let failed = runner.run("pacstrap", &["-K", "/mnt", "base"], Some("x")).unwrap();
+        assert_eq!(failed.status.code(), Some(1));
+        assert_eq!(failed.stderr, b"no mirror\n");
+        assert_eq!(
+            runner.calls(),
+            [
+                ("genfstab -U /mnt".to_string(), None),
+                ("pacstrap -K /mnt base".to_string(), Some("x".to_string())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Passwords and passphrases kept in memory: overwritten with zeros once
+//! they are no longer needed, and left out of debug output.
+
+use std::{
+    fmt,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+/// A password or passphrase, zeroed when dropped. Its value is only
+/// reached through [`Secret::expose`], so it never ends up in logs by way
+/// of `{:?}`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// The secret itself, for the command that needs it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<hidden>)")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        zeroize(&mut self.0);
+    }
+}
+
+/// Overwrite the whole of `value`'s buffer with zeros, spare capacity
+/// included, and leave it empty.
+pub fn zeroize(value: &mut String) {
+    wipe(&mut std::mem::take(value).into_bytes());
+}
+
+/// Overwrite all `bytes.capacity()` bytes of `bytes`' buffer with zeros.
+fn wipe(bytes: &mut Vec<u8>) {
+    let start = bytes.as_mut_ptr();
+    for offset in 0..bytes.capacity() {
+        // SAFETY: every offset below the capacity is inside the allocation,
+        // which `bytes` owns. Volatile writes are not optimized away even
+        // though the buffer is usually freed right after.
+        unsafe { start.add(offset).write_volatile(0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_stay_out_of_debug_output() {
+        let secret = Secret::from("hunter2");
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(format!("{:?}", Some(&secret)), "Some(Secret(<hidden>))");
+    }
+
+    #[test]
+    fn wiping_clears_the_spare_capacity_too() {
+        // What is left past the length after shortening is still secret.
+        let mut bytes = b"correct horse".to_vec();
+        bytes.truncate(7);
+        wipe(&mut bytes);
+        // SAFETY: `bytes` owns `capacity` bytes, all of them written above.
+        let all = unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.capacity()) };
+        assert_eq!(all, [0; 13]);
+
+        let mut value = "hunter2".to_string();
+        zeroize(&mut value);
+        assert_eq!(value, "");
+    }
+}
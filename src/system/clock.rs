@@ -0,0 +1,114 @@
+//! Setting the clock from the network before installing, since package
+//! signatures made "in the future" fail to verify.
+
+use std::{
+    fmt, io,
+    process::Command,
+    sync::mpsc::Receiver,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// systemd's tool for the clock and its NTP client.
+pub const TIMEDATECTL: &str = "timedatectl";
+/// How long to wait for the first synchronization.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(15);
+/// Pause between asking whether the clock is synchronized yet.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Why the clock could not be synchronized.
+#[derive(Debug)]
+pub enum TimeSyncError {
+    /// timedatectl could not be run at all.
+    Start(io::Error),
+    /// timedatectl ran but refused, with what it printed to stderr.
+    Failed(String),
+    /// NTP was turned on but the clock was not synchronized in time.
+    TimedOut(Duration),
+}
+
+impl fmt::Display for TimeSyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeSyncError::Start(err) => write!(f, "could not run {TIMEDATECTL}: {err}"),
+            TimeSyncError::Failed(stderr) => write!(f, "{TIMEDATECTL} failed: {stderr}"),
+            TimeSyncError::TimedOut(waited) => {
+                write!(f, "not synchronized after {} seconds", waited.as_secs())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimeSyncError {}
+
+/// Run `program` (normally [`TIMEDATECTL`]) with `args`, returning what it
+/// printed.
+fn run(program: &str, args: &[&str]) -> Result<String, TimeSyncError> {
+    let output = Command::new(program).args(args).output().map_err(TimeSyncError::Start)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(TimeSyncError::Failed(stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether `timedatectl show --property=NTPSynchronized --value` output
+/// says the clock is synchronized.
+pub fn parse_synchronized(output: &str) -> bool {
+    output.trim() == "yes"
+}
+
+/// Turn on NTP with `program` and wait up to `timeout` for the clock to be
+/// synchronized, asking every `poll`.
+pub fn sync_clock(program: &str, timeout: Duration, poll: Duration) -> Result<(), TimeSyncError> {
+    run(program, &["set-ntp", "true"])?;
+    let start = Instant::now();
+    loop {
+        let status = run(program, &["show", "--property=NTPSynchronized", "--value"])?;
+        if parse_synchronized(&status) {
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            return Err(TimeSyncError::TimedOut(timeout));
+        }
+        thread::sleep(poll);
+    }
+}
+
+/// Synchronize the clock on a background thread; the answer arrives on the
+/// returned channel.
+pub fn spawn_sync() -> Receiver<Result<(), TimeSyncError>> {
+    super::spawn_logged(
+        "clock sync".into(),
+        |()| "clock synchronized over NTP".into(),
+        |_| sync_clock(TIMEDATECTL, SYNC_TIMEOUT, POLL_INTERVAL),
+        |result| result,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synchronized_status_is_read() {
+        assert!(parse_synchronized("yes\n"));
+        assert!(!parse_synchronized("no\n"));
+        assert!(!parse_synchronized(""));
+    }
+
+    #[test]
+    fn missing_timedatectl_is_an_error() {
+        let err = sync_clock("artemis-no-such-timedatectl", Duration::ZERO, Duration::ZERO);
+        assert!(matches!(err, Err(TimeSyncError::Start(_))));
+    }
+
+    #[test]
+    fn clock_that_never_syncs_times_out() {
+        // `false` refuses to turn NTP on; `true` does, but prints no "yes".
+        let err = sync_clock("false", Duration::ZERO, Duration::ZERO).unwrap_err();
+        assert!(matches!(err, TimeSyncError::Failed(_)), "{err}");
+        let err = sync_clock("true", Duration::ZERO, Duration::ZERO).unwrap_err();
+        assert_eq!(err.to_string(), "not synchronized after 0 seconds");
+    }
+}
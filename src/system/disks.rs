@@ -0,0 +1,261 @@
+//! Block device discovery via `lsblk`.
+
+use std::{io, process::Command};
+
+/// A whole disk that can be chosen as the installation target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskInfo {
+    pub name: String,
+    pub size: String,
+    pub model: String,
+}
+
+impl DiskInfo {
+    /// The device node, e.g. `/dev/sda`.
+    pub fn path(&self) -> String {
+        format!("/dev/{}", self.name)
+    }
+
+    /// The size in MiB, if lsblk's figure could be read.
+    pub fn size_mib(&self) -> Option<u64> {
+        parse_size_mib(&self.size)
+    }
+}
+
+/// A partition already on a disk, as `lsblk` reports it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Partition {
+    pub name: String,
+    pub size: String,
+    /// Filesystem found on it, e.g. `vfat`; empty if there is none.
+    pub fstype: String,
+    pub label: String,
+    /// Filesystem UUID, for the fstab; empty without a filesystem.
+    pub uuid: String,
+}
+
+impl Partition {
+    /// The device node, e.g. `/dev/sda2`.
+    pub fn path(&self) -> String {
+        format!("/dev/{}", self.name)
+    }
+}
+
+/// MiB in one of lsblk's human-readable sizes, e.g. `465.8G`. Its units
+/// are powers of 1024.
+pub fn parse_size_mib(size: &str) -> Option<u64> {
+    let unit = size.chars().last()?;
+    let number: f64 = size[..size.len() - unit.len_utf8()].parse().ok()?;
+    let mib = match unit {
+        'B' => number / 1024.0 / 1024.0,
+        'K' => number / 1024.0,
+        'M' => number,
+        'G' => number * 1024.0,
+        'T' => number * 1024.0 * 1024.0,
+        'P' => number * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(mib as u64)
+}
+
+/// List the disks attached to this machine.
+///
+/// Fails if `lsblk` is missing or exits unsuccessfully.
+pub fn detect_disks() -> io::Result<Vec<DiskInfo>> {
+    let output = Command::new("lsblk")
+        .args(["-dnPo", "NAME,SIZE,TYPE,MODEL"])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "lsblk failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_lsblk(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `lsblk -dnPo NAME,SIZE,TYPE,MODEL` output, keeping only real disks.
+pub fn parse_lsblk(output: &str) -> Vec<DiskInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let pairs = parse_pairs(line);
+            let field = |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.clone())
+            };
+            if field("TYPE")? != "disk" {
+                return None;
+            }
+            Some(DiskInfo {
+                name: field("NAME")?,
+                size: field("SIZE").unwrap_or_default(),
+                model: field("MODEL").unwrap_or_default().trim().to_string(),
+            })
+        })
+        .filter(|disk| !is_virtual(&disk.name))
+        .collect()
+}
+
+/// List the partitions on `disk`, e.g. `/dev/sda`, with the filesystem
+/// details `lsblk -f` shows.
+pub fn list_partitions(disk: &str) -> io::Result<Vec<Partition>> {
+    let output = Command::new("lsblk")
+        .args(["-lnPo", "NAME,SIZE,FSTYPE,LABEL,UUID,TYPE", disk])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "lsblk failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_partitions(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `lsblk -lnPo NAME,SIZE,FSTYPE,LABEL,UUID,TYPE` output, keeping only
+/// the partitions.
+pub fn parse_partitions(output: &str) -> Vec<Partition> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let pairs = parse_pairs(line);
+            let field = |key: &str| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+            if field("TYPE")? != "part" {
+                return None;
+            }
+            Some(Partition {
+                name: field("NAME")?,
+                size: field("SIZE").unwrap_or_default(),
+                fstype: field("FSTYPE").unwrap_or_default(),
+                label: field("LABEL").unwrap_or_default(),
+                uuid: field("UUID").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Split a `KEY="value" KEY="value"` line, decoding lsblk's `\xNN` escapes.
+fn parse_pairs(line: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while let Some((key, after)) = rest.split_once("=\"") {
+        let Some(end) = after.find('"') else { break };
+        pairs.push((key.trim().to_string(), unescape(&after[..end])));
+        rest = &after[end + 1..];
+    }
+    pairs
+}
+
+/// Undo lsblk's `\\xNN` escapes. They are bytes, so a non-ASCII label or
+/// model comes as several, decoded together as UTF-8.
+fn unescape(value: &str) -> String {
+    let mut out = Vec::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find("\\x") {
+        out.extend_from_slice(&rest.as_bytes()[..pos]);
+        let hex = rest.get(pos + 2..pos + 4);
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                rest = &rest[pos + 4..];
+            }
+            None => {
+                out.extend_from_slice(b"\\x");
+                rest = &rest[pos + 2..];
+            }
+        }
+    }
+    out.extend_from_slice(rest.as_bytes());
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// RAM-backed devices report themselves as disks but are never targets.
+fn is_virtual(name: &str) -> bool {
+    ["ram", "zram"].iter().any(|prefix| name.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"NAME="sda" SIZE="465.8G" TYPE="disk" MODEL="Samsung SSD 860 EVO 500GB"
+NAME="loop0" SIZE="55.4M" TYPE="loop" MODEL=""
+NAME="nvme0n1" SIZE="953.9G" TYPE="disk" MODEL="WDC PC SN730 SDBPNTY-1T00-1006"
+NAME="zram0" SIZE="4G" TYPE="disk" MODEL=""
+NAME="sr0" SIZE="1024M" TYPE="rom" MODEL="DVD+-RW DU-8A5LH"
+NAME="vda" SIZE="256G" TYPE="disk" MODEL=""
+"#;
+
+    #[test]
+    fn parses_lsblk_output() {
+        let disks = parse_lsblk(SAMPLE);
+        assert_eq!(
+            disks,
+            vec![
+                DiskInfo {
+                    name: "sda".into(),
+                    size: "465.8G".into(),
+                    model: "Samsung SSD 860 EVO 500GB".into(),
+                },
+                DiskInfo {
+                    name: "nvme0n1".into(),
+                    size: "953.9G".into(),
+                    model: "WDC PC SN730 SDBPNTY-1T00-1006".into(),
+                },
+                DiskInfo {
+                    name: "vda".into(),
+                    size: "256G".into(),
+                    model: String::new(),
+                },
+            ]
+        );
+        assert_eq!(disks[0].path(), "/dev/sda");
+    }
+
+    #[test]
+    fn decodes_escaped_quotes_in_model() {
+        let disks = parse_lsblk(r#"NAME="sdb" SIZE="8G" TYPE="disk" MODEL="USB \x22Stick\x22""#);
+        assert_eq!(disks[0].model, "USB \"Stick\"");
+    }
+
+    #[test]
+    fn decodes_utf8_spread_over_escapes() {
+        let line = r#"NAME="sda1" SIZE="8G" FSTYPE="ext4" LABEL="Donn\xc3\xa9es" TYPE="part""#;
+        assert_eq!(parse_partitions(line)[0].label, "Données");
+        // Bytes that are not UTF-8 still leave a readable name.
+        assert_eq!(unescape(r"caf\xe9 \xzz"), "caf\u{fffd} \\xzz");
+    }
+
+    #[test]
+    fn sizes_are_read_in_mib() {
+        assert_eq!(parse_size_mib("465.8G"), Some(476979));
+        assert_eq!(parse_size_mib("512M"), Some(512));
+        assert_eq!(parse_size_mib("1.8T"), Some(1887436));
+        assert_eq!(parse_size_mib("1024K"), Some(1));
+        assert_eq!(parse_size_mib(""), None);
+        assert_eq!(parse_size_mib("big"), None);
+    }
+
+    #[test]
+    fn lists_the_partitions_of_a_disk() {
+        let output = r#"NAME="sda" SIZE="465.8G" FSTYPE="" LABEL="" UUID="" TYPE="disk"
+NAME="sda1" SIZE="512M" FSTYPE="vfat" LABEL="" UUID="1A2B-3C4D" TYPE="part"
+NAME="sda2" SIZE="465.3G" FSTYPE="ext4" LABEL="arch root" UUID="0f1e2d3c" TYPE="part"
+NAME="root" SIZE="465.3G" FSTYPE="" LABEL="" UUID="" TYPE="crypt"
+"#;
+        let partitions = parse_partitions(output);
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(partitions[0].path(), "/dev/sda1");
+        assert_eq!(partitions[0].fstype, "vfat");
+        assert_eq!(partitions[0].uuid, "1A2B-3C4D");
+        assert_eq!(partitions[1].label, "arch root");
+        assert_eq!(partitions[1].size, "465.3G");
+    }
+
+    #[test]
+    fn empty_output_has_no_disks() {
+        assert!(parse_lsblk("").is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+//! What kind of firmware the machine booted with.
+
+use std::path::Path;
+
+/// Directory the kernel exposes EFI variables in when booted through UEFI.
+pub const EFIVARS: &str = "/sys/firmware/efi/efivars";
+
+/// Whether the machine booted through UEFI, judged by whether `efivars`
+/// (normally [`EFIVARS`]) exists.
+pub fn is_uefi(efivars: &Path) -> bool {
+    efivars.is_dir()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn efivars_directory_means_uefi() {
+        let dir = std::env::temp_dir().join(format!("artemis-efivars-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(is_uefi(&dir));
+        std::fs::remove_dir(&dir).unwrap();
+        assert!(!is_uefi(&dir));
+    }
+}
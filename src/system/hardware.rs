@@ -0,0 +1,137 @@
+//! The processor and graphics cards, shown before anything is picked.
+
+use std::{fs, io, path::Path, process::Command};
+
+/// The kernel's description of each processor core.
+pub const CPUINFO: &str = "/proc/cpuinfo";
+
+/// Lists the PCI devices, graphics cards among them.
+pub const LSPCI: &str = "lspci";
+
+/// Who made a graphics card, which decides the drivers it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuVendor {
+    Intel,
+    Amd,
+    Nvidia,
+    /// Virtual machines' adapters and anything else.
+    Other,
+}
+
+impl GpuVendor {
+    pub fn label(self) -> &'static str {
+        match self {
+            GpuVendor::Intel => "Intel",
+            GpuVendor::Amd => "AMD",
+            GpuVendor::Nvidia => "NVIDIA",
+            GpuVendor::Other => "Other",
+        }
+    }
+}
+
+/// What was found out about the machine; `None` where a probe failed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hardware {
+    pub cpu: Option<String>,
+    /// Total memory in MiB.
+    pub memory_mib: Option<u64>,
+    /// One entry per graphics card, so hybrid laptops list two.
+    pub gpus: Vec<GpuVendor>,
+}
+
+/// The processor model according to the cpuinfo file at `path`.
+pub fn cpu_model(path: &Path) -> io::Result<String> {
+    parse_cpu_model(&fs::read_to_string(path)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no model name line"))
+}
+
+/// The first `model name` in `/proc/cpuinfo`, with its runs of spaces
+/// squeezed.
+pub fn parse_cpu_model(text: &str) -> Option<String> {
+    let model = text.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name").then_some(value)
+    })?;
+    let model = model.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!model.is_empty()).then_some(model)
+}
+
+/// The graphics cards `program` (normally [`LSPCI`]) lists.
+pub fn gpus(program: &str) -> io::Result<Vec<GpuVendor>> {
+    let output = Command::new(program).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_gpus(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The vendors of the display controllers in `lspci` output. Besides the
+/// VGA ones this counts the "3D controller" a laptop's second card shows
+/// up as.
+pub fn parse_gpus(output: &str) -> Vec<GpuVendor> {
+    const CLASSES: [&str; 3] = ["VGA compatible controller", "3D controller", "Display controller"];
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(' ')?;
+            let (class, device) = rest.split_once(": ")?;
+            CLASSES.contains(&class).then_some(device)
+        })
+        .map(|device| {
+            if device.starts_with("Intel") {
+                GpuVendor::Intel
+            } else if device.starts_with("Advanced Micro Devices") || device.contains("[AMD/ATI]") {
+                GpuVendor::Amd
+            } else if device.starts_with("NVIDIA") {
+                GpuVendor::Nvidia
+            } else {
+                GpuVendor::Other
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_first_model_name() {
+        let cpuinfo = "processor\t: 0\n\
+                       vendor_id\t: GenuineIntel\n\
+                       model name\t: Intel(R) Core(TM) i7-8550U  CPU @ 1.80GHz\n\n\
+                       processor\t: 1\n\
+                       model name\t: Intel(R) Core(TM) i7-8550U  CPU @ 1.80GHz\n";
+        assert_eq!(
+            parse_cpu_model(cpuinfo).as_deref(),
+            Some("Intel(R) Core(TM) i7-8550U CPU @ 1.80GHz")
+        );
+        // ARM boards have no model name.
+        assert_eq!(parse_cpu_model("processor\t: 0\nBogoMIPS\t: 108.00\n"), None);
+    }
+
+    #[test]
+    fn finds_every_graphics_card() {
+        let lspci = "\
+00:00.0 Host bridge: Intel Corporation Xeon E3-1200 v6/7th Gen Core Processor Host Bridge
+00:02.0 VGA compatible controller: Intel Corporation UHD Graphics 620 (rev 07)
+00:1f.3 Audio device: Intel Corporation Sunrise Point-LP HD Audio (rev 21)
+01:00.0 3D controller: NVIDIA Corporation GP108M [GeForce MX150] (rev a1)
+";
+        assert_eq!(parse_gpus(lspci), [GpuVendor::Intel, GpuVendor::Nvidia]);
+        let amd = "03:00.0 VGA compatible controller: Advanced Micro Devices, Inc. [AMD/ATI] \
+                   Navi 23 [Radeon RX 6600]\n";
+        assert_eq!(parse_gpus(amd), [GpuVendor::Amd]);
+        let vm = "00:02.0 VGA compatible controller: VMware SVGA II Adapter\n";
+        assert_eq!(parse_gpus(vm), [GpuVendor::Other]);
+        assert!(parse_gpus("").is_empty());
+    }
+
+    #[test]
+    fn missing_lspci_is_an_error() {
+        assert!(gpus("artemis-no-such-lspci").is_err());
+    }
+}
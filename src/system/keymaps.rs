@@ -0,0 +1,97 @@
+//! Console keyboard layout discovery.
+
+use std::{fs, io, path::Path, process::Command};
+
+/// Where kbd installs its console keymaps.
+pub const KBD_KEYMAPS: &str = "/usr/share/kbd/keymaps";
+
+/// The layout highlighted when nothing else has been chosen.
+pub const DEFAULT_KEYMAP: &str = "us";
+
+/// List the available console keymaps, asking `localectl` first and falling
+/// back to scanning the kbd keymap directory.
+pub fn detect_keymaps() -> Vec<String> {
+    let from_localectl = Command::new("localectl")
+        .arg("list-keymaps")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_keymap_list(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+    if !from_localectl.is_empty() {
+        return from_localectl;
+    }
+    scan_keymap_dir(Path::new(KBD_KEYMAPS)).unwrap_or_default()
+}
+
+/// Switch the console to `keymap` with loadkeys, so that keys typed to try
+/// the layout out come out as they would with it.
+pub fn load_keymap(keymap: &str) -> io::Result<()> {
+    let output = Command::new("loadkeys").arg(keymap).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+    }
+}
+
+/// Parse `localectl list-keymaps` output: one keymap name per line.
+pub fn parse_keymap_list(output: &str) -> Vec<String> {
+    let mut keymaps: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    keymaps.sort();
+    keymaps.dedup();
+    keymaps
+}
+
+/// Collect the names of all `*.map` / `*.map.gz` files below `root`.
+fn scan_keymap_dir(root: &Path) -> io::Result<Vec<String>> {
+    let mut keymaps = Vec::new();
+    collect_keymaps(root, &mut keymaps)?;
+    keymaps.sort();
+    keymaps.dedup();
+    Ok(keymaps)
+}
+
+fn collect_keymaps(dir: &Path, keymaps: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_keymaps(&path, keymaps)?;
+            continue;
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let stem = name
+            .strip_suffix(".map.gz")
+            .or_else(|| name.strip_suffix(".map"));
+        if let Some(stem) = stem {
+            keymaps.push(stem.to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+    }
+
+    #[test]
+    fn parses_localectl_output() {
+        let output = fs::read_to_string(fixture("localectl-list-keymaps")).unwrap();
+        assert_eq!(parse_keymap_list(&output), ["de", "de-latin1", "fr", "uk", "us"]);
+    }
+
+    #[test]
+    fn scans_keymap_directory() {
+        let keymaps = scan_keymap_dir(&fixture("kbd")).unwrap();
+        assert_eq!(keymaps, ["fr", "uk", "us"]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Locales the new system can be set up with.
+
+use std::{fs, io, path::Path};
+
+/// glibc's list of locales `locale-gen` can build, mostly commented out.
+pub const LOCALE_GEN: &str = "/etc/locale.gen";
+
+/// Every locale glibc supports, read when `locale.gen` is missing.
+pub const SUPPORTED: &str = "/usr/share/i18n/SUPPORTED";
+
+/// List the UTF-8 locales in the first of `paths` that can be read.
+pub fn list_locales(paths: &[&Path]) -> io::Result<Vec<String>> {
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, "no locale list given");
+    for path in paths {
+        match fs::read_to_string(path) {
+            Ok(text) => return Ok(parse_locales(&text)),
+            Err(err) => last_err = err,
+        }
+    }
+    Err(last_err)
+}
+
+/// Parse `locale.gen` or `SUPPORTED`: one `<locale> <charset>` pair per
+/// line, commented out or not. Only plain `xx_YY.UTF-8` locales are kept,
+/// sorted and without duplicates.
+pub fn parse_locales(text: &str) -> Vec<String> {
+    let mut locales: Vec<String> = text
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start_matches('#');
+            let mut fields = line.split_whitespace();
+            let (locale, charset) = (fields.next()?, fields.next()?);
+            let (name, encoding) = locale.split_once('.')?;
+            let plain = name.contains('_') && !name.contains('@');
+            (plain && encoding == "UTF-8" && charset == "UTF-8" && fields.next().is_none())
+                .then(|| locale.to_string())
+        })
+        .collect();
+    locales.sort();
+    locales.dedup();
+    locales
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_utf8_locales_from_locale_gen() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/locale.gen");
+        let locales = list_locales(&[Path::new("/nonexistent/locale.gen"), &fixture]).unwrap();
+        assert_eq!(
+            locales,
+            ["aa_DJ.UTF-8", "de_DE.UTF-8", "en_GB.UTF-8", "en_US.UTF-8", "fr_FR.UTF-8", "ja_JP.UTF-8"]
+        );
+    }
+
+    #[test]
+    fn reads_utf8_locales_from_supported() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/SUPPORTED");
+        let locales = list_locales(&[&fixture]).unwrap();
+        assert_eq!(
+            locales,
+            [
+                "aa_DJ.UTF-8",
+                "be_BY.UTF-8",
+                "de_DE.UTF-8",
+                "en_GB.UTF-8",
+                "en_US.UTF-8",
+                "ja_JP.UTF-8",
+                "zh_CN.UTF-8",
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_supported_list() {
+        let supported = "de_DE.UTF-8 UTF-8\nde_DE ISO-8859-1\nsr_RS@latin UTF-8\nen_US.UTF-8 UTF-8\n";
+        assert_eq!(parse_locales(supported), ["de_DE.UTF-8", "en_US.UTF-8"]);
+        assert!(list_locales(&[Path::new("/nonexistent")]).is_err());
+    }
+}
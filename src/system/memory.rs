@@ -0,0 +1,62 @@
+//! How much memory the machine has, and how much swap that calls for.
+
+use std::{fs, io, path::Path};
+
+/// The kernel's memory statistics.
+pub const MEMINFO: &str = "/proc/meminfo";
+
+/// Swap suggested when the amount of memory is unknown.
+pub const FALLBACK_SWAP_GIB: u32 = 4;
+
+/// Most swap ever suggested; beyond this it is only wasted disk space.
+const MAX_SUGGESTED_SWAP_GIB: u32 = 16;
+
+/// Total memory in MiB according to the meminfo file at `path`.
+pub fn total_memory(path: &Path) -> io::Result<u64> {
+    parse_meminfo(&fs::read_to_string(path)?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no MemTotal line"))
+}
+
+/// The `MemTotal` line of `/proc/meminfo`, in MiB.
+pub fn parse_meminfo(text: &str) -> Option<u64> {
+    let line = text.lines().find_map(|line| line.strip_prefix("MemTotal:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib / 1024)
+}
+
+/// Swap size to suggest for `memory_mib` of memory: twice as much on small
+/// machines, as much up to 8 GiB, then half of it up to a limit.
+pub fn suggested_swap_gib(memory_mib: u64) -> u32 {
+    // The kernel keeps some memory for itself, so 8 GiB shows up as a bit less.
+    let memory_gib = u32::try_from(memory_mib.div_ceil(1024)).unwrap_or(u32::MAX).max(1);
+    let suggested = match memory_gib {
+        0..=2 => memory_gib * 2,
+        3..=8 => memory_gib,
+        _ => memory_gib / 2,
+    };
+    suggested.min(MAX_SUGGESTED_SWAP_GIB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_total_memory_from_meminfo() {
+        let meminfo = "MemTotal:        7989644 kB\nMemFree:         1203380 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(7802));
+        assert_eq!(parse_meminfo("MemFree: 12 kB\n"), None);
+        assert_eq!(parse_meminfo("MemTotal: lots\n"), None);
+    }
+
+    #[test]
+    fn suggestion_follows_the_memory_size() {
+        assert_eq!(suggested_swap_gib(512), 2);
+        assert_eq!(suggested_swap_gib(2048), 4);
+        assert_eq!(suggested_swap_gib(3900), 4);
+        // 8 GiB machines report a little under 8 GiB.
+        assert_eq!(suggested_swap_gib(7802), 8);
+        assert_eq!(suggested_swap_gib(16 * 1024), 8);
+        assert_eq!(suggested_swap_gib(128 * 1024), MAX_SUGGESTED_SWAP_GIB);
+    }
+}
@@ -0,0 +1,176 @@
+//! Package mirrors: the live system's mirrorlist, and ranking it with reflector.
+
+use std::{
+    fs, io,
+    path::Path,
+    process::Command,
+    sync::mpsc::Receiver,
+};
+
+/// The live system's pacman mirrorlist.
+pub const MIRRORLIST: &str = "/etc/pacman.d/mirrorlist";
+
+/// How many of the most recently synced mirrors reflector ranks.
+const RANKED_MIRRORS: &str = "10";
+
+/// A server packages can be downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mirror {
+    /// The `## Country` heading the server is listed under, if any.
+    pub country: Option<String>,
+    /// e.g. `https://mirror.example.org/archlinux/$repo/os/$arch`.
+    pub url: String,
+    /// Whether pacman uses it; commented-out servers are not.
+    pub active: bool,
+}
+
+/// Read the mirrors in the mirrorlist at `path`.
+pub fn read_mirrorlist(path: &Path) -> io::Result<Vec<Mirror>> {
+    Ok(parse_mirrorlist(&fs::read_to_string(path)?))
+}
+
+/// Parse a pacman mirrorlist. Servers take their country from the nearest
+/// `## Country` heading above them; commented-out ones are kept but marked
+/// inactive.
+pub fn parse_mirrorlist(text: &str) -> Vec<Mirror> {
+    let mut country: Option<String> = None;
+    let mut mirrors = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if let Some(heading) = line.strip_prefix("##") {
+            let heading = heading.trim();
+            country = (!heading.is_empty()).then(|| heading.to_string());
+            continue;
+        }
+        let (line, active) = match line.strip_prefix('#') {
+            Some(commented) => (commented.trim_start(), false),
+            None => (line, true),
+        };
+        let Some((key, url)) = line.split_once('=') else { continue };
+        if key.trim() == "Server" && !url.trim().is_empty() {
+            mirrors.push(Mirror { country: country.clone(), url: url.trim().to_string(), active });
+        }
+    }
+    mirrors
+}
+
+/// The countries `mirrors` are listed under, in the order they first appear.
+pub fn countries(mirrors: &[Mirror]) -> Vec<&str> {
+    let mut countries: Vec<&str> = Vec::new();
+    for country in mirrors.iter().filter_map(|mirror| mirror.country.as_deref()) {
+        if !countries.contains(&country) {
+            countries.push(country);
+        }
+    }
+    countries
+}
+
+/// Write `mirrors` out as a mirrorlist with every server active, under
+/// their country headings.
+pub fn render_mirrorlist(mirrors: &[Mirror]) -> String {
+    let mut text = String::new();
+    let mut country = None;
+    for mirror in mirrors {
+        if mirror.country.is_some() && mirror.country != country {
+            country = mirror.country.clone();
+            text.push_str(&format!("## {}\n", mirror.country.as_deref().unwrap_or_default()));
+        }
+        text.push_str(&format!("Server = {}\n", mirror.url));
+    }
+    text
+}
+
+/// Rank the latest mirrors, of `countries` if any are given, by download
+/// rate with reflector on a background thread; they arrive on the returned
+/// channel, fastest first. Reflector downloads from each one, so this takes
+/// a while.
+pub fn spawn_rank(countries: Vec<String>) -> Receiver<Result<Vec<Mirror>, String>> {
+    super::spawn_logged(
+        "could not rank mirrors".into(),
+        |mirrors: &Vec<Mirror>| format!("reflector ranked {} mirrors", mirrors.len()),
+        move |_| rank(&countries).map_err(|err| err.to_string()),
+        |result| result,
+    )
+}
+
+fn rank(countries: &[String]) -> io::Result<Vec<Mirror>> {
+    let mut command = Command::new("reflector");
+    if !countries.is_empty() {
+        command.args(["--country", &countries.join(",")]);
+    }
+    let output = command.args(["--latest", RANKED_MIRRORS, "--sort", "rate"]).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "reflector failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    // Reflector's list has no country headings; with several countries
+    // there is no telling which server is in which.
+    let mut mirrors = parse_mirrorlist(&String::from_utf8_lossy(&output.stdout));
+    if let [country] = countries {
+        for mirror in &mut mirrors {
+            mirror.country = Some(country.clone());
+        }
+    }
+    Ok(mirrors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+##
+## Arch Linux repository mirrorlist
+## Generated on 2024-05-01
+##
+
+## Worldwide
+#Server = https://geo.mirror.pkgbuild.com/$repo/os/$arch
+Server = https://mirror.rackspace.com/archlinux/$repo/os/$arch
+
+## Germany
+Server=http://ftp.fau.de/archlinux/$repo/os/$arch
+#Server = https://mirror.f4st.host/archlinux/$repo/os/$arch
+# Server =
+";
+
+    #[test]
+    fn parses_servers_under_their_country() {
+        let mirrors = parse_mirrorlist(SAMPLE);
+        let summary: Vec<_> = mirrors
+            .iter()
+            .map(|m| (m.country.as_deref().unwrap(), m.url.as_str(), m.active))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Worldwide", "https://geo.mirror.pkgbuild.com/$repo/os/$arch", false),
+                ("Worldwide", "https://mirror.rackspace.com/archlinux/$repo/os/$arch", true),
+                ("Germany", "http://ftp.fau.de/archlinux/$repo/os/$arch", true),
+                ("Germany", "https://mirror.f4st.host/archlinux/$repo/os/$arch", false),
+            ]
+        );
+        assert_eq!(countries(&mirrors), ["Worldwide", "Germany"]);
+    }
+
+    #[test]
+    fn servers_without_a_heading_have_no_country() {
+        let mirrors = parse_mirrorlist("# generated by reflector\nServer = https://a/\n");
+        assert_eq!(mirrors[0].country, None);
+        assert!(countries(&mirrors).is_empty());
+    }
+
+    #[test]
+    fn rendered_list_activates_every_server() {
+        let mirrors = parse_mirrorlist(SAMPLE);
+        let text = render_mirrorlist(&mirrors[2..]);
+        assert_eq!(
+            text,
+            "## Germany\n\
+             Server = http://ftp.fau.de/archlinux/$repo/os/$arch\n\
+             Server = https://mirror.f4st.host/archlinux/$repo/os/$arch\n"
+        );
+        assert_eq!(parse_mirrorlist(&text).iter().filter(|m| m.active).count(), 2);
+    }
+}
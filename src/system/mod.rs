@@ -0,0 +1,48 @@
+//! Probes that inspect the live system the installer is running on.
+
+use std::{
+    fmt,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+pub mod clock;
+pub mod disks;
+pub mod firmware;
+pub mod hardware;
+pub mod keymaps;
+pub mod locales;
+pub mod memory;
+pub mod mirrors;
+pub mod network;
+pub mod timezones;
+pub mod users;
+pub mod wifi;
+
+/// Run `work` on a background thread and send its result, wrapped by `wrap`,
+/// on the returned channel; `work` may send updates of its own before that.
+/// How it went is logged first: `done` describes a success, and a failure is
+/// logged after `failed`.
+fn spawn_logged<T, E, M>(
+    failed: String,
+    done: impl FnOnce(&T) -> String + Send + 'static,
+    work: impl FnOnce(&Sender<M>) -> Result<T, E> + Send + 'static,
+    wrap: impl FnOnce(Result<T, E>) -> M + Send + 'static,
+) -> Receiver<M>
+where
+    T: Send + 'static,
+    E: fmt::Display + Send + 'static,
+    M: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let result = work(&tx);
+        match &result {
+            Ok(value) => log::info!("{}", done(value)),
+            Err(err) => log::warn!("{failed}: {err}"),
+        }
+        // The UI may have quit already; nobody is left to tell.
+        let _ = tx.send(wrap(result));
+    });
+    rx
+}
@@ -0,0 +1,105 @@
+//! Checking that the package mirrors can be reached.
+
+use std::{
+    fmt, io,
+    net::{TcpStream, ToSocketAddrs},
+    sync::{atomic::AtomicBool, mpsc::Receiver},
+    time::Duration,
+};
+
+use crate::retry::{retry, Backoff};
+
+/// Host connected to when checking for internet access, unless another is
+/// given with `--check-host`.
+pub const CHECK_HOST: &str = "archlinux.org:443";
+/// How long each connection attempt may take.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why the check host could not be reached.
+#[derive(Debug)]
+pub enum ConnError {
+    /// The host name could not be resolved.
+    Lookup(io::Error),
+    /// None of the host's addresses took the connection; the last error.
+    Connect(io::Error),
+}
+
+impl fmt::Display for ConnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnError::Lookup(err) => write!(f, "could not look up the host: {err}"),
+            ConnError::Connect(err) => write!(f, "could not connect: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ConnError {}
+
+/// What the background network check reports.
+#[derive(Debug)]
+pub enum CheckUpdate {
+    /// The last try failed and try `attempt` of `attempts` is about to start.
+    Retrying { attempt: u32, attempts: u32 },
+    /// The check is over; nothing more will be sent.
+    Finished(Result<(), ConnError>),
+}
+
+/// Try a TCP connection to `address` (`host:port`), giving each of its
+/// addresses `timeout` to answer.
+pub fn check_connectivity(address: &str, timeout: Duration) -> Result<(), ConnError> {
+    let addresses = address.to_socket_addrs().map_err(ConnError::Lookup)?;
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, timeout) {
+            Ok(_) => return Ok(()),
+            Err(err) => last_error = Some(err),
+        }
+    }
+    Err(match last_error {
+        Some(err) => ConnError::Connect(err),
+        None => ConnError::Lookup(io::Error::new(io::ErrorKind::NotFound, "no addresses")),
+    })
+}
+
+/// Check `address` on a background thread, trying again as `backoff` says;
+/// each retry and then the answer arrive on the returned channel.
+pub fn spawn_check(address: String, backoff: Backoff) -> Receiver<CheckUpdate> {
+    let reachable = format!("network check: {address} is reachable");
+    super::spawn_logged(
+        format!("network check: {address}"),
+        |()| reachable,
+        move |tx| {
+            // The check is never cancelled; the UI just stops listening.
+            let never = AtomicBool::new(false);
+            retry(backoff.attempts, backoff.base_delay, &never, |attempt| {
+                if attempt > 1 {
+                    let _ = tx.send(CheckUpdate::Retrying { attempt, attempts: backoff.attempts });
+                }
+                check_connectivity(&address, CHECK_TIMEOUT)
+            })
+        },
+        CheckUpdate::Finished,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn listening_port_is_reachable_and_closed_one_is_not() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        assert!(check_connectivity(&address, Duration::from_secs(1)).is_ok());
+        drop(listener);
+        let err = check_connectivity(&address, Duration::from_secs(1)).unwrap_err();
+        assert!(matches!(err, ConnError::Connect(_)), "{err}");
+    }
+
+    #[test]
+    fn unresolvable_host_is_unreachable() {
+        let err = check_connectivity("not a host", Duration::from_secs(1)).unwrap_err();
+        assert!(matches!(err, ConnError::Lookup(_)), "{err}");
+    }
+}
@@ -0,0 +1,140 @@
+//! Timezone discovery from the tzdata zoneinfo tree.
+
+use std::{fs, io, path::Path};
+
+/// Where tzdata installs its compiled zone files.
+pub const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// Used when the zoneinfo database cannot be read.
+pub const FALLBACK_TIMEZONE: &str = "UTC";
+
+/// Top-level directories that mirror the whole tree in another format.
+const SKIPPED_DIRS: [&str; 2] = ["posix", "right"];
+
+/// List every `Region/City` zone under `root`, sorted.
+///
+/// Top-level files such as `zone.tab` or bare aliases like `UTC` are not
+/// `Region/City` entries and are skipped.
+pub fn list_timezones(root: &Path) -> io::Result<Vec<String>> {
+    let mut zones = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if SKIPPED_DIRS.contains(&name.as_str()) || !entry.file_type()?.is_dir() {
+            continue;
+        }
+        collect_zones(&entry.path(), &name, &mut zones)?;
+    }
+    zones.sort();
+    Ok(zones)
+}
+
+fn collect_zones(dir: &Path, prefix: &str, zones: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+        // `file_type` does not follow symlinks: linked zone files are kept as
+        // aliases, but linked directories are skipped so a loop cannot recurse.
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_zones(&entry.path(), &name, zones)?;
+        } else if !file_type.is_symlink() || !entry.path().is_dir() {
+            zones.push(name);
+        }
+    }
+    Ok(())
+}
+
+/// The distinct regions of `zones`, in order. A zone without a region
+/// (such as `UTC`) is its own region.
+pub fn regions(zones: &[String]) -> Vec<&str> {
+    let mut regions: Vec<&str> = zones
+        .iter()
+        .map(|zone| zone.split_once('/').map_or(zone.as_str(), |(region, _)| region))
+        .collect();
+    regions.dedup();
+    regions
+}
+
+/// The cities of `region`, i.e. everything after `Region/`.
+pub fn cities<'a>(zones: &'a [String], region: &str) -> Vec<&'a str> {
+    zones
+        .iter()
+        .filter_map(|zone| zone.strip_prefix(region)?.strip_prefix('/'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str, files: &[&str]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("artemis-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "TZif").unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn lists_region_city_zones_sorted() {
+        let root = fixture(
+            "zoneinfo",
+            &[
+                "Europe/Paris",
+                "America/Argentina/Buenos_Aires",
+                "America/New_York",
+                "Europe/Berlin",
+                "posix/Europe/Paris",
+                "right/Europe/Paris",
+                "zone.tab",
+                "UTC",
+            ],
+        );
+        let zones = list_timezones(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            zones,
+            [
+                "America/Argentina/Buenos_Aires",
+                "America/New_York",
+                "Europe/Berlin",
+                "Europe/Paris",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_symlinked_directories() {
+        let root = fixture("zoneinfo-links", &["Europe/Kyiv"]);
+        std::os::unix::fs::symlink(&root, root.join("Europe/Loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("Europe/Kyiv"), root.join("Europe/Kiev")).unwrap();
+        let zones = list_timezones(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(zones, ["Europe/Kiev", "Europe/Kyiv"]);
+    }
+
+    #[test]
+    fn groups_zones_by_region() {
+        let zones: Vec<String> = [
+            "America/Argentina/Buenos_Aires",
+            "America/New_York",
+            "Europe/Paris",
+            "UTC",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(regions(&zones), ["America", "Europe", "UTC"]);
+        assert_eq!(cities(&zones, "America"), ["Argentina/Buenos_Aires", "New_York"]);
+        assert!(cities(&zones, "UTC").is_empty());
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        assert!(list_timezones(Path::new("/nonexistent/zoneinfo")).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+//! Accounts that already exist on the live system.
+
+use std::{fs, path::Path};
+
+/// The live system's account database.
+pub const PASSWD: &str = "/etc/passwd";
+
+/// Names of the accounts in the passwd file at `path`. An unreadable file
+/// lists nobody, so no name is refused because of it.
+pub fn existing_users(path: &Path) -> Vec<String> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse_passwd(&text),
+        Err(err) => {
+            log::warn!("cannot read {}: {err}", path.display());
+            Vec::new()
+        }
+    }
+}
+
+/// The account names in passwd-format `text`, one `name:...` entry per line.
+pub fn parse_passwd(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, _)| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_account_names_from_a_passwd_file() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/passwd");
+        let users = existing_users(&fixture);
+        assert_eq!(users.len(), 9);
+        for name in ["root", "nobody", "systemd-network", "liveuser"] {
+            assert!(users.iter().any(|user| user == name), "missing {name}");
+        }
+        assert!(!users.iter().any(|user| user.starts_with('#')));
+    }
+
+    #[test]
+    fn unreadable_passwd_lists_nobody() {
+        assert!(existing_users(Path::new("/nonexistent/passwd")).is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Wireless networks, found and joined through NetworkManager's `nmcli`.
+
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+    sync::mpsc::Receiver,
+};
+
+use crate::secret::Secret;
+
+/// A wireless network in range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WifiNetwork {
+    pub ssid: String,
+    /// Signal strength in percent.
+    pub signal: u8,
+}
+
+/// List the wireless networks in range, strongest first.
+///
+/// Fails if `nmcli` is missing or exits unsuccessfully, e.g. because there
+/// is no wireless device.
+pub fn scan() -> io::Result<Vec<WifiNetwork>> {
+    let output = Command::new("nmcli")
+        .args(["-t", "-f", "SSID,SIGNAL", "dev", "wifi"])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "nmcli failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parse_networks(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `nmcli -t -f SSID,SIGNAL dev wifi` output. Hidden networks are
+/// left out, and a network seen by several access points is listed once
+/// with its best signal.
+pub fn parse_networks(output: &str) -> Vec<WifiNetwork> {
+    let mut networks: Vec<WifiNetwork> = Vec::new();
+    for line in output.lines() {
+        let fields = split_terse(line);
+        let [ssid, signal] = fields.as_slice() else { continue };
+        if ssid.is_empty() {
+            continue;
+        }
+        let signal = signal.trim().parse().unwrap_or(0);
+        match networks.iter_mut().find(|network| network.ssid == *ssid) {
+            Some(network) => network.signal = network.signal.max(signal),
+            None => networks.push(WifiNetwork { ssid: ssid.clone(), signal }),
+        }
+    }
+    networks.sort_by_key(|network| std::cmp::Reverse(network.signal));
+    networks
+}
+
+/// Whether a wired connection is up, according to
+/// `nmcli -t -f TYPE,STATE dev`. Without `nmcli` nothing is known to be up.
+pub fn wired_connected() -> bool {
+    Command::new("nmcli")
+        .args(["-t", "-f", "TYPE,STATE", "dev"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .is_some_and(|output| parse_wired(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Whether `nmcli -t -f TYPE,STATE dev` output lists a connected ethernet
+/// device.
+pub fn parse_wired(output: &str) -> bool {
+    output.lines().map(split_terse).any(|fields| {
+        matches!(fields.as_slice(), [kind, state] if kind == "ethernet" && state == "connected")
+    })
+}
+
+/// Split a line of `nmcli -t` output on its `:` separators, undoing the
+/// `\:` and `\\` escapes in the values.
+fn split_terse(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => fields.last_mut().unwrap().extend(chars.next()),
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Join `ssid` on a background thread; the outcome arrives on the returned
+/// channel. The passphrase goes in on stdin so it never shows up in a
+/// command line, and is left out for open networks.
+pub fn spawn_connect(ssid: String, passphrase: Secret) -> Receiver<Result<(), String>> {
+    let joined = format!("joined wireless network {ssid}");
+    super::spawn_logged(
+        format!("could not join wireless network {ssid}"),
+        |()| joined,
+        move |_| connect(&ssid, passphrase.expose()).map_err(|err| err.to_string()),
+        |result| result,
+    )
+}
+
+fn connect(ssid: &str, passphrase: &str) -> io::Result<()> {
+    let mut command = Command::new("nmcli");
+    if !passphrase.is_empty() {
+        command.arg("--ask");
+    }
+    let mut child = command
+        .args(["dev", "wifi", "connect", ssid])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if !passphrase.is_empty() {
+            writeln!(stdin, "{passphrase}")?;
+        }
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nmcli_networks() {
+        let output = "Home:82\n\
+                      :40\n\
+                      Caf\\\\e\\: Guest:55\n\
+                      Home:64\n\
+                      Neighbour\\:5G:91\n";
+        assert_eq!(
+            parse_networks(output),
+            vec![
+                WifiNetwork { ssid: "Neighbour:5G".into(), signal: 91 },
+                WifiNetwork { ssid: "Home".into(), signal: 82 },
+                WifiNetwork { ssid: "Caf\\e: Guest".into(), signal: 55 },
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        assert!(parse_networks("\nno signal\n").is_empty());
+        assert_eq!(parse_networks("Lab:n/a")[0].signal, 0);
+    }
+
+    #[test]
+    fn wired_connection_is_spotted() {
+        assert!(parse_wired("wifi:disconnected\nethernet:connected\nloopback:unmanaged\n"));
+        assert!(!parse_wired("wifi:connected\nethernet:unavailable\n"));
+        assert!(!parse_wired(""));
+    }
+}
@@ -0,0 +1,192 @@
+//! Color schemes for the UI. Screens pick colors by role from the active
+//! [`Theme`] rather than naming them directly.
+
+use ratatui::style::Color;
+
+/// Environment variable naming the theme when `--theme` is not given.
+pub const THEME_VAR: &str = "ARTEMIS_THEME";
+
+/// The colors the UI is drawn with, by what they are used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Body text.
+    pub fg: Color,
+    /// Behind everything.
+    pub bg: Color,
+    /// The installer's name and other headings.
+    pub title: Color,
+    /// Prompts and the current step.
+    pub accent: Color,
+    /// Hints and things already done.
+    pub muted: Color,
+    /// Highlighted entries, progress and things that went well.
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    pub border: Color,
+    /// Text typed into input fields.
+    pub input: Color,
+    /// Leave the emoji out of titles and turn an ASCII spinner, for screen
+    /// readers and terminals without the glyphs.
+    pub plain: bool,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        name: "dark",
+        fg: Color::Gray,
+        bg: Color::Reset,
+        title: Color::LightCyan,
+        accent: Color::Cyan,
+        muted: Color::DarkGray,
+        success: Color::LightGreen,
+        warning: Color::Yellow,
+        error: Color::Red,
+        border: Color::White,
+        input: Color::White,
+        plain: false,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        name: "light",
+        fg: Color::Black,
+        bg: Color::White,
+        title: Color::Blue,
+        accent: Color::Blue,
+        muted: Color::DarkGray,
+        success: Color::Green,
+        warning: Color::Magenta,
+        error: Color::Red,
+        border: Color::Black,
+        input: Color::Black,
+        plain: false,
+    };
+
+    /// Bright colors on black with no dim text, for poor eyesight or
+    /// washed-out screens.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        name: "high-contrast",
+        fg: Color::White,
+        bg: Color::Black,
+        title: Color::White,
+        accent: Color::LightYellow,
+        muted: Color::White,
+        success: Color::LightGreen,
+        warning: Color::LightYellow,
+        error: Color::LightRed,
+        border: Color::White,
+        input: Color::White,
+        plain: false,
+    };
+
+    /// The terminal's own colors throughout, with bold for emphasis and no
+    /// decoration. This is what `--accessible` picks.
+    pub const MONOCHROME: Theme = Theme {
+        name: "monochrome",
+        fg: Color::Reset,
+        bg: Color::Reset,
+        title: Color::Reset,
+        accent: Color::Reset,
+        muted: Color::Reset,
+        success: Color::Reset,
+        warning: Color::Reset,
+        error: Color::Reset,
+        border: Color::Reset,
+        input: Color::Reset,
+        plain: true,
+    };
+
+    pub const ALL: [Theme; 4] =
+        [Theme::DARK, Theme::LIGHT, Theme::HIGH_CONTRAST, Theme::MONOCHROME];
+
+    /// The theme called `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Theme> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.name.eq_ignore_ascii_case(name))
+    }
+
+    /// This theme made easier to read: plain titles, and hints in the body
+    /// color rather than a dim one.
+    pub fn accessible(self) -> Theme {
+        Theme { plain: true, muted: self.fg, ..self }
+    }
+
+    /// The theme after this one in [`Theme::ALL`], wrapping around.
+    pub fn next(self) -> Theme {
+        let index = Self::ALL.iter().position(|theme| theme.name == self.name).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The theme named in [`THEME_VAR`], if it is set to a known one.
+    pub fn from_env() -> Option<Theme> {
+        let name = std::env::var(THEME_VAR).ok()?;
+        let theme = Self::from_name(&name);
+        if theme.is_none() {
+            log::warn!("ignoring unknown {THEME_VAR} `{name}`");
+        }
+        theme
+    }
+
+    /// `text` as a title, without a leading or trailing emoji when the
+    /// theme is plain, e.g. `🌐 Network` becomes `Network`.
+    pub fn heading(&self, text: &str) -> String {
+        if !self.plain {
+            return text.to_string();
+        }
+        let decorative = |word: &&str| !word.chars().any(char::is_alphanumeric);
+        let mut words: Vec<&str> = text.split(' ').collect();
+        if words.first().is_some_and(decorative) {
+            words.remove(0);
+        }
+        if words.last().is_some_and(decorative) {
+            words.pop();
+        }
+        words.join(" ")
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn themes_are_found_by_name() {
+        assert_eq!(Theme::from_name("light"), Some(Theme::LIGHT));
+        assert_eq!(Theme::from_name("High-Contrast"), Some(Theme::HIGH_CONTRAST));
+        assert_eq!(Theme::from_name("solarized"), None);
+    }
+
+    #[test]
+    fn next_theme_recolors_the_roles() {
+        assert_eq!(Theme::DARK.next(), Theme::LIGHT);
+        assert_ne!(Theme::DARK.next().accent, Theme::DARK.accent);
+        assert_ne!(Theme::DARK.next().bg, Theme::DARK.bg);
+        assert_eq!(Theme::MONOCHROME.next(), Theme::DARK);
+    }
+
+    #[test]
+    fn accessible_theme_brightens_hints() {
+        let theme = Theme::DARK.accessible();
+        assert!(theme.plain);
+        assert_eq!(theme.muted, Theme::DARK.fg);
+        assert_ne!(theme.muted, Color::DarkGray);
+        assert_eq!(theme.accent, Theme::DARK.accent);
+    }
+
+    #[test]
+    fn plain_headings_drop_the_emoji() {
+        assert_eq!(Theme::DARK.heading("🌐 Network"), "🌐 Network");
+        assert_eq!(Theme::MONOCHROME.heading("🌐 Network"), "Network");
+        let complete = Theme::MONOCHROME.heading("Installation Complete! 🎉");
+        assert_eq!(complete, "Installation Complete!");
+        assert_eq!(Theme::MONOCHROME.heading("🕒 Timezone — Europe"), "Timezone — Europe");
+    }
+}
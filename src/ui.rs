@@ -0,0 +1,3195 @@
+use std::{
+    cmp::Ordering,
+    io,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, Gauge, HighlightSpacing, List, ListItem, ListState, Paragraph,
+        Wrap,
+    },
+    Frame, Terminal,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::{
+        AppState, Connectivity, FinishAction, InstallerState, ListHitbox, Ranking, Retry, Step,
+        TimeSync, WifiStatus, ACCOUNT_FIELDS, LANGUAGES, ROOT_FIELDS,
+    },
+    choices::{
+        Bootloader, Filesystem, Firmware, MountPoint, PartitionScheme, SwapConfig, DESKTOPS,
+        PACKAGES,
+    },
+    install::{self, InstallPhase},
+    system::{mirrors, timezones},
+    validate::{validate_hostname, validate_package_name},
+    theme::Theme,
+    widgets::{
+        popup::{centered_rect, render_key_help, render_popup},
+        text_input::TextInput,
+    },
+};
+
+/// Label of the clickable button in the footer that works like Enter.
+const NEXT_BUTTON: &str = "[ Next ▸ ]";
+
+/// Rows for the title and breadcrumb, and for the spinner and key hints,
+/// each including the border line. Fixed so they survive short terminals.
+const HEADER_HEIGHT: u16 = 2;
+const FOOTER_HEIGHT: u16 = 3;
+
+/// Smallest terminal the regular layout can be drawn in.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+
+/// The logo at the top of the welcome screen.
+const BANNER: &str = r" _____           _                                   ___  ____
+| ____|_ __   __| | ___  __ ___   _____  _   _ _ __ / _ \/ ___|
+|  _| | '_ \ / _` |/ _ \/ _` \ \ / / _ \| | | | '__| | | \___ \
+| |___| | | | (_| |  __/ (_| |\ V / (_) | |_| | |  | |_| |___) |
+|_____|_| |_|\__,_|\___|\__,_| \_/ \___/ \__,_|_|   \___/|____/";
+
+/// What the welcome screen shows when [`BANNER`] does not fit.
+const COMPACT_BANNER: &str = ".-------------.
+| EndeavourOS |
+'-------------'";
+
+/// Draw the UI if anything changed since the last time, returning whether
+/// it was drawn.
+pub fn redraw<B: Backend>(terminal: &mut Terminal<B>, app: &mut AppState) -> io::Result<bool> {
+    if !app.dirty {
+        return Ok(false);
+    }
+    terminal.draw(|f| draw(f, app))?;
+    app.dirty = false;
+    Ok(true)
+}
+
+/// Render the whole installer UI for the current state.
+pub fn draw(f: &mut Frame, app: &mut AppState) {
+    let theme = app.theme;
+    // Screens with clickable parts record them again as they are drawn.
+    app.list_hitbox = None;
+    app.next_button = None;
+
+    // Paint the theme's background; unstyled borders and titles take the
+    // border color.
+    f.render_widget(
+        Block::default().style(Style::default().fg(theme.border).bg(theme.bg)),
+        f.area(),
+    );
+
+    if f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT {
+        too_small_screen(f, &theme);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(HEADER_HEIGHT),
+            Constraint::Min(0),
+            Constraint::Length(FOOTER_HEIGHT),
+        ])
+        .split(f.area());
+
+    let width = f.area().width as usize;
+
+    let header = Paragraph::new(center_text(
+        &theme.heading("🚀 EndeavourOS Installer"),
+        width,
+        Style::default().fg(theme.title).add_modifier(Modifier::BOLD),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .border_style(Style::default().fg(theme.border))
+            .title_bottom(breadcrumb(app, width.saturating_sub(2)).centered()),
+    );
+    f.render_widget(header, chunks[0]);
+
+    match app.step {
+        Step::Welcome => {
+            let height = chunks[1].height as usize;
+            f.render_widget(welcome_screen(width, height, &theme), chunks[1])
+        }
+        Step::Hardware => f.render_widget(hardware_screen(width, app), chunks[1]),
+        Step::Wifi => wifi_screen(f, chunks[1], app),
+        Step::Network => f.render_widget(network_screen(width, app), chunks[1]),
+        Step::Mirrors => mirror_screen(f, chunks[1], app),
+        Step::Language => language_selection_screen(f, chunks[1], app),
+        Step::Locale => locale_selection_screen(f, chunks[1], app),
+        Step::Keyboard => keymap_selection_screen(f, chunks[1], app),
+        Step::Timezone => timezone_selection_screen(f, chunks[1], app),
+        Step::Disk => disk_selection_screen(f, chunks[1], app),
+        Step::PartitionScheme => partition_scheme_screen(f, chunks[1], app),
+        Step::Filesystem => filesystem_selection_screen(f, chunks[1], app),
+        Step::Swap => swap_screen(f, chunks[1], app),
+        Step::Bootloader => bootloader_selection_screen(f, chunks[1], app),
+        Step::Hostname => hostname_screen(f, chunks[1], app),
+        Step::UserAccount => user_account_screen(f, chunks[1], app),
+        Step::Desktop => desktop_selection_screen(f, chunks[1], app),
+        Step::Graphics => driver_screen(f, chunks[1], app),
+        Step::Packages => packages_screen(f, chunks[1], app),
+        Step::ExtraPackages => extra_packages_screen(f, chunks[1], app),
+        Step::Summary if app.command_review.is_some() || app.fstab_review.is_some() => {
+            review_screen(f, chunks[1], app)
+        }
+        Step::Summary => f.render_widget(summary_screen(width, app), chunks[1]),
+        Step::Installing => installing_screen(f, chunks[1], app),
+        Step::Completion => completion_screen(f, chunks[1], app),
+    }
+
+    let timing = app.install_timing(Instant::now()).filter(|_| app.step == Step::Installing);
+    let status = match (timing, &app.time_sync) {
+        (Some((elapsed, Some(left))), _) => format!(
+            "{} Elapsed {}, about {} left",
+            activity(app),
+            clock(elapsed),
+            clock(left)
+        ),
+        (Some((elapsed, None)), _) => {
+            format!("{} Elapsed {}, estimating...", activity(app), clock(elapsed))
+        }
+        (None, TimeSync::Syncing) => format!("{} Synchronizing the clock…", activity(app)),
+        (None, _) => activity(app),
+    };
+    let mut footer_lines = center_text(
+        status.trim_start(),
+        width,
+        Style::default().fg(theme.fg),
+    );
+    footer_lines.extend(center_text(
+        &footer_hint(&app.keybindings()),
+        width,
+        Style::default().fg(theme.muted),
+    ));
+    let footer = Paragraph::new(footer_lines)
+    .block(Block::default().borders(Borders::TOP).border_style(Style::default().fg(theme.border)));
+    f.render_widget(footer, chunks[2]);
+
+    if !matches!(app.step, Step::Installing | Step::Completion) {
+        let footer = chunks[2];
+        let button_width = NEXT_BUTTON.width() as u16;
+        let button = Rect::new(
+            footer.right().saturating_sub(button_width + 1),
+            footer.y + 1,
+            button_width,
+            1,
+        )
+        .intersection(footer);
+        f.render_widget(
+            Paragraph::new(NEXT_BUTTON)
+                .style(Style::default().fg(theme.success).add_modifier(Modifier::BOLD)),
+            button,
+        );
+        app.next_button = Some(button);
+    }
+
+    if let Some(popup) = &app.popup {
+        let prompt = match popup.retry {
+            Some(Retry::NetworkCheck) => "r: retry   any other key: close",
+            Some(Retry::TimeSync) => "r: try again   any other key: continue anyway",
+            Some(Retry::Install { .. }) => "r: retry from the failed command   a: abort",
+            None => "Press any key to continue",
+        };
+        render_popup(f, &popup.title, &popup.message, prompt, &theme);
+    }
+    if app.show_help {
+        let title = format!("Keys: {}", app.step.label());
+        render_key_help(f, &title, &app.keybindings(), &theme);
+    }
+    if app.confirm_quit {
+        let message = app.quit_warning();
+        render_popup(f, "Quit installer? (y/n)", message, "y: quit   n: keep going", &theme);
+    }
+    if let (Some(typed), Some(name)) = (&app.erase_confirm, app.disk_name()) {
+        render_erase_confirm(f, typed, name, &theme);
+    }
+    if let Some(action) = app.confirm_finish {
+        let (title, answer) = match action {
+            FinishAction::PowerOff => ("Power off now? (y/n)", "power off"),
+            _ => ("Reboot now? (y/n)", "reboot"),
+        };
+        let prompt = format!("y: {answer}   n: back to the menu");
+        let message = "Take the installation medium out once the screen goes dark.";
+        render_popup(f, title, message, &prompt, &theme);
+    }
+    if app.confirm_cancel {
+        render_popup(
+            f,
+            "Cancel the installation? (y/n)",
+            "It stops before the next phase begins. What has already been done to the disk \
+             is not undone.",
+            "y: cancel   n: keep installing",
+            &theme,
+        );
+    }
+}
+
+/// Ask for the name of the disk about to be written to, saying how much
+/// of it is left to type.
+fn render_erase_confirm(f: &mut Frame, typed: &TextInput, name: &str, theme: &Theme) {
+    let area = centered_rect(70, 50, f.area());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.error))
+        .title(format!("Write to /dev/{name}?"));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+    let width = inner.width as usize;
+
+    f.render_widget(
+        Paragraph::new(format!("Everything on {name} may be lost. Type its name to go on:"))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        chunks[0],
+    );
+    typed.render(f, chunks[1], "Disk name", true, theme);
+
+    let left = name.chars().count().saturating_sub(typed.value().chars().count());
+    let (status, color) = if typed.value() == name {
+        ("Enter: begin the installation   Esc: go back".to_string(), theme.warning)
+    } else if !name.starts_with(typed.value()) {
+        ("That is not the disk's name   Esc: go back".to_string(), theme.error)
+    } else if left == 1 {
+        ("1 more character to type   Esc: go back".to_string(), theme.muted)
+    } else {
+        (format!("{left} more characters to type   Esc: go back"), theme.muted)
+    };
+    f.render_widget(
+        Paragraph::new(center_line(&status, width, color, Modifier::BOLD)),
+        chunks[2],
+    );
+}
+
+/// Separator between the steps of the breadcrumb.
+const BREADCRUMB_SEPARATOR: &str = " › ";
+
+/// The wizard's steps with the current one highlighted and the finished
+/// ones dimmed, as many around the current step as fit in `width` columns.
+/// When not even that fits, just the position, e.g. `5/12`.
+fn breadcrumb(app: &AppState, width: usize) -> Line<'static> {
+    let theme = app.theme;
+    let steps: Vec<Step> = Step::ALL.into_iter().filter(|&step| !app.skips(step)).collect();
+    let current = steps.iter().position(|&step| step == app.step).unwrap_or(0);
+    let last = steps.len() - 1;
+
+    // Start from every step and drop the farthest ones until the line fits.
+    for reach in (0..=last).rev() {
+        let (first, end) = (current.saturating_sub(reach), (current + reach).min(last));
+        let mut spans = Vec::new();
+        if first > 0 {
+            spans.push(Span::styled("…", Style::default().fg(theme.muted)));
+        }
+        for (index, step) in steps.iter().enumerate().take(end + 1).skip(first) {
+            if !spans.is_empty() {
+                spans.push(Span::styled(BREADCRUMB_SEPARATOR, Style::default().fg(theme.muted)));
+            }
+            // Plain themes may have no colors to tell the current step apart.
+            let label = if index == current && theme.plain {
+                format!("[{}]", step.label())
+            } else {
+                step.label().to_string()
+            };
+            let style = match index.cmp(&current) {
+                Ordering::Less => Style::default().fg(theme.muted),
+                Ordering::Equal => {
+                    Style::default().fg(theme.accent).add_modifier(Modifier::BOLD)
+                }
+                Ordering::Greater => Style::default().fg(theme.fg),
+            };
+            spans.push(Span::styled(label, style));
+        }
+        if end < last {
+            spans.push(Span::styled(BREADCRUMB_SEPARATOR, Style::default().fg(theme.muted)));
+            spans.push(Span::styled("…", Style::default().fg(theme.muted)));
+        }
+        let line = Line::from(spans);
+        if line.width() <= width {
+            return line;
+        }
+    }
+    Line::from(Span::styled(
+        format!("{}/{}", current + 1, steps.len()),
+        Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn too_small_screen(f: &mut Frame, theme: &Theme) {
+    let area = f.area();
+    // Padding with spaces would be trimmed away by the wrapping, so the
+    // paragraph does the centering, line by wrapped line.
+    let message = Paragraph::new(Line::styled(
+        "Terminal too small — resize to continue",
+        Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+    ))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    let middle = Rect {
+        y: area.y + area.height / 2,
+        height: area.height - area.height / 2,
+        ..area
+    };
+    f.render_widget(message, middle);
+}
+
+/// The footer's reminder of the keys for going on, going back, help and
+/// quitting, taken from the screen's full list so it names the same keys.
+fn footer_hint(keys: &[(&str, &str)]) -> String {
+    let short = |(key, action): &(&str, &str)| {
+        let short = match *action {
+            _ if *key == "Enter" => "next",
+            "go back" => "back",
+            "cancel the installation before its next phase" => "cancel",
+            "show this help; any key closes it" => "help",
+            "quit" | "exit to the live environment" => "quit",
+            _ => return None,
+        };
+        Some(format!("{key}: {short}"))
+    };
+    keys.iter().filter_map(short).collect::<Vec<_>>().join("  ")
+}
+
+/// Left padding needed to center `text` in `width` terminal columns.
+///
+/// Uses the rendered display width rather than the byte length, so emoji,
+/// accented and CJK characters are centered correctly.
+fn center_padding(text: &str, width: usize) -> usize {
+    width.saturating_sub(text.width()) / 2
+}
+
+fn center_text<'a>(text: &str, width: usize, style: Style) -> Vec<Line<'a>> {
+    let padding = center_padding(text, width);
+    vec![Line::from(Span::styled(
+        format!("{:width$}{}", "", text, width = padding),
+        style,
+    ))]
+}
+
+/// The spinner, or when it doesn't turn a static label while something runs.
+fn activity(app: &AppState) -> String {
+    if app.animate {
+        app.spinner.frame().to_string()
+    } else if app.busy() {
+        "Working...".to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// What follows "Checking the connection": an ellipsis, and which try it
+/// is on once the first has failed.
+fn retrying(app: &AppState) -> String {
+    match app.network_retry {
+        Some((attempt, attempts)) => format!(", retrying ({attempt}/{attempts})…"),
+        None => "…".to_string(),
+    }
+}
+
+/// `duration` as minutes and seconds, with hours in front once there are
+/// any, e.g. `4:05` or `1:02:03`.
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// The greeting, under the logo when there is room for it in `height` rows.
+fn welcome_screen(width: usize, height: usize, theme: &Theme) -> Paragraph<'static> {
+    // Room for the greeting below, inside the borders.
+    let mut lines = banner(width, height.saturating_sub(2 + 5), theme);
+    lines.extend([
+        center_line("Welcome to EndeavourOS!", width, theme.title, Modifier::BOLD),
+        center_line("", width, Color::Reset, Modifier::empty()),
+        center_line(
+            "This installer will guide you through the installation process.",
+            width,
+            theme.fg,
+            Modifier::empty(),
+        ),
+        center_line("", width, Color::Reset, Modifier::empty()),
+        center_line(
+            "Press 'Enter' to proceed to the next step.",
+            width,
+            theme.success,
+            Modifier::empty(),
+        ),
+    ]);
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(theme.heading("🌟 Welcome")))
+}
+
+/// The largest banner that fits in `width` columns and `height` rows, as
+/// centered lines followed by a blank one. Plain themes leave it out, as
+/// screen readers would spell the art out character by character.
+fn banner(width: usize, height: usize, theme: &Theme) -> Vec<Line<'static>> {
+    if theme.plain {
+        return Vec::new();
+    }
+    let fits = |art: &&str| {
+        let art_width = art.lines().map(|line| line.width()).max().unwrap_or(0);
+        art_width <= width.saturating_sub(2) && art.lines().count() < height
+    };
+    let Some(art) = [BANNER, COMPACT_BANNER].into_iter().find(fits) else {
+        return Vec::new();
+    };
+    // Every line is moved over by as much as the widest, keeping the art
+    // in one piece.
+    let widest = art.lines().max_by_key(|line| line.width()).unwrap_or_default();
+    let padding = center_padding(widest, width);
+    let style = Style::default().fg(theme.title).add_modifier(Modifier::BOLD);
+    let mut lines: Vec<Line> = art
+        .lines()
+        .map(|line| Line::from(Span::styled(format!("{:padding$}{line}", ""), style)))
+        .collect();
+    lines.push(Line::default());
+    lines
+}
+
+/// What was detected about the machine, one row per probe.
+fn hardware_screen(width: usize, app: &AppState) -> Paragraph<'static> {
+    let theme = app.theme;
+    let hardware = &app.hardware;
+    let unknown = || "Unknown".to_string();
+    let graphics = if hardware.gpus.is_empty() {
+        "None found".to_string()
+    } else {
+        hardware.gpus.iter().map(|gpu| gpu.label()).collect::<Vec<_>>().join(" + ")
+    };
+    let firmware = match app.installer.firmware {
+        Firmware::Uefi => "UEFI",
+        Firmware::Bios => "BIOS",
+    };
+    let rows = [
+        ("Processor", hardware.cpu.clone().unwrap_or_else(unknown)),
+        (
+            "Memory",
+            hardware
+                .memory_mib
+                .map(|mib| format!("{:.1} GiB", mib as f64 / 1024.0))
+                .unwrap_or_else(unknown),
+        ),
+        ("Graphics", graphics),
+        ("Firmware", firmware.to_string()),
+    ];
+    // Padded to the longest row so the colons line up once centered.
+    let rows: Vec<String> =
+        rows.iter().map(|(label, value)| format!("{label:>9}: {value}")).collect();
+    let row_width = rows.iter().map(|row| row.width()).max().unwrap_or(0);
+    let mut lines = vec![
+        center_line("This is what the installer found:", width, theme.accent, Modifier::BOLD),
+        center_line("", width, Color::Reset, Modifier::empty()),
+    ];
+    for row in rows {
+        let padding = row_width - row.width();
+        lines.push(center_line(
+            &format!("{row}{:padding$}", ""),
+            width,
+            theme.fg,
+            Modifier::empty(),
+        ));
+    }
+    lines.extend([
+        center_line("", width, Color::Reset, Modifier::empty()),
+        center_line("Press 'Enter' to continue.", width, theme.success, Modifier::empty()),
+    ]);
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(theme.heading("💻 Hardware")))
+}
+
+fn network_screen(width: usize, app: &AppState) -> Paragraph<'static> {
+    let theme = app.theme;
+    let blank = || center_line("", width, Color::Reset, Modifier::empty());
+    let mut lines = vec![
+        center_line(
+            "The installation downloads packages from the internet.",
+            width,
+            theme.fg,
+            Modifier::empty(),
+        ),
+        blank(),
+    ];
+    match app.connectivity {
+        Connectivity::Unchecked | Connectivity::Checking => lines.push(center_line(
+            format!("{} Checking the connection{}", activity(app), retrying(app)).trim_start(),
+            width,
+            theme.warning,
+            Modifier::empty(),
+        )),
+        Connectivity::Online => {
+            lines.push(center_line("✓ Connected", width, theme.success, Modifier::BOLD));
+        }
+        Connectivity::Offline => lines.extend([
+            center_line("✗ No connection", width, theme.error, Modifier::BOLD),
+            center_line(
+                app.network_error.as_deref().unwrap_or(""),
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            center_line(
+                "Plug in a cable or set up Wi-Fi, then press 'r' to check again.",
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+        ]),
+    }
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(theme.heading("🌐 Network")))
+}
+
+fn wifi_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3), Constraint::Length(1)])
+        .split(list);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Pick a wireless network to get online:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    if let Some(err) = &app.wifi_error {
+        f.render_widget(
+            Paragraph::new(vec![
+                center_line("Could not scan for networks", width, theme.error, Modifier::BOLD),
+                center_line(err, width, theme.error, Modifier::empty()),
+            ]),
+            chunks[0],
+        );
+    } else if app.wifi_networks.is_empty() {
+        f.render_widget(
+            Paragraph::new(center_line("No networks in range", width, theme.error, Modifier::BOLD)),
+            chunks[0],
+        );
+    } else {
+        let items = app
+            .wifi_networks
+            .iter()
+            .map(|network| format!("{:<32} {:>3}%", network.ssid, network.signal))
+            .collect();
+        app.wifi_list.select(Some(app.selected_wifi));
+        app.list_hitbox = Some(render_selectable_list(
+            f,
+            chunks[0],
+            items,
+            &mut app.wifi_list,
+            "📶 Wi-Fi",
+            &theme,
+        ));
+    }
+
+    let field = Rect {
+        x: area.x + area.width / 4,
+        width: area.width / 2,
+        ..chunks[1]
+    };
+    let focused = app.wifi_focus == 1;
+    app.wifi_passphrase.render(f, field, "Passphrase (empty for open networks)", focused, &theme);
+
+    let status = match &app.wifi_status {
+        WifiStatus::Idle => None,
+        WifiStatus::Requested => Some(("Connecting…".to_string(), theme.warning)),
+        WifiStatus::Connecting(ssid) => Some((
+            format!("{} Connecting to {ssid}…", activity(app)).trim_start().to_string(),
+            theme.warning,
+        )),
+        WifiStatus::Connected(ssid) => Some((format!("✓ Connected to {ssid}"), theme.success)),
+        WifiStatus::Failed(err) => Some((format!("✗ Could not connect: {err}"), theme.error)),
+    };
+    if let Some((status, color)) = status {
+        f.render_widget(
+            Paragraph::new(center_line(&status, width, color, Modifier::BOLD)),
+            chunks[2],
+        );
+    }
+
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(
+                "Tab: type the passphrase  Enter: connect or continue  s: skip",
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            center_line(
+                "Already online by cable? This screen is skipped then.",
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+fn mirror_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(list);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[0]);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Pick where to download packages from:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    let mut countries = vec!["All countries".to_string()];
+    countries.extend(mirrors::countries(&app.mirrors).into_iter().map(|country| {
+        let ticked = app.picked_countries.iter().any(|picked| picked == country);
+        format!("[{}] {country}", if ticked { "x" } else { " " })
+    }));
+    app.country_list.select(Some(app.selected_country));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        columns[0],
+        countries,
+        &mut app.country_list,
+        "🗺 Country",
+        &theme,
+    ));
+
+    let shown = app.shown_mirrors();
+    let title = if app.ranked_mirrors.is_some() {
+        "🪞 Mirrors, fastest first"
+    } else {
+        "🪞 Mirrors"
+    };
+    let lines: Vec<Line> = if shown.is_empty() {
+        let reason = app.mirror_error.as_deref().unwrap_or("No mirrors listed");
+        vec![
+            Line::from(Span::styled(reason.to_string(), Style::default().fg(theme.error))),
+            Line::from(Span::styled(
+                "The live system's mirrors will be used.",
+                Style::default().fg(theme.muted),
+            )),
+        ]
+    } else {
+        shown
+            .iter()
+            .map(|mirror| {
+                Line::from(Span::styled(mirror.url.clone(), Style::default().fg(theme.fg)))
+            })
+            .collect()
+    };
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(theme.heading(title))),
+        columns[1],
+    );
+
+    let status = match &app.ranking {
+        Ranking::Idle => None,
+        Ranking::Requested => Some(("Ranking mirrors…".to_string(), theme.warning)),
+        Ranking::Running => Some((
+            format!("{} Ranking mirrors with reflector…", activity(app)).trim_start().to_string(),
+            theme.warning,
+        )),
+        Ranking::Failed(err) => Some((format!("✗ Could not rank mirrors: {err}"), theme.error)),
+    };
+    if let Some((status, color)) = status {
+        f.render_widget(
+            Paragraph::new(center_line(&status, width, color, Modifier::BOLD)),
+            rows[1],
+        );
+    }
+
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(
+                "↑/↓: country  Space: tick  r: rank by speed (slow)  Enter: use these mirrors",
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            center_line(
+                "The list is copied into the new system.",
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+/// Split a list screen into a prompt, the list itself and a hint area.
+fn list_screen_layout(area: Rect) -> [Rect; 3] {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(2),
+        ])
+        .split(area);
+    [chunks[0], chunks[1], chunks[2]]
+}
+
+/// `title` with the active list filter, if any, appended.
+fn filtered_title(title: &str, filter: &str) -> String {
+    if filter.is_empty() {
+        title.to_string()
+    } else {
+        format!("{title} — filter: {filter}")
+    }
+}
+
+/// Render a bordered single-selection list, horizontally centered in `area`,
+/// returning where its rows ended up for mouse hit-testing.
+///
+/// `state` carries the highlighted index and the scroll offset; ratatui
+/// scrolls the viewport so the highlighted item always stays visible.
+fn render_selectable_list(
+    f: &mut Frame,
+    area: Rect,
+    items: Vec<String>,
+    state: &mut ListState,
+    title: &str,
+    theme: &Theme,
+) -> ListHitbox {
+    let items = items.into_iter().map(Line::from).collect();
+    render_styled_list(f, area, items, state, title, theme)
+}
+
+/// [`render_selectable_list`] for items styled by the caller, e.g. greyed
+/// out where they cannot be picked.
+fn render_styled_list(
+    f: &mut Frame,
+    area: Rect,
+    items: Vec<Line<'static>>,
+    state: &mut ListState,
+    title: &str,
+    theme: &Theme,
+) -> ListHitbox {
+    let title = theme.heading(title);
+    let content_width = items.iter().map(Line::width).max().unwrap_or(0) + 2;
+    let width = (content_width.max(title.width() + 2) as u16 + 2).min(area.width);
+    let area = Rect {
+        x: area.x + (area.width - width) / 2,
+        width,
+        ..area
+    };
+    let len = items.len();
+    let list = List::new(items.into_iter().map(ListItem::new))
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(theme.fg))
+        .highlight_style(Style::default().fg(theme.success).add_modifier(Modifier::BOLD))
+        .highlight_symbol("→ ")
+        .highlight_spacing(HighlightSpacing::Always);
+    f.render_stateful_widget(list, area, state);
+    ListHitbox {
+        area: area.inner(Margin::new(1, 1)),
+        offset: state.offset(),
+        len,
+    }
+}
+
+fn language_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line("Select your language:", width, theme.accent, Modifier::BOLD)),
+        prompt,
+    );
+
+    let visible = app.filtered_indices().unwrap_or_default();
+    let items = visible.iter().map(|&i| LANGUAGES[i].name.to_string()).collect();
+    app.language_list.select(visible.iter().position(|&i| i == app.installer.language));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.language_list,
+        &filtered_title("🌐 Language Selection", &app.filter),
+        &theme,
+    ));
+
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(
+                &format!("Locale: {}", app.installer.locale()),
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            center_line(
+                "Type to filter, arrow keys to navigate and 'Enter' to select.",
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+fn locale_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Select the format for numbers, dates and currency:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    // The language's and the formats' locales are generated anyway, so they
+    // show ticked too.
+    let generated = app.installer.generated_locales();
+    let visible = app.filtered_indices().unwrap_or_default();
+    let items = visible
+        .iter()
+        .map(|&i| {
+            let locale = &app.locales[i];
+            let ticked = generated.contains(&locale.as_str());
+            format!("{} {locale}", if ticked { "[x]" } else { "[ ]" })
+        })
+        .collect();
+    let title = format!("📅 Formats ({} generated)", generated.len());
+    app.locale_list.select(visible.iter().position(|&i| i == app.selected_locale));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.locale_list,
+        &filtered_title(&title, &app.filter),
+        &theme,
+    ));
+
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(
+                &format!("Display language: {}", app.installer.locale()),
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            center_line(
+                "Type to filter, 'Space' to also generate a locale and 'Enter' to select.",
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+fn keymap_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Select your keyboard layout:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    if app.keymaps.is_empty() {
+        f.render_widget(
+            Paragraph::new(center_line("No keymaps found", width, theme.error, Modifier::BOLD)),
+            chunks[1],
+        );
+    } else {
+        let visible = app.filtered_indices().unwrap_or_default();
+        let items = visible
+            .iter()
+            .map(|&i| {
+                let keymap = &app.keymaps[i];
+                if app.installer.keymap.as_ref() == Some(keymap) {
+                    format!("{keymap} (selected)")
+                } else {
+                    keymap.clone()
+                }
+            })
+            .collect();
+        app.keymap_list.select(visible.iter().position(|&i| i == app.selected_keymap));
+        app.list_hitbox = Some(render_selectable_list(
+            f,
+            chunks[1],
+            items,
+            &mut app.keymap_list,
+            &filtered_title("⌨ Keyboard Layout", &app.filter),
+            &theme,
+        ));
+    }
+
+    let testing = app.testing_keymap;
+    app.keymap_test.render(f, chunks[2], "Type here to test (Tab)", testing, &theme);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Type to filter, Tab to try the layout out, 'Enter' to select.",
+            width,
+            theme.fg,
+            Modifier::empty(),
+        )),
+        chunks[3],
+    );
+}
+
+fn timezone_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line("Select your timezone:", width, theme.accent, Modifier::BOLD)),
+        prompt,
+    );
+
+    let (names, title, selected) = match &app.timezone_region {
+        None => (
+            timezones::regions(&app.timezones),
+            "🕒 Timezone".to_string(),
+            app.selected_region,
+        ),
+        Some(region) => (
+            timezones::cities(&app.timezones, region),
+            format!("🕒 Timezone — {region}"),
+            app.selected_timezone,
+        ),
+    };
+    let visible = app.filtered_indices().unwrap_or_default();
+    let chosen = app.installer.timezone.as_deref();
+    let items = visible
+        .iter()
+        .map(|&i| names[i])
+        .map(|name| {
+            let zone = match &app.timezone_region {
+                Some(region) => format!("{region}/{name}"),
+                None => name.to_string(),
+            };
+            if chosen == Some(zone.as_str()) {
+                format!("{name} (selected)")
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+    app.timezone_list.select(visible.iter().position(|&i| i == selected));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.timezone_list,
+        &filtered_title(&title, &app.filter),
+        &theme,
+    ));
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Type to filter, ↑/↓ to move, PgUp/PgDn to jump, 'Enter' to open or select.",
+            width,
+            theme.fg,
+            Modifier::empty(),
+        )),
+        hint,
+    );
+}
+
+fn disk_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, mut list, hint] = list_screen_layout(area);
+    if app.encrypt {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .split(list);
+        list = chunks[0];
+        let fields = [&app.passphrase, &app.passphrase_confirm];
+        let titles = ["Passphrase", "Confirm passphrase"];
+        for (i, (input, title)) in fields.into_iter().zip(titles).enumerate() {
+            let field = Rect {
+                x: area.x + area.width / 4,
+                width: area.width / 2,
+                ..chunks[i + 1]
+            };
+            input.render(f, field, title, app.disk_focus == i + 1, &theme);
+        }
+        // Only complain once the user has started typing.
+        let started = !app.passphrase.value().is_empty();
+        if let Some(err) = app.encryption_error().filter(|_| started) {
+            f.render_widget(
+                Paragraph::new(center_line(err, width, theme.error, Modifier::empty())),
+                chunks[3],
+            );
+        }
+    }
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Select the installation disk:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    if let Some(err) = &app.disk_error {
+        f.render_widget(
+            Paragraph::new(vec![
+                center_line("Could not detect disks", width, theme.error, Modifier::BOLD),
+                center_line(err, width, theme.error, Modifier::empty()),
+            ]),
+            list,
+        );
+    } else if app.disks.is_empty() {
+        f.render_widget(
+            Paragraph::new(center_line("No disks detected", width, theme.error, Modifier::BOLD)),
+            list,
+        );
+    } else {
+        let items = app
+            .disks
+            .iter()
+            .map(|disk| {
+                let chosen = app.installer.disk.as_deref() == Some(disk.path().as_str());
+                format!(
+                    "{:<10} {:>8}  {}{}",
+                    disk.name,
+                    disk.size,
+                    disk.model,
+                    if chosen { " (selected)" } else { "" }
+                )
+            })
+            .collect();
+        app.disk_list.select(Some(app.selected_disk));
+        app.list_hitbox = Some(render_selectable_list(
+            f,
+            list,
+            items,
+            &mut app.disk_list,
+            "💽 Disk Selection",
+            &theme,
+        ));
+    }
+
+    let encryption = if app.encrypt {
+        "[x] Encrypt with LUKS (Tab to switch fields, 'e' on the list to turn off)"
+    } else {
+        "[ ] Encrypt with LUKS ('e' to turn on)"
+    };
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(encryption, width, theme.fg, Modifier::empty()),
+            center_line(
+                "Next, choose whether to erase it or keep what is on it.",
+                width,
+                theme.warning,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+fn partition_scheme_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    if app.assigning_mounts {
+        return mount_points_screen(f, area, app);
+    }
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(PartitionScheme::ALL.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "How should the disk be partitioned?",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let items = PartitionScheme::ALL.iter().map(|scheme| scheme.label().to_string()).collect();
+    app.scheme_list.select(Some(app.selected_scheme));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        chunks[1],
+        items,
+        &mut app.scheme_list,
+        "🗂 Partitioning",
+        &theme,
+    ));
+
+    let disk = app.installer.disk.as_deref().unwrap_or("the chosen disk");
+    let blank = || center_line("", width, Color::Reset, Modifier::empty());
+    let scheme = PartitionScheme::ALL[app.selected_scheme];
+    let mut details = vec![center_line(scheme.description(), width, theme.fg, Modifier::empty())];
+    details.extend(match scheme {
+        PartitionScheme::Erase => {
+            let mut lines = vec![
+                blank(),
+                center_line(
+                    &format!("⚠ EVERY PARTITION AND FILE ON {disk} WILL BE DELETED"),
+                    width,
+                    theme.error,
+                    Modifier::BOLD,
+                ),
+                blank(),
+            ];
+            // Swap is only asked for later, so this shows the layout
+            // without it; a swap partition would go before root.
+            let firmware = app.installer.firmware;
+            let layout = install::auto_layout(firmware, SwapConfig::None, app.disk_size_mib());
+            let row = |number: &str, size: &str, role: &str, mount: &str| {
+                format!("{number:<3} {size:>8}  {role:<12} {mount:<6}")
+            };
+            let header = row("#", "Size", "Type", "Mount");
+            lines.push(center_line(&header, width, theme.muted, Modifier::BOLD));
+            for partition in layout {
+                let size = partition.size_mib.map_or("rest".to_string(), mib_label);
+                let (role, mount) = (partition.role.label(), partition.role.mount_point());
+                let line = row(&partition.number.to_string(), &size, role, mount.unwrap_or("-"));
+                lines.push(center_line(&line, width, theme.fg, Modifier::empty()));
+            }
+            lines.push(blank());
+            lines.push(match app.erase_error() {
+                Some(err) => center_line(err, width, theme.error, Modifier::BOLD),
+                None => center_line(
+                    "The filesystem is chosen on the next screen.",
+                    width,
+                    theme.fg,
+                    Modifier::empty(),
+                ),
+            });
+            lines
+        }
+        PartitionScheme::Manual => vec![
+            blank(),
+            center_line(
+                &format!("Next, you say where each partition on {disk} is mounted."),
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+            center_line(
+                "Make the partitions beforehand, e.g. with cfdisk.",
+                width,
+                theme.warning,
+                Modifier::empty(),
+            ),
+        ],
+        PartitionScheme::Alongside => vec![
+            blank(),
+            center_line(
+                &format!("Existing systems on {disk} are kept; free space is used."),
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+            blank(),
+            center_line(
+                &format!("⚠ {}; only a dry run works.", install::ALONGSIDE_UNSUPPORTED),
+                width,
+                theme.warning,
+                Modifier::BOLD,
+            ),
+        ],
+    });
+    f.render_widget(Paragraph::new(details), chunks[2]);
+}
+
+/// The partitions on the chosen disk and the mount point given to each.
+fn mount_points_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+    let disk = app.installer.disk.as_deref().unwrap_or("the chosen disk");
+    f.render_widget(
+        Paragraph::new(center_line(
+            &format!("Where should the partitions on {disk} go?"),
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    if let Some(err) = &app.partitions_error {
+        f.render_widget(
+            Paragraph::new(vec![
+                center_line("Could not list the partitions", width, theme.error, Modifier::BOLD),
+                center_line(err, width, theme.error, Modifier::empty()),
+            ]),
+            list,
+        );
+    } else if app.partitions.is_empty() {
+        f.render_widget(
+            Paragraph::new(center_line(
+                &format!("No partitions on {disk}; make them first, e.g. with cfdisk"),
+                width,
+                theme.error,
+                Modifier::BOLD,
+            )),
+            list,
+        );
+    } else {
+        let items = app
+            .partitions
+            .iter()
+            .zip(&app.partition_mounts)
+            .map(|(partition, mount)| {
+                let or_dash = |text: &str| if text.is_empty() { "-" } else { text }.to_string();
+                format!(
+                    "{:<12} {:>8}  {:<6} {:<12} → {}",
+                    partition.name,
+                    partition.size,
+                    or_dash(&partition.fstype),
+                    or_dash(&partition.label),
+                    mount.map_or("not used", MountPoint::label),
+                )
+            })
+            .collect();
+        app.partition_list.select(Some(app.selected_partition));
+        app.list_hitbox = Some(render_selectable_list(
+            f,
+            list,
+            items,
+            &mut app.partition_list,
+            "🗂 Mount points",
+            &theme,
+        ));
+    }
+
+    let mut lines =
+        vec![center_line("Space changes the mount point.", width, theme.muted, Modifier::empty())];
+    if let Some(err) = app.mount_error().filter(|_| !app.partitions.is_empty()) {
+        lines.push(center_line(err, width, theme.error, Modifier::empty()));
+    }
+    f.render_widget(Paragraph::new(lines), hint);
+}
+
+/// `mib` in whole GiB where it is one, otherwise in MiB.
+fn mib_label(mib: u64) -> String {
+    if mib >= 1024 && mib.is_multiple_of(1024) {
+        format!("{}G", mib / 1024)
+    } else if mib >= 1024 {
+        format!("{:.1}G", mib as f64 / 1024.0)
+    } else {
+        format!("{mib}M")
+    }
+}
+
+fn filesystem_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(Filesystem::ALL.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Format the new system with:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let items = Filesystem::ALL
+        .iter()
+        .map(|fs| format!("{:<6} {}", fs.label(), fs.description()))
+        .collect();
+    app.filesystem_list.select(Some(app.selected_filesystem));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        chunks[1],
+        items,
+        &mut app.filesystem_list,
+        "🗄 Filesystem",
+        &theme,
+    ));
+
+    let installer = &app.installer;
+    let filesystem = Filesystem::ALL[app.selected_filesystem];
+    let option = |text: String| center_line(&text, width, theme.fg, Modifier::empty());
+    let checkbox = |checked: bool| if checked { "[x]" } else { "[ ]" };
+    let mut lines = vec![center_line("", width, Color::Reset, Modifier::empty())];
+    if filesystem == Filesystem::Btrfs {
+        lines.push(option(format!(
+            "{} Subvolumes @ and @home  (s)",
+            checkbox(installer.btrfs_subvolumes)
+        )));
+        lines.push(option(format!(
+            "{} Automatic snapshots     (n)",
+            checkbox(installer.btrfs_snapshots)
+        )));
+    }
+    if let Some(error) = app.filesystem_error() {
+        lines.push(center_line(error, width, theme.error, Modifier::empty()));
+    }
+    f.render_widget(Paragraph::new(lines), chunks[2]);
+}
+
+fn swap_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let options = app.swap_options();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(options.len() as u16 + 2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Swap space, for when memory runs out:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let items = options
+        .iter()
+        .map(|swap| format!("{:<14} {}", swap.label(), swap.description()))
+        .collect();
+    app.swap_list.select(Some(app.selected_swap));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        chunks[1],
+        items,
+        &mut app.swap_list,
+        "💤 Swap",
+        &theme,
+    ));
+
+    let sized = options[app.selected_swap].size_gib().is_some();
+    if sized {
+        let field = Rect {
+            x: area.x + area.width / 4,
+            width: area.width / 2,
+            ..chunks[2]
+        };
+        app.swap_size.render(f, field, "Size, e.g. 4G", true, &theme);
+    }
+
+    let mut lines = Vec::new();
+    if sized {
+        lines.push(center_line(
+            &format!("Suggested for this machine's memory: {} GiB", app.suggested_swap_gib),
+            width,
+            theme.muted,
+            Modifier::empty(),
+        ));
+    }
+    if let Some(error) = app.swap_error() {
+        lines.push(center_line(error, width, theme.error, Modifier::empty()));
+    }
+    f.render_widget(Paragraph::new(lines), chunks[3]);
+}
+
+fn bootloader_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Start the new system with:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    let items = Bootloader::ALL
+        .iter()
+        .map(|bootloader| {
+            let item = format!("{:<13} {}", bootloader.label(), bootloader.description());
+            if bootloader.supports(app.installer.firmware) {
+                Line::from(item)
+            } else {
+                Line::styled(item, Style::default().fg(theme.muted))
+            }
+        })
+        .collect();
+    app.bootloader_list.select(Some(app.selected_bootloader));
+    app.list_hitbox = Some(render_styled_list(
+        f,
+        list,
+        items,
+        &mut app.bootloader_list,
+        "🥾 Bootloader",
+        &theme,
+    ));
+
+    if app.installer.firmware == Firmware::Bios {
+        f.render_widget(
+            Paragraph::new(vec![
+                center_line(
+                    "This machine started in legacy BIOS mode.",
+                    width,
+                    theme.muted,
+                    Modifier::empty(),
+                ),
+                center_line(
+                    "systemd-boot needs UEFI firmware, so only GRUB can be picked.",
+                    width,
+                    theme.muted,
+                    Modifier::empty(),
+                ),
+            ]),
+            hint,
+        );
+    }
+}
+
+fn hostname_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Choose a hostname for this computer:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let field = Rect {
+        x: area.x + area.width / 4,
+        width: area.width / 2,
+        ..chunks[1]
+    };
+    app.hostname.render(f, field, "🏷 Hostname", true, &theme);
+
+    // Only complain once the user has started typing.
+    let hostname = app.hostname.value();
+    let error = validate_hostname(hostname).err().filter(|_| !hostname.is_empty());
+    if let Some(err) = error {
+        f.render_widget(
+            Paragraph::new(center_line(err, width, theme.error, Modifier::empty())),
+            chunks[2],
+        );
+    }
+}
+
+fn user_account_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(if app.separate_root { 3 } else { 0 }),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Create your user account (Tab to switch fields):",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let fields = [
+        &app.username,
+        &app.full_name,
+        &app.password,
+        &app.password_confirm,
+    ];
+    for (i, (input, title)) in fields.into_iter().zip(ACCOUNT_FIELDS).enumerate() {
+        let field = Rect {
+            x: area.x + area.width / 4,
+            width: area.width / 2,
+            ..chunks[i + 1]
+        };
+        input.render(f, field, title, i == app.account_focus, &theme);
+    }
+
+    let toggle_focused = app.account_focus == ACCOUNT_FIELDS.len();
+    let mark = |chosen: bool| if chosen { "[x]" } else { "[ ]" };
+    let mut toggle = format!(
+        "{} Disable root (use sudo)   {} Separate root password",
+        mark(!app.separate_root),
+        mark(app.separate_root),
+    );
+    if toggle_focused {
+        toggle.push_str("  (Space)");
+    }
+    let (color, modifier) = if toggle_focused {
+        (theme.accent, Modifier::BOLD)
+    } else {
+        (theme.fg, Modifier::empty())
+    };
+    f.render_widget(Paragraph::new(center_line(&toggle, width, color, modifier)), chunks[5]);
+
+    // The root password and its confirmation share a row to save height.
+    if app.separate_root {
+        let fields = [&app.root_password, &app.root_password_confirm];
+        for (i, (input, title)) in fields.into_iter().zip(ROOT_FIELDS).enumerate() {
+            let field = Rect {
+                x: area.x + area.width / 4 + i as u16 * (area.width / 4),
+                width: area.width / 4,
+                ..chunks[6]
+            };
+            let focused = app.account_focus == ACCOUNT_FIELDS.len() + 1 + i;
+            input.render(f, field, title, focused, &theme);
+        }
+    }
+
+    // Hold back the empty-field complaints until the user has typed something.
+    let started = !app.username.value().is_empty() || !app.password_confirm.value().is_empty();
+    if let Some(err) = app.account_error().filter(|_| started) {
+        f.render_widget(
+            Paragraph::new(center_line(err, width, theme.error, Modifier::empty())),
+            chunks[7],
+        );
+    }
+}
+
+fn desktop_selection_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Select a desktop environment:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    let items = DESKTOPS.iter().map(|desktop| desktop.name.to_string()).collect();
+    app.desktop_list.select(Some(app.installer.desktop));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.desktop_list,
+        "🖥 Desktop",
+        &theme,
+    ));
+
+    let desktop = app.installer.desktop();
+    let packages = if desktop.packages.is_empty() {
+        "Installs: base system only".to_string()
+    } else {
+        format!("Installs: {}", desktop.packages.join(" "))
+    };
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(desktop.description, width, theme.fg, Modifier::empty()),
+            center_line(&packages, width, theme.muted, Modifier::empty()),
+        ]),
+        hint,
+    );
+}
+
+/// Graphics drivers for the cards on the hardware screen, the usual choice
+/// for them highlighted at first.
+fn driver_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    let found = app.hardware.gpus.iter().map(|gpu| gpu.label()).collect::<Vec<_>>();
+    let prompt_text = if found.is_empty() {
+        "No graphics card was recognised; generic drivers will work:".to_string()
+    } else {
+        format!("Select drivers for your {} graphics:", found.join(" + "))
+    };
+    f.render_widget(
+        Paragraph::new(center_line(&prompt_text, width, theme.accent, Modifier::BOLD)),
+        prompt,
+    );
+
+    let options = app.driver_options();
+    let items = options.iter().map(|driver| driver.name.to_string()).collect();
+    app.driver_list.select(Some(app.selected_driver));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.driver_list,
+        "🎮 Graphics",
+        &theme,
+    ));
+
+    let driver = options[app.selected_driver];
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(driver.description, width, theme.fg, Modifier::empty()),
+            center_line(
+                &format!("Installs: {}", driver.packages.join(" ")),
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+/// The package catalog with a checkbox on each entry, filtered by what has
+/// been typed.
+fn packages_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let [prompt, list, hint] = list_screen_layout(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Tick any extra software to install:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        prompt,
+    );
+
+    let extra = &app.installer.extra_packages;
+    let visible = app.filtered_indices().unwrap_or_default();
+    let items = visible
+        .iter()
+        .map(|&i| {
+            let ticked = extra.iter().any(|package| package == PACKAGES[i].name);
+            format!("{} {}", if ticked { "[x]" } else { "[ ]" }, PACKAGES[i].name)
+        })
+        .collect();
+    let title = format!("📦 Packages ({} selected)", extra.len());
+    app.package_list.select(visible.iter().position(|&i| i == app.selected_package));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        list,
+        items,
+        &mut app.package_list,
+        &filtered_title(&title, &app.filter),
+        &theme,
+    ));
+
+    let description = if visible.contains(&app.selected_package) {
+        PACKAGES[app.selected_package].description
+    } else {
+        "No package matches the filter."
+    };
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(description, width, theme.muted, Modifier::empty()),
+            center_line(
+                "Type to filter, 'Space' to tick and 'Enter' to continue.",
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+        ]),
+        hint,
+    );
+}
+
+/// A field for the names of packages the catalog doesn't have, warning
+/// about any that cannot be package names.
+fn extra_packages_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Any other packages to install, separated by spaces:",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let names: Vec<&str> = app.package_names.value().split_whitespace().collect();
+    let (valid, invalid): (Vec<&str>, Vec<&str>) =
+        names.into_iter().partition(|name| validate_package_name(name).is_ok());
+    let field = Rect {
+        x: area.x + area.width / 8,
+        width: area.width * 3 / 4,
+        ..chunks[1]
+    };
+    let title = format!("📦 Packages ({} entered)", valid.len());
+    app.package_names.render(f, field, &title, true, &theme);
+
+    // Not worth stopping for; they are just left out.
+    let warning = if invalid.is_empty() {
+        "Leave the field empty to skip this step.".to_string()
+    } else {
+        format!("Not package names, left out: {}", invalid.join(" "))
+    };
+    let color = if invalid.is_empty() { theme.muted } else { theme.warning };
+    f.render_widget(
+        Paragraph::new(center_line(&warning, width, color, Modifier::empty())),
+        chunks[2],
+    );
+}
+
+fn summary_screen(width: usize, app: &AppState) -> Paragraph<'static> {
+    let theme = app.theme;
+    let installer = &app.installer;
+    let not_set = || "Not set".to_string();
+    let rows = [
+        ("Language", language_summary(installer)),
+        ("Keyboard", installer.keymap.clone().unwrap_or_else(not_set)),
+        ("Timezone", installer.timezone.clone().unwrap_or_else(not_set)),
+        ("Disk", installer.disk.clone().unwrap_or_else(not_set)),
+        ("Hostname", installer.hostname.clone().unwrap_or_else(not_set)),
+        ("Username", installer.username.clone().unwrap_or_else(not_set)),
+        ("Root", root_summary(installer).to_string()),
+        ("Desktop", desktop_summary(installer)),
+        ("Partitions", partitions_summary(installer)),
+        ("Bootloader", installer.bootloader.label().to_string()),
+        ("Swap", swap_summary(installer.swap)),
+        ("Mirrors", mirrors_summary(installer)),
+    ];
+
+    let mut lines = vec![
+        center_line("Review your choices:", width, theme.accent, Modifier::BOLD),
+    ];
+    for (label, value) in rows {
+        lines.push(center_line(
+            &format!("{label:>10}: {value:<24}"),
+            width,
+            theme.fg,
+            Modifier::empty(),
+        ));
+    }
+    let disk = installer.disk.as_deref().unwrap_or("the target disk");
+    lines.extend([
+        center_line("", width, Color::Reset, Modifier::empty()),
+        center_line(
+            &match installer.partition_scheme {
+                PartitionScheme::Erase => format!("⚠ ALL DATA ON {disk} WILL BE ERASED"),
+                PartitionScheme::Manual => format!("⚠ Existing partitions on {disk} are used"),
+                PartitionScheme::Alongside => format!("⚠ {disk} will be resized; back it up first"),
+            },
+            width,
+            theme.error,
+            Modifier::BOLD,
+        ),
+        match (app.connectivity, &app.time_sync) {
+            (Connectivity::Online, TimeSync::Failed(_)) => center_line(
+                "⚠ The clock is not synchronized; press Enter to install anyway",
+                width,
+                theme.warning,
+                Modifier::empty(),
+            ),
+            (Connectivity::Online, TimeSync::Unsynced | TimeSync::Syncing) => center_line(
+                "Synchronizing the clock…",
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            (Connectivity::Online, TimeSync::Synced) => {
+                center_line("", width, Color::Reset, Modifier::empty())
+            }
+            (Connectivity::Unchecked | Connectivity::Checking, _) => center_line(
+                format!("{} Checking the internet connection{}", activity(app), retrying(app))
+                    .trim_start(),
+                width,
+                theme.muted,
+                Modifier::empty(),
+            ),
+            (Connectivity::Offline, _) => center_line(
+                "⚠ No internet connection; press 'r' to check again",
+                width,
+                theme.warning,
+                Modifier::empty(),
+            ),
+        },
+    ]);
+    let prompt = match &app.answers_error {
+        Some(error) => {
+            lines.push(center_line(error, width, theme.error, Modifier::empty()));
+            "Press Enter to BEGIN INSTALLATION anyway"
+        }
+        None => "Press Enter to BEGIN INSTALLATION",
+    };
+    lines.extend([
+        center_line(prompt, width, theme.warning, Modifier::BOLD),
+        center_line(
+            "or '←' to go back and edit, 'c' to see the commands, 'f' the fstab.",
+            width,
+            theme.warning,
+            Modifier::empty(),
+        ),
+    ]);
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(theme.heading("📋 Summary")))
+}
+
+/// The commands the installation will run or the fstab it leads to, in a
+/// panel that scrolls.
+fn review_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let [review, hint] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .areas(area);
+    let (key, title, lines) = match (&mut app.command_review, &mut app.fstab_review) {
+        (Some(commands), _) => ('c', "📜 Commands to run", commands),
+        (None, Some(fstab)) => ('f', "📄 /etc/fstab", fstab),
+        (None, None) => return,
+    };
+    lines.render(f, review, title, &theme);
+    f.render_widget(
+        Paragraph::new(center_line(
+            &format!("PgUp/PgDn: scroll  {key}: back to the summary  Enter: BEGIN INSTALLATION"),
+            area.width as usize,
+            theme.warning,
+            Modifier::empty(),
+        )),
+        hint,
+    );
+}
+
+/// How many mirrors were chosen, e.g. `3 servers`.
+fn mirrors_summary(installer: &InstallerState) -> String {
+    match &installer.mirrorlist {
+        Some(list) => {
+            let servers = mirrors::parse_mirrorlist(list).len();
+            format!("{servers} server{}", if servers == 1 { "" } else { "s" })
+        }
+        None => "Live system's list".to_string(),
+    }
+}
+
+/// The swap choice, e.g. `Swap file, 8 GiB`.
+fn swap_summary(swap: SwapConfig) -> String {
+    match swap.size_gib() {
+        Some(size) => format!("{}, {size} GiB", swap.label()),
+        None => swap.label().to_string(),
+    }
+}
+
+/// The display language, the formats if they follow another locale, and
+/// how many more locales are generated.
+fn language_summary(installer: &InstallerState) -> String {
+    let name = LANGUAGES[installer.language].name;
+    let summary = if installer.format_locale() == installer.locale() {
+        name.to_string()
+    } else {
+        format!("{name}, {} formats", installer.format_locale())
+    };
+    let shown = if installer.format_locale() == installer.locale() { 1 } else { 2 };
+    match installer.generated_locales().len() - shown {
+        0 => summary,
+        1 => format!("{summary}, 1 more locale"),
+        count => format!("{summary}, {count} more locales"),
+    }
+}
+
+/// The desktop and the graphics drivers going with it, e.g. `GNOME, nvidia-open`.
+fn desktop_summary(installer: &InstallerState) -> String {
+    let mut summary = installer.desktop().name.to_string();
+    if let Some(driver) = installer.driver {
+        summary = format!("{summary}, {}", driver.name);
+    }
+    match installer.extra_packages.len() + installer.typed_packages.len() {
+        0 => summary,
+        1 => format!("{summary}, 1 extra package"),
+        count => format!("{summary}, {count} extra packages"),
+    }
+}
+
+/// Whether root is locked or has its own password.
+fn root_summary(installer: &InstallerState) -> &'static str {
+    if installer.root_password.is_some() {
+        "Separate root password"
+    } else {
+        "Locked, use sudo"
+    }
+}
+
+/// How the disk will be laid out, e.g. `Erase entire disk (btrfs, encrypted)`.
+fn partitions_summary(installer: &InstallerState) -> String {
+    let encrypted = installer.encryption.is_some();
+    match installer.partition_scheme {
+        PartitionScheme::Erase => {}
+        PartitionScheme::Alongside if encrypted => {
+            return format!("{} (encrypted)", PartitionScheme::Alongside.label());
+        }
+        scheme => return scheme.label().to_string(),
+    }
+    let mut details = vec![installer.filesystem.label().to_string()];
+    if encrypted {
+        details.push("encrypted".into());
+    }
+    if installer.filesystem == Filesystem::Btrfs {
+        if installer.btrfs_subvolumes {
+            details.push("subvolumes".into());
+        }
+        if installer.btrfs_snapshots {
+            details.push("snapshots".into());
+        }
+    }
+    format!("{} ({})", PartitionScheme::Erase.label(), details.join(", "))
+}
+
+fn installing_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let width = area.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(5),
+            Constraint::Length(2),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Installing EndeavourOS…",
+            width,
+            theme.accent,
+            Modifier::BOLD,
+        )),
+        chunks[0],
+    );
+
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(theme.heading("📦 Progress")))
+        .gauge_style(Style::default().fg(theme.success).bg(theme.bg))
+        .ratio(app.progress)
+        .label(format!("{} — {:.0}%", app.phase.label(), app.progress * 100.0));
+    f.render_widget(gauge, chunks[1]);
+
+    let status = match &app.install_error {
+        Some(error) => center_line(
+            &format!("✗ Installation failed: {error}"),
+            width,
+            theme.error,
+            Modifier::BOLD,
+        ),
+        None if app.cancelling() => center_line(
+            "Cancelling: stopping before the next phase…",
+            width,
+            theme.warning,
+            Modifier::BOLD,
+        ),
+        None => center_line(&app.install_message, width, theme.fg, Modifier::empty()),
+    };
+    f.render_widget(Paragraph::new(status), chunks[2]);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(chunks[3]);
+
+    let phase_width = bottom[0].width as usize;
+    let current = InstallPhase::ALL.iter().position(|&phase| phase == app.phase);
+    let phases: Vec<Line> = InstallPhase::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, phase)| {
+            let (marker, color) = match current {
+                Some(current) if i < current => ("✓", theme.success),
+                Some(current) if i == current => ("→", theme.title),
+                _ => (" ", theme.muted),
+            };
+            center_line(
+                &format!("{marker} {:<24}", phase.label()),
+                phase_width,
+                color,
+                Modifier::empty(),
+            )
+        })
+        .collect();
+    f.render_widget(Paragraph::new(phases), bottom[0]);
+
+    app.install_log.render(f, bottom[1], "📜 Log", &theme);
+}
+
+/// The installation is done; what to do next is picked from a menu.
+fn completion_screen(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let theme = app.theme;
+    let block = Block::default().borders(Borders::ALL).title(theme.heading("✅ Completion"));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let width = inner.width as usize;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(vec![
+            center_line(
+                &theme.heading("Installation Complete! 🎉"),
+                width,
+                theme.success,
+                Modifier::BOLD,
+            ),
+            center_line("", width, Color::Reset, Modifier::empty()),
+            center_line(
+                "You can now restart your system and enjoy EndeavourOS.",
+                width,
+                theme.fg,
+                Modifier::empty(),
+            ),
+        ]),
+        chunks[0],
+    );
+
+    let items = FinishAction::ALL.iter().map(|action| action.label().to_string()).collect();
+    app.action_list.select(Some(app.selected_action));
+    app.list_hitbox = Some(render_selectable_list(
+        f,
+        chunks[1],
+        items,
+        &mut app.action_list,
+        "What next?",
+        &theme,
+    ));
+    f.render_widget(
+        Paragraph::new(center_line(
+            "Arrow keys to choose, 'Enter' to go ahead, 'Q' to exit.",
+            width,
+            theme.title,
+            Modifier::empty(),
+        )),
+        chunks[2],
+    );
+
+    if app.viewing_log {
+        let pane = centered_rect(90, 90, f.area());
+        f.render_widget(Clear, pane);
+        app.install_log.render(f, pane, "📜 Installation log (Esc to close)", &theme);
+    }
+}
+
+fn center_line<'a>(
+    text: &str,
+    width: usize,
+    fg: Color,
+    modifier: Modifier,
+) -> Line<'a> {
+    let padding = center_padding(text, width);
+    Line::from(Span::styled(
+        format!("{:width$}{}", "", text, width = padding),
+        Style::default().fg(fg).add_modifier(modifier),
+    ))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Popup;
+    use crate::system::disks::{DiskInfo, Partition};
+    use crate::widgets::spinner::{Spinner, BRAILLE};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn render(app: &mut AppState, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut out = String::new();
+        for y in 0..buffer.area.height {
+            let mut x = 0;
+            while x < buffer.area.width {
+                let symbol = buffer[(x, y)].symbol();
+                out.push_str(symbol);
+                // Wide glyphs cover the following cell too.
+                x += symbol.width().max(1) as u16;
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn language_screen_marks_selected_language() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.installer.language = 1;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ Français"));
+        assert!(!screen.contains("→ English"));
+    }
+
+    #[test]
+    fn language_list_scrolls_to_keep_selection_visible() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.installer.language = LANGUAGES.len() - 1;
+        let screen = render(&mut app, 60, 16);
+        assert!(screen.contains(LANGUAGES[LANGUAGES.len() - 1].name));
+        assert!(!screen.contains(LANGUAGES[0].name));
+    }
+
+    #[test]
+    fn disk_screen_without_disks_says_so() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("No disks detected"));
+    }
+
+    #[test]
+    fn disk_screen_shows_detection_error() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        app.disk_error = Some("lsblk failed".into());
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Could not detect disks"));
+        assert!(screen.contains("lsblk failed"));
+    }
+
+    #[test]
+    fn popup_is_drawn_over_the_screen() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        app.popup = Some(Popup {
+            title: "Installation failed".into(),
+            message: "pacstrap exited with status 1".into(),
+            back_to: Step::Summary,
+            retry: None,
+        });
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Installation failed"));
+        assert!(screen.contains("pacstrap exited with status 1"));
+        assert!(screen.contains("Press any key to continue"));
+    }
+
+    #[test]
+    fn quitting_mid_install_warns_about_the_disk() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let (_tx, rx) = std::sync::mpsc::channel();
+        app.attach_install(rx);
+        app.confirm_quit = true;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Quit installer? (y/n)"));
+        assert!(screen.contains("inconsistent state"));
+
+        app.install_rx = None;
+        app.step = Step::Desktop;
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("inconsistent state"));
+    }
+
+    #[test]
+    fn cancelling_the_install_is_confirmed_then_shown() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        app.confirm_cancel = true;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Cancel the installation? (y/n)"));
+
+        app.confirm_cancel = false;
+        app.install_cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(render(&mut app, 80, 24).contains("stopping before the next phase"));
+    }
+
+    #[test]
+    fn list_rows_and_next_button_are_clickable() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+
+        let list = app.list_hitbox.unwrap();
+        let row = (0..buffer.area.height)
+            .find(|&y| {
+                let line: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+                line.contains("Français")
+            })
+            .unwrap();
+        assert_eq!(list.index_at(list.area.x, row), Some(1));
+
+        let button = app.next_button.unwrap();
+        let label: String = (button.x..button.right())
+            .map(|x| buffer[(x, button.y)].symbol())
+            .collect();
+        assert_eq!(label, NEXT_BUTTON);
+
+        app.step = Step::Installing;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert_eq!(app.list_hitbox, None);
+        assert_eq!(app.next_button, None);
+    }
+
+    #[test]
+    fn filter_narrows_list_and_shows_in_title() {
+        let mut app = AppState::new();
+        app.step = Step::Language;
+        app.filter = "ñ".into();
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Language Selection — filter: ñ"));
+        assert!(screen.contains("Español"));
+        assert!(!screen.contains("English"));
+    }
+
+    #[test]
+    fn hostname_screen_flags_invalid_input() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        app.hostname.set_value("-bad");
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("cannot start or end with a hyphen"));
+    }
+
+    #[test]
+    fn desktop_screen_describes_highlighted_option() {
+        let mut app = AppState::new();
+        app.step = Step::Desktop;
+        app.installer.desktop = 2;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ XFCE"));
+        assert!(screen.contains(DESKTOPS[2].description));
+    }
+
+    #[test]
+    fn install_log_scrolls_to_newest_entries() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        for i in 1..=20 {
+            app.install_log.push(format!("task {i:02}"));
+        }
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("task 20"));
+        assert!(!screen.contains("task 01"));
+
+        app.on_key(crossterm::event::KeyCode::PageUp);
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("task 20"));
+        assert!(screen.contains("lines back, PgDn to follow"));
+    }
+
+    #[test]
+    fn installing_footer_shows_the_time_taken_and_left() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        let minute_ago = Instant::now() - Duration::from_secs(60);
+        app.install_started = Some((minute_ago, 0.0));
+        assert!(render(&mut app, 80, 24).contains("Elapsed 1:00, estimating..."));
+
+        app.report_progress(InstallPhase::Pacstrap, 0.25);
+        assert!(render(&mut app, 80, 24).contains("Elapsed 1:00, about 3:00 left"));
+        assert_eq!(clock(Duration::from_secs(3723)), "1:02:03");
+    }
+
+    #[test]
+    fn gauge_fill_matches_progress() {
+        let mut app = AppState::new();
+        app.step = Step::Installing;
+        app.report_progress(InstallPhase::Format, 0.25);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+
+        // The gauge's top border sits below the header and the prompt.
+        let top = (0..buffer.area.height)
+            .find(|&y| buffer[(0, y)].symbol() == "┌")
+            .unwrap();
+        let row = top + 1;
+        let inner = buffer.area.width - 2;
+        let filled = (1..=inner)
+            .filter(|&x| buffer[(x, row)].symbol() == "█")
+            .count();
+        assert_eq!(filled, (f64::from(inner) * 0.25).round() as usize);
+
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Formatting partitions — 25%"));
+    }
+
+    #[test]
+    fn summary_lists_choices_and_warns() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/sda".into());
+        app.installer.username = Some("alice".into());
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Username: alice"));
+        assert!(screen.contains("Keyboard: Not set"));
+        assert!(screen.contains("ALL DATA ON /dev/sda WILL BE ERASED"));
+    }
+
+    #[test]
+    fn breadcrumb_highlights_the_current_step() {
+        let mut app = AppState::new();
+        app.step = Step::Disk;
+        let mut terminal = Terminal::new(TestBackend::new(220, 24)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = (0..buffer.area.height)
+            .find(|&y| (0..buffer.area.width).any(|x| buffer[(x, y)].symbol() == "›"))
+            .unwrap();
+        let line: String = (0..buffer.area.width).map(|x| buffer[(x, row)].symbol()).collect();
+        assert!(line.contains(
+            "Welcome › Hardware › Wi-Fi › Network › Mirrors › Language › Formats › Keyboard › \
+             Timezone › Disk"
+        ));
+        let style_of = |label: &str| {
+            let x = line.find(label).unwrap();
+            let x = line[..x].chars().count() as u16;
+            buffer[(x, row)].style()
+        };
+        assert_eq!(style_of("Disk").fg, Some(app.theme.accent));
+        assert!(style_of("Disk").add_modifier.contains(Modifier::BOLD));
+        assert_eq!(style_of("Welcome").fg, Some(app.theme.muted));
+        assert_eq!(style_of("Summary").fg, Some(app.theme.fg));
+    }
+
+    #[test]
+    fn theme_colors_the_whole_screen() {
+        let mut app = AppState::new();
+        app.theme = Theme::LIGHT;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        for corner in [(0, 0), (79, 23), (40, 12)] {
+            assert_eq!(buffer[corner].bg, Color::White);
+        }
+        let welcome = (0..24)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .find(|&(x, y)| buffer[(x, y)].symbol() == "W")
+            .unwrap();
+        assert_eq!(buffer[welcome].fg, Theme::LIGHT.title);
+    }
+
+    #[test]
+    fn encryption_asks_for_a_passphrase() {
+        let mut app = AppState::new();
+        app.load_disks(Ok(vec![crate::system::disks::DiskInfo {
+            name: "sda".into(),
+            size: "512G".into(),
+            model: "Samsung SSD".into(),
+        }]));
+        app.step = Step::Disk;
+        assert!(render(&mut app, 80, 24).contains("[ ] Encrypt with LUKS"));
+
+        app.on_key(crossterm::event::KeyCode::Char('e'));
+        for c in "secret".chars() {
+            app.on_key(crossterm::event::KeyCode::Char(c));
+        }
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("[x] Encrypt with LUKS"));
+        assert!(screen.contains("Confirm passphrase"));
+        assert!(screen.contains("Passphrase must be at least 8 characters"));
+        assert!(!screen.contains("secret"));
+    }
+
+    #[test]
+    fn separate_root_asks_for_its_password() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("[x] Disable root (use sudo)"));
+        assert!(!screen.contains("Root password"));
+
+        app.account_focus = ACCOUNT_FIELDS.len();
+        app.on_key(crossterm::event::KeyCode::Char(' '));
+        app.on_key(crossterm::event::KeyCode::Tab);
+        for c in "toor".chars() {
+            app.on_key(crossterm::event::KeyCode::Char(c));
+        }
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("[x] Separate root password"));
+        assert!(screen.contains("Root password") && screen.contains("Confirm"));
+        assert!(!screen.contains("toor"));
+    }
+
+    #[test]
+    fn focused_field_has_an_accent_border() {
+        let mut app = AppState::new();
+        app.step = Step::UserAccount;
+        app.account_focus = 1;
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let corner_of = |title: &str| {
+            let row = (0..buffer.area.height)
+                .find(|&y| {
+                    let line: String =
+                        (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+                    line.contains(title)
+                })
+                .unwrap();
+            let x = (0..buffer.area.width).find(|&x| buffer[(x, row)].symbol() == "┌").unwrap();
+            buffer[(x, row)].fg
+        };
+        assert_eq!(corner_of("Full name"), app.theme.accent);
+        assert_eq!(corner_of("Username"), app.theme.border);
+    }
+
+    #[test]
+    fn summary_can_list_the_commands() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/sda".into());
+        app.on_key(crossterm::event::KeyCode::Char('c'));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Commands to run"));
+        assert!(screen.contains("sgdisk --zap-all /dev/sda"));
+        assert!(!screen.contains("Review your choices"));
+
+        app.on_key(crossterm::event::KeyCode::PageDown);
+        app.on_key(crossterm::event::KeyCode::PageDown);
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("bootctl install"));
+    }
+
+    #[test]
+    fn summary_can_show_the_fstab() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/sda".into());
+        app.on_key(crossterm::event::KeyCode::Char('f'));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("/etc/fstab"));
+        assert!(screen.contains("/dev/sda2 / ext4 rw,relatime 0 1"));
+        assert!(screen.contains("f: back to the summary"));
+    }
+
+    #[test]
+    fn no_emoji_mode_keeps_titles_plain_and_centered() {
+        let is_emoji = |c: char| ('\u{1F000}'..='\u{1FFFF}').contains(&c);
+        let mut app = AppState::new();
+        app.accessible = true;
+        app.set_theme(Theme::DARK);
+        for step in Step::ALL {
+            app.step = step;
+            let screen = render(&mut app, 80, 24);
+            assert!(!screen.chars().any(is_emoji), "{step:?}:\n{screen}");
+        }
+        app.step = Step::Welcome;
+        let screen = render(&mut app, 80, 24);
+        let header = screen.lines().next().unwrap();
+        let title = "EndeavourOS Installer";
+        assert_eq!(header.find(title), Some(center_padding(title, 80)));
+    }
+
+    #[test]
+    fn monochrome_is_plain_text() {
+        let mut app = AppState::new();
+        app.theme = Theme::MONOCHROME;
+        app.animate = false;
+        app.step = Step::Network;
+        let (_tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Working..."));
+        assert!(screen.contains("[Network]"));
+        assert!(!screen.contains('🌐') && !screen.contains('🚀'));
+
+        app.on_tick();
+        assert_eq!(app.spinner, Spinner::braille());
+    }
+
+    #[test]
+    fn plain_drawing_turns_an_ascii_spinner() {
+        let mut app = AppState::new();
+        app.accessible = true;
+        app.set_theme(Theme::DARK);
+        app.step = Step::Network;
+        let (_tx, rx) = std::sync::mpsc::channel();
+        app.attach_network_check(rx);
+        app.on_tick();
+        assert_eq!(app.spinner.frame(), "/");
+        assert!(!render(&mut app, 80, 24).contains("Working..."));
+
+        app.on_key(crossterm::event::KeyCode::F(3));
+        assert_eq!(app.spinner.frame(), BRAILLE[0]);
+    }
+
+    #[test]
+    fn breadcrumb_shrinks_to_fit() {
+        let mut app = AppState::new();
+        app.step = Step::Hostname;
+        let narrow = breadcrumb(&app, 50).to_string();
+        assert!(narrow.contains("Hostname"));
+        assert!(narrow.starts_with('…') && narrow.ends_with('…'));
+        assert!(narrow.chars().count() <= 50);
+        assert_eq!(breadcrumb(&app, 5).to_string(), "15/23");
+
+        app.installer.partition_scheme = crate::choices::PartitionScheme::Manual;
+        assert_eq!(breadcrumb(&app, 5).to_string(), "14/22");
+        app.wired = true;
+        assert_eq!(breadcrumb(&app, 5).to_string(), "13/21");
+    }
+
+    #[test]
+    fn wifi_screen_lists_networks_and_status() {
+        let mut app = AppState::new();
+        app.step = Step::Wifi;
+        app.load_wifi(Ok(vec![crate::system::wifi::WifiNetwork {
+            ssid: "Home".into(),
+            signal: 80,
+        }]));
+        app.wifi_status = WifiStatus::Connected("Home".into());
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ Home"));
+        assert!(screen.contains("80%"));
+        assert!(screen.contains("Passphrase"));
+        assert!(screen.contains("✓ Connected to Home"));
+
+        app.load_wifi(Err(std::io::Error::other("nmcli not found")));
+        assert!(render(&mut app, 80, 24).contains("nmcli not found"));
+    }
+
+    #[test]
+    fn key_help_shows_the_steps_bindings() {
+        let mut app = AppState::new();
+        app.step = Step::Filesystem;
+        app.show_help = true;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Keys: Filesystem"));
+        assert!(screen.contains("btrfs snapshots on or off"));
+        assert!(screen.contains("any key closes it"));
+    }
+
+    #[test]
+    fn key_help_only_lists_keys_the_screen_uses() {
+        let mut app = AppState::new();
+        app.show_help = true;
+        app.step = Step::Language;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("move the highlight"));
+        assert!(screen.contains("filter the list"));
+        assert!(!screen.contains("q/Ctrl+Q") && screen.contains("Ctrl+Q"));
+
+        app.step = Step::Hostname;
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("q/Ctrl+Q") && screen.contains("Ctrl+Q"));
+
+        app.step = Step::Welcome;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Keys: Welcome"));
+        assert!(!screen.contains("move the highlight"));
+        assert!(!screen.contains("go back"));
+    }
+
+    #[test]
+    fn mirror_screen_lists_countries_and_servers() {
+        let mut app = AppState::new();
+        app.step = Step::Mirrors;
+        app.load_mirrors(Ok(crate::system::mirrors::parse_mirrorlist(
+            "## Germany\nServer = https://de.example/$repo\n",
+        )));
+        app.ranking = Ranking::Failed("reflector not found".into());
+        let screen = render(&mut app, 100, 24);
+        assert!(screen.contains("→ All countries"));
+        assert!(screen.contains("[ ] Germany"));
+        assert!(screen.contains("https://de.example/$repo"));
+        assert!(screen.contains("reflector not found"));
+
+        app.picked_countries = vec!["Germany".into()];
+        assert!(render(&mut app, 100, 24).contains("[x] Germany"));
+
+        app.load_mirrors(Err(std::io::Error::other("no mirrorlist")));
+        let screen = render(&mut app, 100, 24);
+        assert!(screen.contains("no mirrorlist"));
+        assert!(screen.contains("live system's mirrors will be used"));
+    }
+
+    #[test]
+    fn hardware_screen_lists_what_was_found() {
+        use crate::system::hardware::GpuVendor;
+        let mut app = AppState::new();
+        app.step = Step::Hardware;
+        app.installer.firmware = Firmware::Bios;
+        app.load_memory(Ok(7802));
+        app.load_hardware(
+            Ok("Intel(R) Core(TM) i7-8550U CPU @ 1.80GHz".into()),
+            Ok(vec![GpuVendor::Intel, GpuVendor::Nvidia]),
+        );
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Processor: Intel(R) Core(TM) i7-8550U CPU @ 1.80GHz"));
+        assert!(screen.contains("   Memory: 7.6 GiB"));
+        assert!(screen.contains(" Graphics: Intel + NVIDIA"));
+        assert!(screen.contains(" Firmware: BIOS"));
+        let column = |label: &str| screen.lines().find_map(|line| line.find(label));
+        assert_eq!(column("Processor:").map(|c| c + 3), column("Firmware:").map(|c| c + 2));
+
+        app.load_hardware(Err(io::ErrorKind::NotFound.into()), Err(io::ErrorKind::NotFound.into()));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Processor: Unknown"));
+        assert!(screen.contains("Graphics: None found"));
+        app.on_enter();
+        assert_eq!(app.step, Step::Wifi);
+    }
+
+    #[test]
+    fn driver_screen_offers_drivers_for_the_card() {
+        use crate::system::hardware::GpuVendor;
+        let mut app = AppState::new();
+        app.step = Step::Graphics;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("No graphics card was recognised"));
+        assert!(screen.contains("Installs: mesa"));
+
+        app.load_hardware(Ok("cpu".into()), Ok(vec![GpuVendor::Amd]));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Select drivers for your AMD graphics:"));
+        assert!(screen.contains("mesa + xf86-video-amdgpu"));
+        assert!(screen.contains("Installs: mesa vulkan-radeon"));
+    }
+
+    #[test]
+    fn packages_screen_counts_the_ticked_packages() {
+        let mut app = AppState::new();
+        app.step = Step::Packages;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Packages (0 selected)"));
+        assert!(screen.contains("→ [ ] base-devel"));
+
+        app.installer.extra_packages = vec!["git".into(), "htop".into()];
+        app.filter = "git".into();
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Packages (2 selected) — filter: git"));
+        assert!(screen.contains("[x] git"));
+        assert!(!screen.contains("htop"));
+
+        app.step = Step::Summary;
+        assert!(render(&mut app, 80, 24).contains("Desktop: KDE Plasma, 2 extra packages"));
+    }
+
+    #[test]
+    fn extra_packages_screen_warns_about_invalid_names() {
+        let mut app = AppState::new();
+        app.step = Step::ExtraPackages;
+        assert!(render(&mut app, 80, 24).contains("Packages (0 entered)"));
+
+        app.package_names.set_value("tmux Steam ncdu");
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Packages (2 entered)"));
+        assert!(screen.contains("Not package names, left out: Steam"));
+    }
+
+    #[test]
+    fn disk_name_prompt_counts_what_is_left_to_type() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer.disk = Some("/dev/nvme0n1".into());
+        let mut typed = TextInput::new();
+        typed.set_value("nvme");
+        app.erase_confirm = Some(typed);
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Write to /dev/nvme0n1?"));
+        assert!(screen.contains("3 more characters to type"));
+
+        app.erase_confirm.as_mut().unwrap().set_value("nvme0n1");
+        assert!(render(&mut app, 80, 24).contains("Enter: begin the installation"));
+        app.erase_confirm.as_mut().unwrap().set_value("sda");
+        assert!(render(&mut app, 80, 24).contains("That is not the disk's name"));
+    }
+
+    #[test]
+    fn network_screen_reports_the_check() {
+        let mut app = AppState::new();
+        app.step = Step::Network;
+        assert!(render(&mut app, 80, 24).contains("Checking the connection…"));
+        app.network_retry = Some((2, 5));
+        assert!(render(&mut app, 80, 24).contains("Checking the connection, retrying (2/5)…"));
+        app.network_retry = None;
+        app.connectivity = Connectivity::Online;
+        assert!(render(&mut app, 80, 24).contains("✓ Connected"));
+        app.connectivity = Connectivity::Offline;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("✗ No connection"));
+        assert!(screen.contains("press 'r' to check again"));
+
+        app.step = Step::Summary;
+        assert!(render(&mut app, 80, 24).contains("No internet connection"));
+        app.connectivity = Connectivity::Online;
+        assert!(!render(&mut app, 80, 24).contains("No internet connection"));
+    }
+
+    #[test]
+    fn summary_reports_whether_the_clock_synced() {
+        use crate::system::clock::TimeSyncError;
+        let sync = |app: &mut AppState, result| {
+            let (tx, rx) = std::sync::mpsc::channel();
+            app.attach_time_sync(rx);
+            tx.send(result).unwrap();
+        };
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.connectivity = Connectivity::Online;
+        app.animate = false;
+        sync(&mut app, Ok(()));
+        assert!(render(&mut app, 80, 24).contains("Working... Synchronizing the clock…"));
+        app.on_tick();
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("Synchronizing") && !screen.contains("not synchronized"));
+
+        sync(&mut app, Err(TimeSyncError::Failed("no NTP servers".into())));
+        app.on_tick();
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Clock not synchronized"));
+        assert!(screen.contains("r: try again   any other key: continue anyway"));
+        app.popup = None;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("The clock is not synchronized; press Enter to install anyway"));
+    }
+
+    #[test]
+    fn bios_explains_why_only_grub_is_offered() {
+        let mut app = AppState::new();
+        app.step = Step::Bootloader;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ systemd-boot"));
+        assert!(!screen.contains("needs UEFI firmware"));
+
+        app.installer.firmware = Firmware::Bios;
+        app.selected_bootloader = 1;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ GRUB"));
+        assert!(screen.contains("systemd-boot  Simple and fast"));
+        assert!(screen.contains("only GRUB can be picked"));
+    }
+
+    #[test]
+    fn resize_redraws_at_the_new_size() {
+        let mut app = AppState::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        assert!(redraw(&mut terminal, &mut app).unwrap());
+        assert!(!redraw(&mut terminal, &mut app).unwrap());
+
+        terminal.backend_mut().resize(30, 8);
+        app.on_resize(30, 8);
+        assert!(redraw(&mut terminal, &mut app).unwrap());
+        let buffer = terminal.backend().buffer();
+        assert_eq!(buffer.area, Rect::new(0, 0, 30, 8));
+        let screen: String = (0..8)
+            .flat_map(|y| (0..30).map(move |x| (x, y)))
+            .map(|(x, y)| buffer[(x, y)].symbol())
+            .collect();
+        assert!(screen.contains("Terminal too small"));
+    }
+
+    #[test]
+    fn header_stays_centered_through_resizes() {
+        let mut app = AppState::new();
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        for (width, height) in [(80, 24), (50, 12), (120, 30)] {
+            terminal.backend_mut().resize(width, height);
+            app.on_resize(width, height);
+            assert!(redraw(&mut terminal, &mut app).unwrap());
+            let buffer = terminal.backend().buffer();
+            let mut header = String::new();
+            let mut x = 0;
+            while x < width {
+                let symbol = buffer[(x, 0)].symbol();
+                header.push_str(symbol);
+                x += symbol.width().max(1) as u16;
+            }
+            let text = header.trim();
+            assert_eq!(text, "🚀 EndeavourOS Installer");
+            let left = header.len() - header.trim_start().len();
+            let right = usize::from(width) - left - text.width();
+            assert!(left.abs_diff(right) <= 1, "{width}: {left} vs {right}");
+        }
+    }
+
+    #[test]
+    fn erase_scheme_warns_about_data_loss() {
+        let mut app = AppState::new();
+        app.step = Step::PartitionScheme;
+        app.installer.disk = Some("/dev/sda".into());
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ Erase entire disk"));
+        assert!(screen.contains("EVERY PARTITION AND FILE ON /dev/sda WILL BE DELETED"));
+
+        app.selected_scheme = 1;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("where each partition on /dev/sda is mounted"));
+        assert!(!screen.contains("WILL BE DELETED"));
+
+        app.selected_scheme = 2;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Installing alongside is not supported yet"));
+    }
+
+    #[test]
+    fn erase_scheme_previews_the_layout() {
+        let mut app = AppState::new();
+        app.step = Step::PartitionScheme;
+        app.installer.firmware = Firmware::Uefi;
+        app.disks = vec![DiskInfo { name: "sda".into(), size: "64G".into(), model: String::new() }];
+        app.installer.disk = Some("/dev/sda".into());
+        let screen = render(&mut app, 80, 30);
+        assert!(screen.contains("1       512M  EFI system   /boot"));
+        assert!(screen.contains("2      63.5G  root         /"));
+
+        app.disks[0].size = "8G".into();
+        let screen = render(&mut app, 80, 30);
+        assert!(screen.contains("The disk is too small for the system"));
+    }
+
+    #[test]
+    fn mount_point_screen_lists_the_partitions() {
+        let mut app = AppState::new();
+        app.step = Step::PartitionScheme;
+        app.installer.firmware = Firmware::Bios;
+        app.installer.disk = Some("/dev/sda".into());
+        app.assigning_mounts = true;
+        let partition = |name: &str, fstype: &str, label: &str| Partition {
+            name: name.into(),
+            size: "100G".into(),
+            fstype: fstype.into(),
+            label: label.into(),
+            uuid: String::new(),
+        };
+        let partitions = vec![partition("sda1", "ext4", "arch"), partition("sda2", "", "")];
+        app.load_partitions("/dev/sda".into(), Ok(partitions));
+        app.partition_mounts[0] = Some(MountPoint::Root);
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Where should the partitions on /dev/sda go?"));
+        assert!(screen.contains("sda1             100G  ext4   arch         → /"));
+        assert!(screen.contains("sda2             100G  -      -            → not used"));
+        assert!(!screen.contains("has to be mounted"));
+
+        app.load_partitions("/dev/sda".into(), Err(io::Error::other("lsblk failed")));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Could not list the partitions"));
+    }
+
+    #[test]
+    fn swap_size_field_shows_for_sized_options() {
+        let mut app = AppState::new();
+        app.step = Step::Swap;
+        app.load_memory(Ok(2048));
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("→ No swap"));
+        assert!(screen.contains("zram"));
+        assert!(!screen.contains("Size, e.g. 4G"));
+
+        app.selected_swap = 1;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Size, e.g. 4G"));
+        assert!(screen.contains("Suggested for this machine's memory: 4 GiB"));
+
+        app.step = Step::Summary;
+        app.installer.swap = SwapConfig::Partition { size_gib: 4 };
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Swap: Swap partition, 4 GiB"));
+        assert!(screen.contains("BEGIN INSTALLATION"));
+    }
+
+    #[test]
+    fn btrfs_shows_its_options_and_what_is_wrong() {
+        let mut app = AppState::new();
+        app.step = Step::Filesystem;
+        app.installer.btrfs_snapshots = true;
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("Automatic snapshots"));
+
+        app.selected_filesystem = 1;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("[ ] Subvolumes @ and @home"));
+        assert!(screen.contains("[x] Automatic snapshots"));
+        assert!(screen.contains("Snapshots need the @ and @home subvolume layout"));
+    }
+
+    #[test]
+    fn formats_screen_ticks_the_generated_locales() {
+        let mut app = AppState::new();
+        let locales = ["de_DE.UTF-8", "en_GB.UTF-8", "en_US.UTF-8", "ja_JP.UTF-8"];
+        app.load_locales(Ok(locales.map(String::from).to_vec()));
+        app.step = Step::Locale;
+        app.installer.extra_locales = vec!["ja_JP.UTF-8".into()];
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Formats (2 generated)"));
+        assert!(screen.contains("[ ] de_DE.UTF-8"));
+        assert!(screen.contains("[x] en_US.UTF-8"));
+        assert!(screen.contains("[x] ja_JP.UTF-8"));
+
+        app.step = Step::Summary;
+        assert!(render(&mut app, 80, 24).contains("Language: English, 1 more locale"));
+    }
+
+    #[test]
+    fn summary_shows_every_chosen_field() {
+        let mut app = AppState::new();
+        app.step = Step::Summary;
+        app.installer = InstallerState {
+            language: 1,
+            format_locale: Some("fr_CA.UTF-8".into()),
+            keymap: Some("fr".into()),
+            timezone: Some("Europe/Paris".into()),
+            disk: Some("/dev/nvme0n1".into()),
+            hostname: Some("atelier".into()),
+            username: Some("marie".into()),
+            desktop: 1,
+            ..InstallerState::default()
+        };
+        let screen = render(&mut app, 80, 24);
+        for row in [
+            "Language: Français, fr_CA.UTF-8 formats",
+            "Keyboard: fr",
+            "Timezone: Europe/Paris",
+            "Disk: /dev/nvme0n1",
+            "Hostname: atelier",
+            "Username: marie",
+            "Desktop: GNOME",
+            "Partitions: Erase entire disk (ext4)",
+        ] {
+            assert!(screen.contains(row), "missing {row:?}");
+        }
+        assert!(screen.contains("Press Enter to BEGIN INSTALLATION"));
+    }
+
+    #[test]
+    fn footer_mentions_back_navigation() {
+        let mut app = AppState::new();
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Enter: next  ?/F1: help  q/Ctrl+Q: quit"));
+        assert!(!screen.contains(": back"));
+
+        app.step = Step::Hardware;
+        assert!(render(&mut app, 80, 24).contains("Esc/←/Backspace: back"));
+        // The hostname field takes letters, ← and Backspace.
+        app.step = Step::Hostname;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Enter: next  Esc: back  F1: help  Ctrl+Q: quit"));
+    }
+
+    #[test]
+    fn tiny_terminal_shows_resize_warning() {
+        let screen = render(&mut AppState::new(), 36, 6);
+        assert!(screen.contains("Terminal too small"));
+        assert!(!screen.contains("EndeavourOS Installer"));
+    }
+
+    #[test]
+    fn resize_warning_is_centered() {
+        let message = "Terminal too small — resize to continue";
+        let screen = render(&mut AppState::new(), 100, 6);
+        let line = screen.lines().find(|line| line.contains(message)).unwrap();
+        let left = line.find(message).unwrap();
+        let right = 100 - left - message.width();
+        assert!(left.abs_diff(right) <= 1, "{left} columns left, {right} right");
+
+        // Each wrapped line is centered on its own.
+        let screen = render(&mut AppState::new(), 36, 6);
+        let line = screen.lines().find(|line| line.contains("Terminal too small")).unwrap();
+        assert_eq!(line.find('T'), Some(3), "{line:?}");
+    }
+
+    #[test]
+    fn welcome_banner_shrinks_to_fit_and_skips_plain_themes() {
+        let mut app = AppState::new();
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains(r"|_____|_| |_|\__,_|"));
+        assert!(screen.contains("Press 'Enter' to proceed"));
+
+        let screen = render(&mut app, 60, 24);
+        assert!(!screen.contains("|_____|"));
+        assert!(screen.contains("| EndeavourOS |"));
+        assert!(screen.contains("Press 'Enter' to proceed"));
+
+        // Too short for either: the greeting keeps its rows.
+        let screen = render(&mut app, 80, 12);
+        assert!(!screen.contains("| EndeavourOS |"));
+        assert!(screen.contains("Press 'Enter' to proceed"));
+
+        app.theme = Theme::DARK.accessible();
+        let screen = render(&mut app, 80, 24);
+        assert!(!screen.contains("|_____|") && !screen.contains("| EndeavourOS |"));
+        assert!(screen.contains("Welcome to EndeavourOS!"));
+    }
+
+    #[test]
+    fn completion_menu_lists_every_action() {
+        let mut app = AppState::new();
+        app.step = Step::Completion;
+        let screen = render(&mut app, 80, 24);
+        for action in FinishAction::ALL {
+            assert!(screen.contains(action.label()), "{action:?}");
+        }
+        assert!(screen.contains("→ Reboot now"));
+
+        app.on_key(crossterm::event::KeyCode::Down);
+        assert!(render(&mut app, 80, 24).contains("→ Power off"));
+        app.on_key(crossterm::event::KeyCode::Enter);
+        assert!(render(&mut app, 80, 24).contains("Power off now? (y/n)"));
+
+        app.install_log.push("pacstrap done");
+        app.viewing_log = true;
+        let screen = render(&mut app, 80, 24);
+        assert!(screen.contains("Installation log") && screen.contains("pacstrap done"));
+    }
+
+    #[test]
+    fn screens_survive_degenerate_widths() {
+        let mut app = AppState::new();
+        for width in [0, 5] {
+            let _ = welcome_screen(width, 0, &Theme::DARK);
+            let _ = network_screen(width, &app);
+            let _ = summary_screen(width, &app);
+            let mut terminal = Terminal::new(TestBackend::new(width as u16, 3)).unwrap();
+            terminal.draw(|f| completion_screen(f, f.area(), &mut app)).unwrap();
+            let _ = breadcrumb(&app, width);
+            let _ = center_text("🚀 EndeavourOS Installer", width, Style::default());
+        }
+        for (width, height) in [(0, 0), (1, 1), (5, 24), (80, 1)] {
+            let screen = render(&mut app, width, height);
+            assert!(!screen.contains("EndeavourOS Installer"), "{width}x{height}");
+        }
+    }
+
+    #[test]
+    fn minimum_size_terminal_shows_regular_ui() {
+        let screen = render(&mut AppState::new(), MIN_WIDTH, MIN_HEIGHT);
+        assert!(!screen.contains("Terminal too small"));
+    }
+
+    #[test]
+    fn center_padding_ascii() {
+        assert_eq!(center_padding("Hello", 20), 7);
+    }
+
+    #[test]
+    fn center_padding_accented() {
+        // Precomposed "ç"/"ñ" are two bytes but one column wide.
+        assert_eq!(center_padding("Français", 20), 6);
+        assert_eq!(center_padding("Español", 21), 7);
+    }
+
+    #[test]
+    fn center_padding_combining() {
+        // "n" followed by U+0303 COMBINING TILDE renders as a single column.
+        assert_eq!(center_padding("Espan\u{0303}ol", 21), 7);
+    }
+
+    #[test]
+    fn center_padding_emoji() {
+        // "🚀" is four bytes but two columns wide.
+        assert_eq!(center_padding("🚀 EndeavourOS Installer", 40), 8);
+    }
+
+    #[test]
+    fn center_padding_cjk() {
+        // Each CJK ideograph occupies two columns.
+        assert_eq!(center_padding("中文", 10), 3);
+    }
+
+    #[test]
+    fn center_padding_wider_than_area() {
+        assert_eq!(center_padding("EndeavourOS", 4), 0);
+    }
+}
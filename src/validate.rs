@@ -0,0 +1,260 @@
+//! Validation of values the user types in.
+
+/// Check `hostname` against RFC 1123 label rules: 1–63 lowercase ASCII
+/// letters, digits or hyphens, not starting or ending with a hyphen.
+pub fn validate_hostname(hostname: &str) -> Result<(), &'static str> {
+    if hostname.is_empty() {
+        return Err("Hostname cannot be empty");
+    }
+    if hostname.len() > 63 {
+        return Err("Hostname must be at most 63 characters");
+    }
+    if !hostname
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err("Use only lowercase letters, digits and hyphens");
+    }
+    if hostname.starts_with('-') || hostname.ends_with('-') {
+        return Err("Hostname cannot start or end with a hyphen");
+    }
+    Ok(())
+}
+
+/// Check that `username` is a lowercase alphanumeric login of at most 32
+/// characters starting with a letter.
+pub fn validate_username(username: &str) -> Result<(), &'static str> {
+    let Some(first) = username.chars().next() else {
+        return Err("Username cannot be empty");
+    };
+    if username.len() > 32 {
+        return Err("Username must be at most 32 characters");
+    }
+    if !first.is_ascii_lowercase() {
+        return Err("Username must start with a lowercase letter");
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+    {
+        return Err("Use only lowercase letters and digits");
+    }
+    Ok(())
+}
+
+/// Check `name` against pacman's package naming rules: lowercase ASCII
+/// letters, digits and `@._+-`, not starting with a hyphen or a dot.
+pub fn validate_package_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("Package name cannot be empty");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "@._+-".contains(c))
+    {
+        return Err("Use only lowercase letters, digits and @._+-");
+    }
+    if name.starts_with(['-', '.']) {
+        return Err("Package name cannot start with a hyphen or a dot");
+    }
+    Ok(())
+}
+
+/// Check that `zone` looks like a zoneinfo name such as `Europe/Berlin`:
+/// ASCII letters, digits and `_+-`, in parts separated by single slashes.
+pub fn validate_timezone(zone: &str) -> Result<(), &'static str> {
+    if zone.split('/').any(str::is_empty) {
+        return Err("Timezone must be a name like Europe/Berlin");
+    }
+    if !zone.chars().all(|c| c.is_ascii_alphanumeric() || "_+-/".contains(c)) {
+        return Err("Use only letters, digits and _+-/");
+    }
+    Ok(())
+}
+
+/// Check that `keymap` looks like a console keymap name such as `de-latin1`:
+/// ASCII letters, digits and `_.-`, not starting with a hyphen or a dot.
+pub fn validate_keymap(keymap: &str) -> Result<(), &'static str> {
+    if keymap.is_empty() {
+        return Err("Keymap cannot be empty");
+    }
+    if !keymap.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)) {
+        return Err("Use only letters, digits and _.-");
+    }
+    if keymap.starts_with(['-', '.']) {
+        return Err("Keymap cannot start with a hyphen or a dot");
+    }
+    Ok(())
+}
+
+/// Check that `disk` is a device path under `/dev/`, such as `/dev/sda` or
+/// `/dev/disk/by-id/…`, without `..` or characters device names never have.
+pub fn validate_disk(disk: &str) -> Result<(), &'static str> {
+    let Some(name) = disk.strip_prefix("/dev/") else {
+        return Err("Disk must be a device under /dev/");
+    };
+    if name.split('/').any(|part| part.is_empty() || part == "..") {
+        return Err("Disk must be a device name like /dev/sda");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || "_.:-/".contains(c)) {
+        return Err("Use only letters, digits and _.:-/");
+    }
+    Ok(())
+}
+
+/// Shortest disk encryption passphrase accepted.
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Check that `passphrase` is long enough to be worth encrypting with.
+pub fn validate_passphrase(passphrase: &str) -> Result<(), &'static str> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err("Passphrase must be at least 8 characters");
+    }
+    Ok(())
+}
+
+/// Largest swap partition or file accepted, in GiB.
+pub const MAX_SWAP_GIB: u32 = 256;
+
+/// Parse `size` as a whole number of GiB of swap, 1 to [`MAX_SWAP_GIB`].
+/// It may end in a unit, `M`, `G` or `T` (`MiB` and so on work too), and
+/// is in GiB without one.
+pub fn validate_swap_size(size: &str) -> Result<u32, &'static str> {
+    if size.is_empty() {
+        return Err("Swap size cannot be empty");
+    }
+    let digits = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (number, unit) = size.split_at(digits);
+    let mib_per_unit: u64 = match unit.to_ascii_uppercase().as_str() {
+        "M" | "MB" | "MIB" => 1,
+        "" | "G" | "GB" | "GIB" => 1024,
+        "T" | "TB" | "TIB" => 1024 * 1024,
+        _ => return Err("Swap size must be a number with an optional unit, e.g. 4G"),
+    };
+    if number.is_empty() {
+        return Err("Swap size must be a number with an optional unit, e.g. 4G");
+    }
+    let Some(mib) = number.parse::<u64>().ok().and_then(|n| n.checked_mul(mib_per_unit)) else {
+        return Err("Swap size must be at most 256 GiB");
+    };
+    if mib == 0 {
+        return Err("Swap size must be at least 1 GiB");
+    }
+    if mib % 1024 != 0 {
+        return Err("Swap size must be a whole number of GiB");
+    }
+    match u32::try_from(mib / 1024) {
+        Ok(gib) if gib <= MAX_SWAP_GIB => Ok(gib),
+        _ => Err("Swap size must be at most 256 GiB"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_hostnames() {
+        assert!(validate_hostname("endeavouros").is_ok());
+        assert!(validate_hostname("my-laptop-2").is_ok());
+        assert!(validate_hostname("a").is_ok());
+        assert!(validate_hostname(&"a".repeat(63)).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_hostnames() {
+        assert!(validate_hostname("").is_err());
+        assert!(validate_hostname(&"a".repeat(64)).is_err());
+        assert!(validate_hostname("My-Laptop").is_err());
+        assert!(validate_hostname("my_laptop").is_err());
+        assert!(validate_hostname("my.laptop").is_err());
+        assert!(validate_hostname("-laptop").is_err());
+        assert!(validate_hostname("laptop-").is_err());
+    }
+
+    #[test]
+    fn accepts_valid_usernames() {
+        assert!(validate_username("alice").is_ok());
+        assert!(validate_username("user42").is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_usernames() {
+        assert!(validate_username("").is_err());
+        assert!(validate_username("42user").is_err());
+        assert!(validate_username("Alice").is_err());
+        assert!(validate_username("al ice").is_err());
+        assert!(validate_username("al-ice").is_err());
+        assert!(validate_username(&"a".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn package_names_follow_pacman_rules() {
+        assert!(validate_package_name("base-devel").is_ok());
+        assert!(validate_package_name("gtk2+").is_ok());
+        assert!(validate_package_name("python3.12").is_ok());
+        assert!(validate_package_name("").is_err());
+        assert!(validate_package_name("Firefox").is_err());
+        assert!(validate_package_name("vim;reboot").is_err());
+        assert!(validate_package_name("-vim").is_err());
+        assert!(validate_package_name(".vim").is_err());
+    }
+
+    #[test]
+    fn timezones_keymaps_and_disks_are_plain_names() {
+        assert!(validate_timezone("Europe/Berlin").is_ok());
+        assert!(validate_timezone("America/Argentina/Buenos_Aires").is_ok());
+        assert!(validate_timezone("Etc/GMT+5").is_ok());
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("UTC; rm -rf /").is_err());
+        assert!(validate_timezone("/etc/passwd").is_err());
+        assert!(validate_timezone("").is_err());
+
+        assert!(validate_keymap("de-latin1").is_ok());
+        assert!(validate_keymap("mac-us.iso15").is_ok());
+        assert!(validate_keymap("de $(reboot)").is_err());
+        assert!(validate_keymap("-us").is_err());
+        assert!(validate_keymap("").is_err());
+
+        assert!(validate_disk("/dev/nvme0n1").is_ok());
+        assert!(validate_disk("/dev/disk/by-id/nvme-eui.0025_38b5").is_ok());
+        assert!(validate_disk("sda").is_err());
+        assert!(validate_disk("/dev/").is_err());
+        assert!(validate_disk("/dev/../etc/shadow").is_err());
+        assert!(validate_disk("/dev/sda; reboot").is_err());
+    }
+
+    #[test]
+    fn short_passphrases_are_rejected() {
+        assert!(validate_passphrase("").is_err());
+        assert!(validate_passphrase("1234567").is_err());
+        assert!(validate_passphrase("12345678").is_ok());
+        assert!(validate_passphrase("ñññññññ").is_err());
+    }
+
+    #[test]
+    fn swap_size_is_a_whole_number_of_gib() {
+        assert_eq!(validate_swap_size("8"), Ok(8));
+        assert_eq!(validate_swap_size("256"), Ok(256));
+        assert!(validate_swap_size("").is_err());
+        assert!(validate_swap_size("0").is_err());
+        assert!(validate_swap_size("257").is_err());
+        assert!(validate_swap_size("99999999999").is_err());
+        assert!(validate_swap_size("4.5").is_err());
+        assert!(validate_swap_size("-4").is_err());
+    }
+
+    #[test]
+    fn swap_size_takes_a_unit() {
+        assert_eq!(validate_swap_size("4G"), Ok(4));
+        assert_eq!(validate_swap_size("4gib"), Ok(4));
+        assert_eq!(validate_swap_size("2048M"), Ok(2));
+        assert_eq!(validate_swap_size("0T"), Err("Swap size must be at least 1 GiB"));
+        assert_eq!(validate_swap_size("1T"), Err("Swap size must be at most 256 GiB"));
+        assert_eq!(validate_swap_size("99999999999T"), Err("Swap size must be at most 256 GiB"));
+        assert_eq!(validate_swap_size("1500M"), Err("Swap size must be a whole number of GiB"));
+        assert!(validate_swap_size("G").is_err());
+        assert!(validate_swap_size("4 G").is_err());
+        assert!(validate_swap_size("4X").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! A bounded log that follows new lines and can be scrolled back through.
+
+use std::collections::VecDeque;
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::theme::Theme;
+
+/// The most recent lines of a log, oldest first. Once full, every new line
+/// pushes out the oldest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogBuffer {
+    lines: VecDeque<String>,
+    capacity: usize,
+    /// How many lines the view is scrolled back from the newest; 0 keeps
+    /// following new lines as they arrive.
+    scroll: usize,
+    /// Rows the log was last drawn in, which is how far a page scrolls.
+    page: usize,
+}
+
+impl LogBuffer {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            scroll: 0,
+            page: 1,
+        }
+    }
+
+    /// Add `line` at the bottom. A view scrolled back stays on the lines it
+    /// shows rather than jumping.
+    pub fn push(&mut self, line: impl Into<String>) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.into());
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.max_scroll());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll = 0;
+    }
+
+    /// Whether the newest line is in view, so new ones will be too.
+    pub fn is_following(&self) -> bool {
+        self.scroll == 0
+    }
+
+    /// Scroll one page back towards older lines.
+    pub fn page_up(&mut self) {
+        self.scroll = (self.scroll + self.page).min(self.max_scroll());
+    }
+
+    /// Show the oldest lines, for reading from the start.
+    pub fn scroll_to_top(&mut self) {
+        // Drawing trims this to the last full page.
+        self.scroll = self.lines.len();
+    }
+
+    /// Scroll one page forward; reaching the bottom follows new lines again.
+    pub fn page_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(self.page);
+    }
+
+    /// Furthest back the view can go while still filling a page.
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.page)
+    }
+
+    /// The lines that fit in `height` rows at the current scroll position.
+    pub fn visible(&self, height: usize) -> impl Iterator<Item = &str> {
+        // Scrolled all the way back, the first lines still fill the page.
+        let end = self.lines.len() - self.scroll.min(self.lines.len().saturating_sub(height));
+        let start = end.saturating_sub(height);
+        self.lines.range(start..end).map(String::as_str)
+    }
+
+    /// Draw the log in a bordered panel filling `area`, noting in the title
+    /// when it is scrolled back.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, title: &str, theme: &Theme) {
+        self.page = usize::from(area.height.saturating_sub(2)).max(1);
+        self.scroll = self.scroll.min(self.max_scroll());
+        let title = if self.is_following() {
+            theme.heading(title)
+        } else {
+            format!("{} — {} lines back, PgDn to follow", theme.heading(title), self.scroll)
+        };
+        let lines: Vec<Line> = self
+            .visible(self.page)
+            .map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(theme.fg))))
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(count: usize, capacity: usize) -> LogBuffer {
+        let mut log = LogBuffer::with_capacity(capacity);
+        for i in 1..=count {
+            log.push(format!("line {i}"));
+        }
+        log
+    }
+
+    #[test]
+    fn oldest_lines_make_room_for_new_ones() {
+        let log = filled(5, 3);
+        assert_eq!(log.visible(usize::MAX).collect::<Vec<_>>(), ["line 3", "line 4", "line 5"]);
+    }
+
+    #[test]
+    fn view_follows_the_newest_lines() {
+        let log = filled(10, 100);
+        assert_eq!(log.visible(3).collect::<Vec<_>>(), ["line 8", "line 9", "line 10"]);
+        assert_eq!(log.visible(20).count(), 10);
+    }
+
+    #[test]
+    fn scrolling_back_pauses_following_until_the_bottom() {
+        let mut log = filled(10, 100);
+        log.page = 3;
+        log.page_up();
+        assert!(!log.is_following());
+        assert_eq!(log.visible(3).collect::<Vec<_>>(), ["line 5", "line 6", "line 7"]);
+
+        log.push("line 11");
+        assert_eq!(log.visible(3).collect::<Vec<_>>(), ["line 5", "line 6", "line 7"]);
+
+        for _ in 0..10 {
+            log.page_up();
+        }
+        assert_eq!(log.visible(3).next(), Some("line 1"));
+        for _ in 0..10 {
+            log.page_down();
+        }
+        assert!(log.is_following());
+        assert_eq!(log.visible(1).next(), Some("line 11"));
+
+        log.scroll_to_top();
+        assert_eq!(log.visible(3).collect::<Vec<_>>(), ["line 1", "line 2", "line 3"]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Reusable building blocks shared by several screens.
+
+pub mod log_buffer;
+pub mod popup;
+pub mod spinner;
+pub mod text_input;
@@ -0,0 +1,155 @@
+//! A message box drawn over whatever is already on screen.
+
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::theme::Theme;
+
+/// Share of the terminal the popup covers, in percent.
+const POPUP_WIDTH: u16 = 70;
+const POPUP_HEIGHT: u16 = 40;
+
+/// A rectangle covering `percent_x` by `percent_y` percent of `area`,
+/// centered in it.
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let width = (u32::from(area.width) * u32::from(percent_x.min(100)) / 100) as u16;
+    let height = (u32::from(area.height) * u32::from(percent_y.min(100)) / 100) as u16;
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Draw a bordered box with `title`, `message` and a `prompt` telling the
+/// user how to answer in the middle of the terminal.
+pub fn render_popup(f: &mut Frame, title: &str, message: &str, prompt: &str, theme: &Theme) {
+    let popup = centered_rect(POPUP_WIDTH, POPUP_HEIGHT, f.area());
+
+    let mut text: Vec<Line> = message.lines().map(|line| Line::from(line.to_string())).collect();
+    text.extend([
+        Line::from(""),
+        Line::from(Span::styled(
+            prompt.to_string(),
+            Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+        )),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.error))
+        .title(title.to_string());
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(block),
+        popup,
+    );
+}
+
+/// Draw `bindings`, pairs of keys and what they do, in a bordered box
+/// with `title` in the middle of the terminal, shrunk to fit them.
+pub fn render_key_help(f: &mut Frame, title: &str, bindings: &[(&str, &str)], theme: &Theme) {
+    let key_width = bindings.iter().map(|(key, _)| key.width()).max().unwrap_or(0);
+    let lines: Vec<Line> = bindings
+        .iter()
+        .map(|(key, action)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{key:>key_width$}  "),
+                    Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(action.to_string(), Style::default().fg(theme.fg)),
+            ])
+        })
+        .collect();
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0).max(title.width());
+    let width = (content_width as u16).saturating_add(4);
+    let height = (lines.len() as u16).saturating_add(2);
+    // The smallest share of the terminal that still fits `needed` cells.
+    let percent = |needed: u16, total: u16| {
+        (u32::from(needed) * 100).div_ceil(u32::from(total.max(1))).min(100) as u16
+    };
+    let area = f.area();
+    let popup = centered_rect(percent(width, area.width), percent(height, area.height), area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(title.to_string())
+        .padding(Padding::horizontal(1));
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn centered_rect_is_centered_and_sized() {
+        let area = Rect::new(0, 0, 80, 24);
+        assert_eq!(centered_rect(50, 50, area), Rect::new(20, 6, 40, 12));
+        assert_eq!(centered_rect(100, 100, area), area);
+
+        let offset = Rect::new(10, 5, 41, 11);
+        let rect = centered_rect(50, 50, offset);
+        assert_eq!((rect.width, rect.height), (20, 5));
+        assert_eq!(rect.x - offset.x, offset.right() - rect.right() - 1);
+        assert_eq!(rect.y - offset.y, offset.bottom() - rect.bottom());
+    }
+
+    #[test]
+    fn popup_is_centered_over_existing_content() {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|f| {
+                f.render_widget(Paragraph::new(vec![Line::from("x".repeat(80)); 24]), f.area());
+                render_popup(f, "Oops", "Something went wrong", "Press any key", &Theme::DARK);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..24)
+            .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+
+        let top = rows.iter().position(|row| row.contains('┌')).unwrap();
+        let bottom = rows.iter().rposition(|row| row.contains('└')).unwrap();
+        assert!(top.abs_diff(24 - 1 - bottom) <= 1);
+        assert_eq!(rows[top].find('┌'), Some(12));
+        assert!(rows[top].contains("Oops"));
+        assert!(rows.iter().any(|row| row.contains("Something went wrong")));
+        assert!(rows.iter().any(|row| row.contains("Press any key")));
+        assert_eq!(rows[0], "x".repeat(80));
+    }
+
+    #[test]
+    fn key_help_lines_up_the_keys() {
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        let bindings = [("↑/↓", "move"), ("Enter", "confirm"), ("q", "quit")];
+        terminal
+            .draw(|f| render_key_help(f, "Keys", &bindings, &Theme::DARK))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..12)
+            .map(|y| (0..60).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        let top = rows.iter().position(|row| row.contains("Keys")).unwrap();
+        assert_eq!(top, 3);
+        let column = |text: &str| {
+            let row = rows.iter().find(|row| row.contains(text)).unwrap();
+            row[..row.find(text).unwrap()].chars().count()
+        };
+        assert_eq!(column("move"), column("confirm"));
+        assert_eq!(column("quit"), column("confirm"));
+        assert_eq!(column("Enter") + "Enter".len(), column("q") + 1);
+    }
+}
@@ -0,0 +1,83 @@
+//! The activity indicator shown while something runs in the background.
+
+use std::time::Duration;
+
+/// Braille dots going round, for terminals with a Unicode font.
+pub const BRAILLE: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// The classic turning bar, which any terminal can draw.
+pub const ASCII: &[&str] = &["|", "/", "-", "\\"];
+
+/// A looping animation: which frame is showing and how long each one stays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spinner {
+    frames: &'static [&'static str],
+    interval: Duration,
+    index: usize,
+}
+
+impl Spinner {
+    /// A spinner over `frames`, advancing every `interval`. An empty set of
+    /// frames draws nothing.
+    pub const fn new(frames: &'static [&'static str], interval: Duration) -> Self {
+        Self { frames, interval, index: 0 }
+    }
+
+    pub const fn braille() -> Self {
+        Self::new(BRAILLE, Duration::from_millis(100))
+    }
+
+    pub const fn ascii() -> Self {
+        Self::new(ASCII, Duration::from_millis(200))
+    }
+
+    /// How long each frame shows, which is how often the main loop ticks.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Move on to the next frame, wrapping back to the first after the last.
+    pub fn tick(&mut self) {
+        self.index = (self.index + 1) % self.frames.len().max(1);
+    }
+
+    /// The frame to draw now.
+    pub fn frame(&self) -> &'static str {
+        self.frames.get(self.index).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick_cycles_through_the_frames_and_wraps() {
+        let mut spinner = Spinner::ascii();
+        let mut seen = vec![spinner.frame()];
+        for _ in 0..ASCII.len() {
+            spinner.tick();
+            seen.push(spinner.frame());
+        }
+        assert_eq!(seen, ["|", "/", "-", "\\", "|"]);
+        assert_eq!(spinner, Spinner::ascii());
+
+        let mut spinner = Spinner::braille();
+        for _ in 0..BRAILLE.len() + 2 {
+            spinner.tick();
+        }
+        assert_eq!(spinner.frame(), "⠹");
+    }
+
+    #[test]
+    fn frames_and_speed_are_configurable() {
+        let mut spinner = Spinner::new(&[".", "o", "O"], Duration::from_millis(50));
+        assert_eq!(spinner.interval(), Duration::from_millis(50));
+        spinner.tick();
+        assert_eq!(spinner.frame(), "o");
+
+        let mut empty = Spinner::new(&[], Duration::from_secs(1));
+        empty.tick();
+        assert_eq!(empty.frame(), "");
+    }
+}
@@ -0,0 +1,235 @@
+//! A single-line editable text field.
+
+use std::fmt;
+
+use ratatui::{
+    layout::{Position, Rect},
+    style::Style,
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::{secret, theme::Theme};
+
+/// The contents of a single-line text field and the cursor position in it.
+/// Not `Clone`, so what is typed into a password field has one copy, which
+/// is zeroed when the field is cleared, grows or is dropped.
+#[derive(Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    /// Cursor position, in characters from the start of `value`.
+    cursor: usize,
+    /// Character drawn in place of each typed one, for secrets.
+    mask: Option<char>,
+}
+
+/// Leaves a masked field's value out, so secrets don't end up in logs.
+impl fmt::Debug for TextInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self.mask {
+            Some(_) => &"<hidden>" as &dyn fmt::Debug,
+            None => &self.value,
+        };
+        f.debug_struct("TextInput")
+            .field("value", value)
+            .field("cursor", &self.cursor)
+            .field("mask", &self.mask)
+            .finish()
+    }
+}
+
+impl Drop for TextInput {
+    fn drop(&mut self) {
+        secret::zeroize(&mut self.value);
+    }
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A field that shows `•` for every character, for passwords.
+    pub fn masked() -> Self {
+        Self { value: String::new(), cursor: 0, mask: Some('•') }
+    }
+
+    /// Empty the field, overwriting its whole buffer with zeros so a secret
+    /// does not linger in freed memory.
+    pub fn zeroize(&mut self) {
+        secret::zeroize(&mut self.value);
+        self.cursor = 0;
+    }
+
+    /// Insert `c` at the cursor.
+    pub fn insert_char(&mut self, c: char) {
+        // Growing in place would leave the old buffer behind unzeroed.
+        if self.value.len() + c.len_utf8() > self.value.capacity() {
+            let mut grown = String::with_capacity((self.value.capacity() * 2).max(32));
+            grown.push_str(&self.value);
+            secret::zeroize(&mut std::mem::replace(&mut self.value, grown));
+        }
+        let at = self.byte_index(self.cursor);
+        self.value.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Delete the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let at = self.byte_index(self.cursor);
+        self.value.remove(at);
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.chars().count();
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replace the contents with `value`, leaving the cursor at the end.
+    pub fn set_value(&mut self, value: &str) {
+        secret::zeroize(&mut self.value);
+        self.value = value.to_string();
+        self.move_end();
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    /// The text as it should appear on screen.
+    fn display(&self) -> String {
+        match self.mask {
+            Some(mask) => mask.to_string().repeat(self.value.chars().count()),
+            None => self.value.clone(),
+        }
+    }
+
+    /// Draw the field in a bordered box. When `focused`, place the terminal
+    /// cursor at the edit position, where the terminal will blink it.
+    pub fn render(&self, f: &mut Frame, area: Rect, title: &str, focused: bool, theme: &Theme) {
+        let display = self.display();
+        let cursor_column = match self.mask {
+            Some(_) => self.cursor,
+            None => self.value[..self.byte_index(self.cursor)].width(),
+        };
+        let border = if focused { theme.accent } else { theme.border };
+        let field = Paragraph::new(display).style(Style::default().fg(theme.input)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border))
+                .title(theme.heading(title)),
+        );
+        f.render_widget(field, area);
+
+        if focused {
+            let inner_right = area.right().saturating_sub(2);
+            let x = (area.x + 1 + cursor_column as u16).min(inner_right);
+            f.set_cursor_position(Position::new(x, area.y + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::new();
+        for c in text.chars() {
+            input.insert_char(c);
+        }
+        input
+    }
+
+    #[test]
+    fn edits_value() {
+        let mut input = typed("host");
+        input.backspace();
+        assert_eq!(input.value(), "hos");
+    }
+
+    #[test]
+    fn backspace_on_empty_is_noop() {
+        let mut input = TextInput::new();
+        input.backspace();
+        assert_eq!(input.value(), "");
+    }
+
+    #[test]
+    fn edits_at_the_cursor() {
+        let mut input = typed("héllo");
+        input.move_left();
+        input.move_left();
+        input.insert_char('X');
+        assert_eq!(input.value(), "hélXlo");
+        input.move_home();
+        input.backspace();
+        input.insert_char('>');
+        assert_eq!(input.value(), ">hélXlo");
+        input.move_end();
+        input.move_right();
+        input.backspace();
+        assert_eq!(input.value(), ">hélXl");
+    }
+
+    #[test]
+    fn masked_field_hides_value() {
+        let mut input = TextInput::masked();
+        for c in "sécret".chars() {
+            input.insert_char(c);
+        }
+        assert_eq!(input.value(), "sécret");
+        assert_eq!(input.display(), "••••••");
+    }
+
+    #[test]
+    fn masked_value_stays_out_of_debug_output() {
+        let mut input = TextInput::masked();
+        input.set_value("hunter2");
+        assert!(!format!("{input:?}").contains("hunter2"));
+        let mut plain = TextInput::new();
+        plain.set_value("werkstatt");
+        assert!(format!("{plain:?}").contains("werkstatt"));
+    }
+
+    #[test]
+    fn long_values_survive_the_buffer_growing() {
+        let mut input = TextInput::masked();
+        let typed = "correct horse battery staple, and then some more words".repeat(3);
+        typed.chars().for_each(|c| input.insert_char(c));
+        assert_eq!(input.value(), typed);
+    }
+
+    #[test]
+    fn zeroize_clears_value() {
+        let mut input = TextInput::masked();
+        input.insert_char('x');
+        input.zeroize();
+        assert_eq!(input.value(), "");
+        input.insert_char('y');
+        assert_eq!(input.value(), "y");
+    }
+}